@@ -0,0 +1,215 @@
+use crate::error::Position;
+use std::fmt;
+
+/// A Jack class, the contents of one .jack file.
+///
+/// Expressions keep the flat shape of the grammar, `term (op term)*`,
+/// since Jack has no operator precedence and evaluates left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    pub position: Position,
+    pub name: String,
+    pub vars: Vec<ClassVarDec>,
+    pub subroutines: Vec<Subroutine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int,
+    Char,
+    Boolean,
+    Class(String),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Char => write!(f, "char"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Class(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClassVarKind {
+    Static,
+    Field,
+}
+
+/// `static int x, y;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassVarDec {
+    pub kind: ClassVarKind,
+    pub ty: Type,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubroutineKind {
+    Constructor,
+    Function,
+    Method,
+}
+
+/// A constructor, function or method; a `void` one has no return type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subroutine {
+    pub position: Position,
+    pub kind: SubroutineKind,
+    pub return_type: Option<Type>,
+    pub name: String,
+    pub parameters: Vec<(Type, String)>,
+    pub vars: Vec<VarDec>,
+    pub statements: Vec<Statement>,
+}
+
+/// `var int x, y;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarDec {
+    pub ty: Type,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// `let name[index] = value;`
+    Let {
+        position: Position,
+        name: String,
+        index: Option<Expression>,
+        value: Expression,
+    },
+    If {
+        condition: Expression,
+        then: Vec<Statement>,
+        otherwise: Option<Vec<Statement>>,
+    },
+    While {
+        condition: Expression,
+        body: Vec<Statement>,
+    },
+    Do(SubroutineCall),
+    Return {
+        position: Position,
+        value: Option<Expression>,
+    },
+}
+
+/// `term (op term)*`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    pub term: Term,
+    pub rest: Vec<(BinaryOp, Term)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    IntegerConstant(u16),
+    StringConstant(String),
+    KeywordConstant(KeywordConstant),
+    Variable(Position, String),
+    /// `name[index]`
+    Index(Position, String, Box<Expression>),
+    Call(SubroutineCall),
+    Parenthesized(Box<Expression>),
+    Unary(UnaryOp, Box<Term>),
+}
+
+/// `name(arguments)` or `receiver.name(arguments)`, where the receiver
+/// is a variable or a class name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubroutineCall {
+    pub position: Position,
+    pub receiver: Option<String>,
+    pub name: String,
+    pub arguments: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeywordConstant {
+    True,
+    False,
+    Null,
+    This,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    And,
+    Or,
+    Lt,
+    Gt,
+    Eq,
+}
+
+const BINARY_OPS: [(char, BinaryOp); 9] = [
+    ('+', BinaryOp::Add),
+    ('-', BinaryOp::Sub),
+    ('*', BinaryOp::Mul),
+    ('/', BinaryOp::Div),
+    ('&', BinaryOp::And),
+    ('|', BinaryOp::Or),
+    ('<', BinaryOp::Lt),
+    ('>', BinaryOp::Gt),
+    ('=', BinaryOp::Eq),
+];
+
+impl BinaryOp {
+    pub fn parse(symbol: char) -> Option<BinaryOp> {
+        BINARY_OPS
+            .iter()
+            .find(|(c, _)| *c == symbol)
+            .map(|&(_, op)| op)
+    }
+
+    pub fn symbol(self) -> char {
+        BINARY_OPS.iter().find(|(_, op)| *op == self).unwrap().0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+impl UnaryOp {
+    pub fn parse(symbol: char) -> Option<UnaryOp> {
+        match symbol {
+            '-' => Some(UnaryOp::Neg),
+            '~' => Some(UnaryOp::Not),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            UnaryOp::Neg => '-',
+            UnaryOp::Not => '~',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators() {
+        for (symbol, op) in BINARY_OPS {
+            assert_eq!(BinaryOp::parse(symbol), Some(op));
+            assert_eq!(op.symbol(), symbol);
+        }
+        assert_eq!(BinaryOp::parse('~'), None);
+        for op in [UnaryOp::Neg, UnaryOp::Not] {
+            assert_eq!(UnaryOp::parse(op.symbol()), Some(op));
+        }
+        assert_eq!(UnaryOp::parse('+'), None);
+    }
+}
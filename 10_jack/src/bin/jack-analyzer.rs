@@ -0,0 +1,72 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "usage: jack-analyzer <file.jack | directory>
+
+Writes the tokens of every .jack file to XxxT.xml and its parse tree to
+Xxx.xml next to it, in the format of the course's comparison files.";
+
+fn fail(path: &Path, e: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", path.display(), e);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let input = match args.as_slice() {
+        [input] => PathBuf::from(input),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut paths = if input.is_dir() {
+        fs::read_dir(&input)
+            .unwrap_or_else(|e| fail(&input, e))
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "jack"))
+            .collect()
+    } else {
+        vec![input]
+    };
+    paths.sort();
+
+    let mut failed = false;
+    for path in paths.iter() {
+        let source = fs::read_to_string(path).unwrap_or_else(|e| fail(path, e));
+        let tokens = match jack::tokenizer::tokenize(&source) {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                for e in errors {
+                    eprintln!("{}:{}", path.display(), e);
+                }
+                failed = true;
+                continue;
+            }
+        };
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let output = path.with_file_name(format!("{}T.xml", stem));
+        if let Err(e) = fs::write(&output, jack::xml::tokens(&tokens)) {
+            fail(&output, e);
+        }
+
+        let class = match jack::parser::Parser::new(tokens).class() {
+            Ok(class) => class,
+            Err(e) => {
+                eprintln!("{}:{}", path.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        let output = path.with_extension("xml");
+        if let Err(e) = fs::write(&output, jack::xml::class(&class)) {
+            fail(&output, e);
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
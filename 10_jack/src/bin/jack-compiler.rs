@@ -0,0 +1,74 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "usage: jack-compiler <file.jack | directory> [--os]
+
+Compiles every .jack file into Xxx.vm next to it. The classes of a
+directory are checked together, so calls between them are checked too.
+
+With --os, the classes of the OS the program doesn't define itself are
+compiled along and written to the same directory. Translate it with
+hack-vm --shared for the program to fit in the ROM.";
+
+fn fail(path: &Path, e: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", path.display(), e);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, os) = match args.as_slice() {
+        [input] => (PathBuf::from(input), false),
+        [input, os] if os == "--os" => (PathBuf::from(input), true),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let directory = match input.is_dir() {
+        true => input.clone(),
+        false => input.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+    let mut paths: Vec<PathBuf> = if input.is_dir() {
+        fs::read_dir(&input)
+            .unwrap_or_else(|e| fail(&input, e))
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "jack"))
+            .collect()
+    } else {
+        vec![input]
+    };
+    paths.sort();
+
+    let mut sources = Vec::new();
+    for path in paths.iter() {
+        let source = fs::read_to_string(path).unwrap_or_else(|e| fail(path, e));
+        sources.push((path.display().to_string(), source));
+    }
+    let mut files: Vec<(&str, &str)> = sources
+        .iter()
+        .map(|(p, s)| (p.as_str(), s.as_str()))
+        .collect();
+    if os {
+        files = jack::os::link(&files);
+    }
+    let compiled = jack::compile_program(&files).unwrap_or_else(|errors| {
+        for (file, e) in errors {
+            eprintln!("{}:{}", file, e);
+        }
+        process::exit(1);
+    });
+
+    for (k, (name, vm)) in compiled.iter().enumerate() {
+        let output = match paths.get(k) {
+            Some(path) => path.with_extension("vm"),
+            None => directory.join(format!("{}.vm", name)),
+        };
+        if let Err(e) = fs::write(&output, vm) {
+            fail(&output, e);
+        }
+    }
+}
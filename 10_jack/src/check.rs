@@ -0,0 +1,410 @@
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Position};
+use crate::symbols::{Kind, SymbolTable};
+use std::collections::HashMap;
+
+/// Checks the classes of a program for the mistakes the code generator
+/// would silently turn into broken VM code, returning the errors of each
+/// class with its name.
+///
+/// Calls are only checked against the classes given, so calls into
+/// classes that aren't part of the program (the OS ones, unless they are
+/// compiled along) are taken as they are.
+pub fn check(classes: &[Class]) -> Vec<(String, Error)> {
+    let classes: HashMap<&str, &Class> = classes.iter().map(|c| (c.name.as_str(), c)).collect();
+    let mut names: Vec<&&str> = classes.keys().collect();
+    names.sort();
+
+    let mut errors = Vec::new();
+    for name in names {
+        let class = classes[*name];
+        let mut symbols = SymbolTable::new();
+        for var in class.vars.iter() {
+            let kind = match var.kind {
+                ClassVarKind::Static => Kind::Static,
+                ClassVarKind::Field => Kind::Field,
+            };
+            for name in var.names.iter() {
+                symbols.define(name, &var.ty, kind);
+            }
+        }
+
+        let mut class_errors = Vec::new();
+        for subroutine in class.subroutines.iter() {
+            let mut checker = Checker {
+                classes: &classes,
+                class,
+                subroutine,
+                symbols: symbols.clone(),
+                errors: &mut class_errors,
+            };
+            checker.subroutine();
+        }
+        class_errors.sort_by_key(|e| e.position);
+        errors.extend(class_errors.into_iter().map(|e| (class.name.clone(), e)));
+    }
+    errors
+}
+
+/// Whether every path through the statements ends in a return.
+fn returns(statements: &[Statement]) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::Return { .. } => true,
+        Statement::If {
+            then,
+            otherwise: Some(otherwise),
+            ..
+        } => returns(then) && returns(otherwise),
+        _ => false,
+    })
+}
+
+struct Checker<'a, 'e> {
+    classes: &'a HashMap<&'a str, &'a Class>,
+    class: &'a Class,
+    subroutine: &'a Subroutine,
+    symbols: SymbolTable,
+    errors: &'e mut Vec<Error>,
+}
+
+impl<'a> Checker<'a, '_> {
+    fn error(&mut self, position: Position, kind: ErrorKind) {
+        self.errors.push(Error { position, kind });
+    }
+
+    fn name(&self) -> String {
+        format!("{}.{}", self.class.name, self.subroutine.name)
+    }
+
+    fn subroutine(&mut self) {
+        let subroutine = self.subroutine;
+        self.symbols.start_subroutine();
+        for (ty, name) in subroutine.parameters.iter() {
+            self.symbols.define(name, ty, Kind::Argument);
+        }
+        for var in subroutine.vars.iter() {
+            for name in var.names.iter() {
+                self.symbols.define(name, &var.ty, Kind::Var);
+            }
+        }
+        self.statements(&subroutine.statements);
+        if !returns(&subroutine.statements) {
+            let kind = ErrorKind::MissingReturn(self.name());
+            self.error(subroutine.position, kind);
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn variable(&mut self, position: Position, name: &str) {
+        if self.symbols.get(name).is_none() {
+            self.error(position, ErrorKind::Undeclared(name.to_string()));
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                position,
+                name,
+                index,
+                value,
+            } => {
+                self.variable(*position, name);
+                if let Some(index) = index {
+                    self.expression(index);
+                }
+                self.expression(value);
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.expression(condition);
+                self.statements(then);
+                if let Some(otherwise) = otherwise {
+                    self.statements(otherwise);
+                }
+            }
+            Statement::While { condition, body } => {
+                self.expression(condition);
+                self.statements(body);
+            }
+            Statement::Do(call) => {
+                self.call(call);
+            }
+            Statement::Return { position, value } => {
+                match (&self.subroutine.return_type, value) {
+                    (None, Some(_)) => {
+                        let kind = ErrorKind::ReturnValueFromVoid(self.name());
+                        self.error(*position, kind);
+                    }
+                    (Some(_), None) => {
+                        let kind = ErrorKind::MissingReturnValue(self.name());
+                        self.error(*position, kind);
+                    }
+                    _ => {}
+                }
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+            }
+        }
+    }
+
+    /// Checks the call against the subroutine it calls, if it's in one of
+    /// the classes of the program, and returns that subroutine with its
+    /// full name.
+    fn call(&mut self, call: &SubroutineCall) -> Option<(String, &'a Subroutine)> {
+        for argument in call.arguments.iter() {
+            self.expression(argument);
+        }
+
+        // a variable or this is passed as the object, a class name passes none
+        let (class, on_object) = match &call.receiver {
+            None => (Some(self.class), true),
+            Some(receiver) => match self.symbols.get(receiver) {
+                Some(symbol) => match &symbol.ty {
+                    Type::Class(name) => (self.classes.get(name.as_str()).copied(), true),
+                    ty => {
+                        let kind = ErrorKind::PrimitiveReceiver {
+                            name: receiver.clone(),
+                            ty: ty.to_string(),
+                        };
+                        self.error(call.position, kind);
+                        return None;
+                    }
+                },
+                None => (self.classes.get(receiver.as_str()).copied(), false),
+            },
+        };
+        let class = class?;
+        let name = format!("{}.{}", class.name, call.name);
+
+        let Some(subroutine) = class.subroutines.iter().find(|s| s.name == call.name) else {
+            let kind = ErrorKind::UnknownSubroutine {
+                class: class.name.clone(),
+                name: call.name.clone(),
+            };
+            self.error(call.position, kind);
+            return None;
+        };
+        let method = subroutine.kind == SubroutineKind::Method;
+        if method && !on_object {
+            self.error(call.position, ErrorKind::MethodOnClass(name.clone()));
+        } else if !method && on_object {
+            self.error(call.position, ErrorKind::NotAMethod(name.clone()));
+        } else if method
+            && call.receiver.is_none()
+            && self.subroutine.kind == SubroutineKind::Function
+        {
+            self.error(call.position, ErrorKind::MethodWithoutObject(name.clone()));
+        }
+        let expected = subroutine.parameters.len();
+        let found = call.arguments.len();
+        if expected != found {
+            let kind = ErrorKind::WrongArgumentCount {
+                subroutine: name.clone(),
+                expected,
+                found,
+            };
+            self.error(call.position, kind);
+        }
+        Some((name, subroutine))
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        self.term(&expression.term);
+        for (_, term) in expression.rest.iter() {
+            self.term(term);
+        }
+    }
+
+    fn term(&mut self, term: &Term) {
+        match term {
+            Term::Variable(position, name) => self.variable(*position, name),
+            Term::Index(position, name, index) => {
+                self.variable(*position, name);
+                self.expression(index);
+            }
+            Term::Call(call) => {
+                if let Some((name, subroutine)) = self.call(call)
+                    && subroutine.return_type.is_none()
+                {
+                    self.error(call.position, ErrorKind::VoidAsValue(name));
+                }
+            }
+            Term::Parenthesized(expression) => self.expression(expression),
+            Term::Unary(_, term) => self.term(term),
+            Term::IntegerConstant(_) | Term::StringConstant(_) | Term::KeywordConstant(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn errors(sources: &[&str]) -> Vec<String> {
+        let classes: Vec<Class> = sources.iter().map(|s| parse(s).unwrap()).collect();
+        check(&classes)
+            .iter()
+            .map(|(class, e)| format!("{}:{}", class, e))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_program() {
+        let main = "
+class Main {
+    function void main() {
+        var Point p;
+        let p = Point.new(1, 2);
+        do p.move(3);
+        do Output.printInt(p.getX() + Main.twice(4));
+        return;
+    }
+    function int twice(int n) {
+        if (n > 0) { return n + n; } else { return 0; }
+    }
+}";
+        let point = "
+class Point {
+    field int x, y;
+    constructor Point new(int ax, int ay) { let x = ax; let y = ay; return this; }
+    method void move(int dx) { let x = x + dx; do draw(); return; }
+    method void draw() { return; }
+    method int getX() { return x; }
+}";
+        assert_eq!(errors(&[main, point]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_undeclared() {
+        let main = "
+class Main {
+    static int count;
+    function void main(int n) {
+        var int i;
+        let i = n + count;
+        let j = k[i];
+        return;
+    }
+}";
+        assert_eq!(
+            errors(&[main]),
+            [
+                "Main:7:13: cannot find 'j' in this scope",
+                "Main:7:17: cannot find 'k' in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calls() {
+        let main = "
+class Main {
+    function void main() {
+        var Point p;
+        do Main.missing();
+        do p.move();
+        do Point.new(1, 2, 3);
+        do helper();
+        let p = p.move(1);
+        do Output.anything(1, 2);
+        return;
+    }
+    method void helper() { return; }
+}";
+        let point = "
+class Point {
+    constructor Point new(int x, int y) { return this; }
+    method void move(int dx) { return; }
+}";
+        assert_eq!(
+            errors(&[main, point]),
+            [
+                "Main:5:12: class 'Main' has no subroutine 'missing'",
+                "Main:6:12: 'Point.move' expects 1 argument, found 0",
+                "Main:7:12: 'Point.new' expects 2 arguments, found 3",
+                "Main:8:12: method 'Main.helper' can't be called without an object from a function",
+                "Main:9:17: 'Point.move' returns void and has no value",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_call_kinds() {
+        let main = "
+class Main {
+    field int n;
+    function void main() {
+        var Main m;
+        var int x;
+        do Main.bump(3);
+        do m.helper(1);
+        do x.foo();
+        do Main.helper(1);
+        do m.bump(1);
+        return;
+    }
+    method void bump(int by) {
+        do helper(2);
+        do bump(by);
+        return;
+    }
+    function void helper(int k) { return; }
+}";
+        assert_eq!(
+            errors(&[main]),
+            [
+                "Main:7:12: method 'Main.bump' must be called on an object",
+                "Main:8:12: 'Main.helper' is not a method and must be called as 'Main.helper'",
+                "Main:9:12: 'x' is of type int, which has no subroutines",
+                "Main:15:12: 'Main.helper' is not a method and must be called as 'Main.helper'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_returns() {
+        let main = "
+class Main {
+    function int noReturn() {
+        var int i;
+        let i = 1;
+    }
+    function int halfReturn(boolean b) {
+        if (b) { return 1; }
+    }
+    function int loopReturn() {
+        while (true) { return 1; }
+    }
+    function int bothReturn(boolean b) {
+        if (b) { return 1; } else { if (~b) { return 2; } else { return 3; } }
+    }
+    function void noValue() {
+        return 1;
+    }
+    function int value() {
+        return;
+    }
+}";
+        assert_eq!(
+            errors(&[main]),
+            [
+                "Main:3:18: 'Main.noReturn' can reach its end without returning",
+                "Main:7:18: 'Main.halfReturn' can reach its end without returning",
+                "Main:10:18: 'Main.loopReturn' can reach its end without returning",
+                "Main:17:9: void subroutine 'Main.noValue' can't return a value",
+                "Main:20:9: 'Main.value' must return a value",
+            ]
+        );
+    }
+}
@@ -0,0 +1,560 @@
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Position};
+use crate::symbols::{Kind, SymbolTable};
+use std::fmt::Write;
+
+/// Compiles a class into VM code, one function per subroutine.
+///
+/// Labels are numbered per subroutine and named like the ones of the
+/// course's compiler, so its output can be compared against ours.
+pub fn compile(class: &Class) -> Result<String, Vec<Error>> {
+    let mut compiler = Compiler {
+        class: class.name.clone(),
+        symbols: SymbolTable::new(),
+        ifs: 0,
+        whiles: 0,
+        out: String::new(),
+        errors: Vec::new(),
+    };
+    compiler.class(class);
+    if compiler.errors.is_empty() {
+        Ok(compiler.out)
+    } else {
+        Err(compiler.errors)
+    }
+}
+
+struct Compiler {
+    class: String,
+    symbols: SymbolTable,
+    ifs: usize,
+    whiles: usize,
+    out: String,
+    errors: Vec<Error>,
+}
+
+impl Compiler {
+    fn write(&mut self, command: &str) {
+        self.out.push_str(command);
+        self.out.push('\n');
+    }
+
+    fn push(&mut self, segment: &str, index: u16) {
+        writeln!(self.out, "push {} {}", segment, index).unwrap();
+    }
+
+    fn pop(&mut self, segment: &str, index: u16) {
+        writeln!(self.out, "pop {} {}", segment, index).unwrap();
+    }
+
+    fn call(&mut self, function: &str, arguments: usize) {
+        writeln!(self.out, "call {} {}", function, arguments).unwrap();
+    }
+
+    /// The segment and index of a variable.
+    fn variable(&mut self, position: Position, name: &str) -> (&'static str, u16) {
+        match self.symbols.get(name) {
+            Some(symbol) => (symbol.kind.segment(), symbol.index),
+            None => {
+                self.errors.push(Error {
+                    position,
+                    kind: ErrorKind::Undeclared(name.to_string()),
+                });
+                ("constant", 0)
+            }
+        }
+    }
+
+    fn class(&mut self, class: &Class) {
+        for var in class.vars.iter() {
+            let kind = match var.kind {
+                ClassVarKind::Static => Kind::Static,
+                ClassVarKind::Field => Kind::Field,
+            };
+            for name in var.names.iter() {
+                self.symbols.define(name, &var.ty, kind);
+            }
+        }
+        for subroutine in class.subroutines.iter() {
+            self.subroutine(subroutine);
+        }
+    }
+
+    fn subroutine(&mut self, subroutine: &Subroutine) {
+        self.symbols.start_subroutine();
+        self.ifs = 0;
+        self.whiles = 0;
+        if subroutine.kind == SubroutineKind::Method {
+            let this = Type::Class(self.class.clone());
+            self.symbols.define("this", &this, Kind::Argument);
+        }
+        for (ty, name) in subroutine.parameters.iter() {
+            self.symbols.define(name, ty, Kind::Argument);
+        }
+        for var in subroutine.vars.iter() {
+            for name in var.names.iter() {
+                self.symbols.define(name, &var.ty, Kind::Var);
+            }
+        }
+
+        let locals = self.symbols.count(Kind::Var);
+        writeln!(
+            self.out,
+            "function {}.{} {}",
+            self.class, subroutine.name, locals
+        )
+        .unwrap();
+        match subroutine.kind {
+            SubroutineKind::Constructor => {
+                let fields = self.symbols.count(Kind::Field);
+                self.push("constant", fields);
+                self.call("Memory.alloc", 1);
+                self.pop("pointer", 0);
+            }
+            SubroutineKind::Method => {
+                self.push("argument", 0);
+                self.pop("pointer", 0);
+            }
+            SubroutineKind::Function => {}
+        }
+        self.statements(&subroutine.statements);
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                position,
+                name,
+                index: None,
+                value,
+            } => {
+                self.expression(value);
+                let (segment, index) = self.variable(*position, name);
+                self.pop(segment, index);
+            }
+            Statement::Let {
+                position,
+                name,
+                index: Some(index),
+                value,
+            } => {
+                let (segment, n) = self.variable(*position, name);
+                self.push(segment, n);
+                self.expression(index);
+                self.write("add");
+                self.expression(value);
+                self.pop("temp", 0);
+                self.pop("pointer", 1);
+                self.push("temp", 0);
+                self.pop("that", 0);
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let n = self.ifs;
+                self.ifs += 1;
+                self.expression(condition);
+                writeln!(self.out, "if-goto IF_TRUE{}", n).unwrap();
+                writeln!(self.out, "goto IF_FALSE{}", n).unwrap();
+                writeln!(self.out, "label IF_TRUE{}", n).unwrap();
+                self.statements(then);
+                match otherwise {
+                    Some(otherwise) => {
+                        writeln!(self.out, "goto IF_END{}", n).unwrap();
+                        writeln!(self.out, "label IF_FALSE{}", n).unwrap();
+                        self.statements(otherwise);
+                        writeln!(self.out, "label IF_END{}", n).unwrap();
+                    }
+                    None => writeln!(self.out, "label IF_FALSE{}", n).unwrap(),
+                }
+            }
+            Statement::While { condition, body } => {
+                let n = self.whiles;
+                self.whiles += 1;
+                writeln!(self.out, "label WHILE_EXP{}", n).unwrap();
+                self.expression(condition);
+                self.write("not");
+                writeln!(self.out, "if-goto WHILE_END{}", n).unwrap();
+                self.statements(body);
+                writeln!(self.out, "goto WHILE_EXP{}", n).unwrap();
+                writeln!(self.out, "label WHILE_END{}", n).unwrap();
+            }
+            Statement::Do(call) => {
+                self.subroutine_call(call);
+                self.pop("temp", 0);
+            }
+            Statement::Return { value, .. } => {
+                match value {
+                    Some(value) => self.expression(value),
+                    None => self.push("constant", 0),
+                }
+                self.write("return");
+            }
+        }
+    }
+
+    /// Methods get the object they're called on as argument 0: `this` for
+    /// unqualified names, the variable for `variable.name(...)`.
+    /// Any other `Name.name(...)` calls a function or a constructor.
+    fn subroutine_call(&mut self, call: &SubroutineCall) {
+        let arguments = call.arguments.len();
+        let (class, arguments) = match &call.receiver {
+            None => {
+                self.push("pointer", 0);
+                (self.class.clone(), arguments + 1)
+            }
+            Some(receiver) => match self.symbols.get(receiver) {
+                Some(symbol) => {
+                    let (segment, index, ty) =
+                        (symbol.kind.segment(), symbol.index, symbol.ty.to_string());
+                    self.push(segment, index);
+                    (ty, arguments + 1)
+                }
+                None => (receiver.clone(), arguments),
+            },
+        };
+        for argument in call.arguments.iter() {
+            self.expression(argument);
+        }
+        self.call(&format!("{}.{}", class, call.name), arguments);
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        self.term(&expression.term);
+        for (op, term) in expression.rest.iter() {
+            self.term(term);
+            match op {
+                BinaryOp::Add => self.write("add"),
+                BinaryOp::Sub => self.write("sub"),
+                BinaryOp::Mul => self.call("Math.multiply", 2),
+                BinaryOp::Div => self.call("Math.divide", 2),
+                BinaryOp::And => self.write("and"),
+                BinaryOp::Or => self.write("or"),
+                BinaryOp::Lt => self.write("lt"),
+                BinaryOp::Gt => self.write("gt"),
+                BinaryOp::Eq => self.write("eq"),
+            }
+        }
+    }
+
+    fn term(&mut self, term: &Term) {
+        match term {
+            Term::IntegerConstant(n) => self.push("constant", *n),
+            Term::StringConstant(s) => {
+                self.push("constant", s.chars().count() as u16);
+                self.call("String.new", 1);
+                for c in s.chars() {
+                    self.push("constant", c as u16);
+                    self.call("String.appendChar", 2);
+                }
+            }
+            Term::KeywordConstant(KeywordConstant::True) => {
+                self.push("constant", 0);
+                self.write("not");
+            }
+            Term::KeywordConstant(KeywordConstant::False | KeywordConstant::Null) => {
+                self.push("constant", 0);
+            }
+            Term::KeywordConstant(KeywordConstant::This) => self.push("pointer", 0),
+            Term::Variable(position, name) => {
+                let (segment, index) = self.variable(*position, name);
+                self.push(segment, index);
+            }
+            Term::Index(position, name, index) => {
+                let (segment, n) = self.variable(*position, name);
+                self.push(segment, n);
+                self.expression(index);
+                self.write("add");
+                self.pop("pointer", 1);
+                self.push("that", 0);
+            }
+            Term::Call(call) => self.subroutine_call(call),
+            Term::Parenthesized(expression) => self.expression(expression),
+            Term::Unary(op, term) => {
+                self.term(term);
+                match op {
+                    UnaryOp::Neg => self.write("neg"),
+                    UnaryOp::Not => self.write("not"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use computer::computer::Computer;
+    use computer::rom::ROM32K;
+
+    fn vm(source: &str) -> String {
+        compile(&parse(source).unwrap()).unwrap()
+    }
+
+    fn lines(vm: &str) -> Vec<&str> {
+        vm.lines().collect()
+    }
+
+    /// A bump allocator and a Sys.init that runs Main.main and halts.
+    const RUNTIME: [(&str, &str); 2] = [
+        (
+            "Memory",
+            "class Memory {
+                static int free;
+                function int alloc(int size) {
+                    var int block;
+                    if (free = 0) { let free = 2048; }
+                    let block = free;
+                    let free = free + size;
+                    return block;
+                }
+            }",
+        ),
+        (
+            "Sys",
+            "class Sys {
+                function void init() {
+                    do Main.main();
+                    while (true) {}
+                    return;
+                }
+            }",
+        ),
+    ];
+
+    /// Compiles the classes with the runtime, then runs them on the Hack computer.
+    fn run(classes: &[(&str, &str)], cycles: usize) -> Computer {
+        let compiled: Vec<(String, String)> = classes
+            .iter()
+            .chain(RUNTIME.iter())
+            .map(|(name, source)| (name.to_string(), vm(source)))
+            .collect();
+        let files: Vec<(&str, &str)> = compiled
+            .iter()
+            .map(|(n, s)| (n.as_str(), s.as_str()))
+            .collect();
+        let asm = vm_translator::translate_program(&files).unwrap();
+        let words = assembler::assemble(&asm).unwrap();
+        let rom = ROM32K::from_hack(&assembler::to_hack(&words)).unwrap();
+        let mut computer = Computer::new(rom);
+        computer.step(cycles);
+        computer
+    }
+
+    #[test]
+    fn test_function() {
+        let source = "
+class Main {
+    function int sum(int n) {
+        var int i, total;
+        let total = 0;
+        while (i < n) {
+            let i = i + 1;
+            let total = total + i;
+        }
+        return total;
+    }
+}";
+        assert_eq!(
+            lines(&vm(source)),
+            [
+                "function Main.sum 2",
+                "push constant 0",
+                "pop local 1",
+                "label WHILE_EXP0",
+                "push local 0",
+                "push argument 0",
+                "lt",
+                "not",
+                "if-goto WHILE_END0",
+                "push local 0",
+                "push constant 1",
+                "add",
+                "pop local 0",
+                "push local 1",
+                "push local 0",
+                "add",
+                "pop local 1",
+                "goto WHILE_EXP0",
+                "label WHILE_END0",
+                "push local 1",
+                "return",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_objects() {
+        let source = "
+class Point {
+    field int x, y;
+    static int count;
+    constructor Point new(int ax, int ay) {
+        let x = ax;
+        let y = ay;
+        let count = count + 1;
+        return this;
+    }
+    method int plus(Point other) {
+        return x + other.getX() + sum();
+    }
+    method void dispose() {
+        do Memory.deAlloc(this);
+        return;
+    }
+}";
+        let vm = vm(source);
+        let vm = lines(&vm);
+        assert_eq!(
+            vm[..9],
+            [
+                "function Point.new 0",
+                "push constant 2",
+                "call Memory.alloc 1",
+                "pop pointer 0",
+                "push argument 0",
+                "pop this 0",
+                "push argument 1",
+                "pop this 1",
+                "push static 0",
+            ]
+        );
+        assert_eq!(
+            vm[14..],
+            [
+                "function Point.plus 0",
+                "push argument 0",
+                "pop pointer 0",
+                "push this 0",
+                "push argument 1",
+                "call Point.getX 1",
+                "add",
+                "push pointer 0",
+                "call Point.sum 1",
+                "add",
+                "return",
+                "function Point.dispose 0",
+                "push argument 0",
+                "pop pointer 0",
+                "push pointer 0",
+                "call Memory.deAlloc 1",
+                "pop temp 0",
+                "push constant 0",
+                "return",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrays_and_strings() {
+        let source = "
+class Main {
+    function void main(Array a) {
+        let a[1] = a[0];
+        do Output.printString(\"Hi\");
+        if (~a) { return; } else { return; }
+    }
+}";
+        assert_eq!(
+            lines(&vm(source))[1..],
+            [
+                "push argument 0",
+                "push constant 1",
+                "add",
+                "push argument 0",
+                "push constant 0",
+                "add",
+                "pop pointer 1",
+                "push that 0",
+                "pop temp 0",
+                "pop pointer 1",
+                "push temp 0",
+                "pop that 0",
+                "push constant 2",
+                "call String.new 1",
+                "push constant 72",
+                "call String.appendChar 2",
+                "push constant 105",
+                "call String.appendChar 2",
+                "call Output.printString 1",
+                "pop temp 0",
+                "push argument 0",
+                "not",
+                "if-goto IF_TRUE0",
+                "goto IF_FALSE0",
+                "label IF_TRUE0",
+                "push constant 0",
+                "return",
+                "goto IF_END0",
+                "label IF_FALSE0",
+                "push constant 0",
+                "return",
+                "label IF_END0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undeclared() {
+        let class = parse("class Main { function int f() { let x = y; return z[0]; } }").unwrap();
+        let errors: Vec<String> = compile(&class)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "1:41: cannot find 'y' in this scope",
+                "1:37: cannot find 'x' in this scope",
+                "1:51: cannot find 'z' in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        // results are written from address 8000 on
+        let main = "
+class Main {
+    function void main() {
+        var Array out;
+        var Counter c;
+        let out = 8000;
+        let out[0] = Main.fib(7);
+        let c = Counter.new(5);
+        do c.add(-3);
+        let out[1] = c.get();
+        let out[2] = (2 + 3) - 1 & 6;
+        let out[3] = ~(1 < 2) | (2 > 1);
+        return;
+    }
+
+    function int fib(int n) {
+        if (n < 2) { return n; }
+        return Main.fib(n - 1) + Main.fib(n - 2);
+    }
+}";
+        let counter = "
+class Counter {
+    field int value;
+    constructor Counter new(int start) { let value = start; return this; }
+    method void add(int n) { let value = value + n; return; }
+    method int get() { return value; }
+}";
+        let computer = run(&[("Main", main), ("Counter", counter)], 10000);
+        assert_eq!(computer.peek(8000), 13);
+        assert_eq!(computer.peek(8001), 2);
+        assert_eq!(computer.peek(8002), 4);
+        assert_eq!(computer.peek(8003), 0xFFFF);
+    }
+}
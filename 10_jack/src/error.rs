@@ -0,0 +1,119 @@
+use std::fmt;
+
+/// Where a token starts, lines and columns start at 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    UnterminatedString,
+    UnterminatedComment,
+    IntegerTooLarge(String),
+    Expected {
+        expected: String,
+        found: String,
+    },
+    Undeclared(String),
+    UnknownSubroutine {
+        class: String,
+        name: String,
+    },
+    MethodWithoutObject(String),
+    MethodOnClass(String),
+    NotAMethod(String),
+    PrimitiveReceiver {
+        name: String,
+        ty: String,
+    },
+    WrongArgumentCount {
+        subroutine: String,
+        expected: usize,
+        found: usize,
+    },
+    VoidAsValue(String),
+    MissingReturn(String),
+    ReturnValueFromVoid(String),
+    MissingReturnValue(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+            ErrorKind::UnterminatedString => {
+                write!(f, "string constant is missing its closing '\"'")
+            }
+            ErrorKind::UnterminatedComment => write!(f, "comment is missing its closing '*/'"),
+            ErrorKind::IntegerTooLarge(n) => {
+                write!(f, "integer constant {} is larger than 32767", n)
+            }
+            ErrorKind::Expected { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::Undeclared(name) => write!(f, "cannot find '{}' in this scope", name),
+            ErrorKind::UnknownSubroutine { class, name } => {
+                write!(f, "class '{}' has no subroutine '{}'", class, name)
+            }
+            ErrorKind::MethodWithoutObject(name) => write!(
+                f,
+                "method '{}' can't be called without an object from a function",
+                name
+            ),
+            ErrorKind::MethodOnClass(name) => {
+                write!(f, "method '{}' must be called on an object", name)
+            }
+            ErrorKind::NotAMethod(name) => write!(
+                f,
+                "'{}' is not a method and must be called as '{}'",
+                name, name
+            ),
+            ErrorKind::PrimitiveReceiver { name, ty } => {
+                write!(f, "'{}' is of type {}, which has no subroutines", name, ty)
+            }
+            ErrorKind::WrongArgumentCount {
+                subroutine,
+                expected,
+                found,
+            } => {
+                let s = if *expected == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "'{}' expects {} argument{}, found {}",
+                    subroutine, expected, s, found
+                )
+            }
+            ErrorKind::VoidAsValue(name) => write!(f, "'{}' returns void and has no value", name),
+            ErrorKind::MissingReturn(name) => {
+                write!(f, "'{}' can reach its end without returning", name)
+            }
+            ErrorKind::ReturnValueFromVoid(name) => {
+                write!(f, "void subroutine '{}' can't return a value", name)
+            }
+            ErrorKind::MissingReturnValue(name) => write!(f, "'{}' must return a value", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub position: Position,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,105 @@
+pub mod ast;
+pub mod check;
+pub mod codegen;
+pub mod error;
+pub mod os;
+pub mod parser;
+pub mod symbols;
+pub mod tokenizer;
+pub mod xml;
+
+use error::Error;
+
+/// An error with the name of the file it's in.
+pub type FileError = (String, Error);
+
+/// Compiles the source of one class into VM code.
+pub fn compile(source: &str) -> Result<String, Vec<Error>> {
+    let class = parser::parse(source)?;
+    let errors = check::check(std::slice::from_ref(&class));
+    if !errors.is_empty() {
+        return Err(errors.into_iter().map(|(_, e)| e).collect());
+    }
+    codegen::compile(&class)
+}
+
+/// Compiles the classes of a program, given as (file name, source),
+/// checking the calls between them, into (file name, VM code).
+///
+/// Errors come with the name of the file they're in.
+pub fn compile_program(files: &[(&str, &str)]) -> Result<Vec<(String, String)>, Vec<FileError>> {
+    let mut classes = Vec::new();
+    let mut errors = Vec::new();
+    for (file, source) in files {
+        match parser::parse(source) {
+            Ok(class) => classes.push((file.to_string(), class)),
+            Err(e) => errors.extend(e.into_iter().map(|e| (file.to_string(), e))),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let (files, classes): (Vec<String>, Vec<_>) = classes.into_iter().unzip();
+    let file = |class: &str| {
+        let k = classes.iter().position(|c| c.name == class).unwrap();
+        files[k].clone()
+    };
+    let mut errors: Vec<FileError> = check::check(&classes)
+        .into_iter()
+        .map(|(class, e)| (file(&class), e))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut compiled = Vec::new();
+    for (file, class) in files.iter().zip(classes.iter()) {
+        match codegen::compile(class) {
+            Ok(vm) => compiled.push((file.clone(), vm)),
+            Err(e) => errors.extend(e.into_iter().map(|e| (file.clone(), e))),
+        }
+    }
+    if errors.is_empty() {
+        Ok(compiled)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_program() {
+        let main = "class Main { function void main() { do Util.log(1, 2); return; } }";
+        let util = "class Util { function void log(int n) { return; } }";
+        let errors = compile_program(&[("Main.jack", main), ("Util.jack", util)]).unwrap_err();
+        let errors: Vec<String> = errors.iter().map(|(f, e)| format!("{}:{}", f, e)).collect();
+        assert_eq!(
+            errors,
+            ["Main.jack:1:40: 'Util.log' expects 1 argument, found 2"]
+        );
+
+        let main = "class Main { function void main() { do Util.log(1); return; } }";
+        let compiled = compile_program(&[("Main.jack", main), ("Util.jack", util)]).unwrap();
+        assert_eq!(compiled[0].0, "Main.jack");
+        assert_eq!(
+            compiled[1].1,
+            "function Util.log 0\npush constant 0\nreturn\n"
+        );
+
+        let errors = compile_program(&[("Main.jack", "class Main {"), ("Util.jack", util)]);
+        assert_eq!(errors.unwrap_err()[0].0, "Main.jack");
+    }
+
+    #[test]
+    fn test_compile() {
+        let errors = compile("class Main { function int f() { return g(); } }").unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "1:40: class 'Main' has no subroutine 'g'"
+        );
+    }
+}
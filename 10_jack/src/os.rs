@@ -0,0 +1,320 @@
+use std::path::Path;
+
+/// The operating system of the Jack platform, written in Jack: the
+/// standard library every program can call, and Sys.init, which starts
+/// the program after initializing the other classes.
+pub const CLASSES: [(&str, &str); 8] = [
+    ("Array", include_str!("../os/Array.jack")),
+    ("Keyboard", include_str!("../os/Keyboard.jack")),
+    ("Math", include_str!("../os/Math.jack")),
+    ("Memory", include_str!("../os/Memory.jack")),
+    ("Output", include_str!("../os/Output.jack")),
+    ("Screen", include_str!("../os/Screen.jack")),
+    ("String", include_str!("../os/String.jack")),
+    ("Sys", include_str!("../os/Sys.jack")),
+];
+
+/// Adds the OS classes to the files of a program, given as (file name,
+/// source), except the ones the program defines itself in a file of
+/// the same name.
+pub fn link<'a>(files: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    let defined: Vec<&str> = files
+        .iter()
+        .filter_map(|(file, _)| Path::new(file).file_stem()?.to_str())
+        .collect();
+    let mut linked = files.to_vec();
+    linked.extend(CLASSES.iter().filter(|(name, _)| !defined.contains(name)));
+    linked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Class;
+    use crate::parser::parse;
+    use computer::emulator::Emulator;
+    use computer::rom::ROM32K;
+
+    /// Initializes the classes the tests need, runs Main.main and halts,
+    /// leaving Output and Screen out as they take long to set up.
+    const SYS: &str = "
+class Sys {
+    function void init() {
+        do Memory.init();
+        do Math.init();
+        do Main.main();
+        while (true) {}
+        return;
+    }
+    function void error(int code) {
+        do Memory.poke(7999, code);
+        while (true) {}
+        return;
+    }
+}";
+
+    /// Runs Main with the OS on the emulator, for the given number of cycles.
+    fn run(main: &str, cycles: usize) -> Emulator {
+        let files = link(&[("Main", main), ("Sys", SYS)]);
+        let compiled = crate::compile_program(&files).unwrap();
+        let files: Vec<(&str, &str)> = compiled
+            .iter()
+            .map(|(n, s)| (n.as_str(), s.as_str()))
+            .collect();
+        let asm = vm_translator::translate_program_shared(&files).unwrap();
+        let words = assembler::assemble(&asm).unwrap();
+        let rom = ROM32K::from_hack(&assembler::to_hack(&words)).unwrap();
+        let mut emulator = Emulator::new(&rom);
+        emulator.step(cycles);
+        emulator
+    }
+
+    #[test]
+    fn test_link() {
+        let linked = link(&[("dir/Main.jack", ""), ("dir/Math.jack", "")]);
+        let names: Vec<&str> = linked.iter().map(|(n, _)| *n).collect();
+        assert_eq!(
+            names,
+            [
+                "dir/Main.jack",
+                "dir/Math.jack",
+                "Array",
+                "Keyboard",
+                "Memory",
+                "Output",
+                "Screen",
+                "String",
+                "Sys"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_api() {
+        // the subroutines of the book's OS, with their number of arguments
+        let api = [
+            (
+                "Math",
+                &[
+                    ("abs", 1),
+                    ("multiply", 2),
+                    ("divide", 2),
+                    ("min", 2),
+                    ("max", 2),
+                    ("sqrt", 1),
+                ][..],
+            ),
+            (
+                "String",
+                &[
+                    ("new", 1),
+                    ("dispose", 0),
+                    ("length", 0),
+                    ("charAt", 1),
+                    ("setCharAt", 2),
+                    ("appendChar", 1),
+                    ("eraseLastChar", 0),
+                    ("intValue", 0),
+                    ("setInt", 1),
+                    ("backSpace", 0),
+                    ("doubleQuote", 0),
+                    ("newLine", 0),
+                ][..],
+            ),
+            ("Array", &[("new", 1), ("dispose", 0)][..]),
+            (
+                "Output",
+                &[
+                    ("moveCursor", 2),
+                    ("printChar", 1),
+                    ("printString", 1),
+                    ("printInt", 1),
+                    ("println", 0),
+                    ("backSpace", 0),
+                ][..],
+            ),
+            (
+                "Screen",
+                &[
+                    ("clearScreen", 0),
+                    ("setColor", 1),
+                    ("drawPixel", 2),
+                    ("drawLine", 4),
+                    ("drawRectangle", 4),
+                    ("drawCircle", 3),
+                ][..],
+            ),
+            (
+                "Keyboard",
+                &[
+                    ("keyPressed", 0),
+                    ("readChar", 0),
+                    ("readLine", 1),
+                    ("readInt", 1),
+                ][..],
+            ),
+            (
+                "Memory",
+                &[("peek", 1), ("poke", 2), ("alloc", 1), ("deAlloc", 1)][..],
+            ),
+            ("Sys", &[("halt", 0), ("error", 1), ("wait", 1)][..]),
+        ];
+        let classes: Vec<Class> = CLASSES.iter().map(|(_, s)| parse(s).unwrap()).collect();
+        for (class, subroutines) in api {
+            let class = classes.iter().find(|c| c.name == class).unwrap();
+            for (name, arguments) in subroutines {
+                let subroutine = class.subroutines.iter().find(|s| s.name == *name);
+                let found = subroutine.map(|s| s.parameters.len());
+                assert_eq!(found, Some(*arguments), "{}.{}", class.name, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compile() {
+        let main =
+            "class Main { function void main() { do Output.printInt(Math.sqrt(49)); return; } }";
+        let compiled = crate::compile_program(&link(&[("Main", main)])).unwrap();
+        assert_eq!(compiled.len(), 9);
+        assert!(compiled.iter().all(|(_, vm)| !vm.is_empty()));
+    }
+
+    #[test]
+    fn test_math() {
+        let main = "
+class Main {
+    function void main() {
+        var Array out;
+        let out = 8000;
+        let out[0] = Math.multiply(-7, 6);
+        let out[1] = 123 * 45;
+        let out[2] = -42 / 5;
+        let out[3] = 5535 / 45;
+        let out[4] = Math.max(3, Math.abs(-4));
+        let out[5] = Math.divide(-32767 - 1, 2);
+        let out[6] = Math.divide(-32767 - 1, -3);
+        let out[7] = Math.divide(-32767 - 1, -32767 - 1);
+        let out[8] = Math.divide(100, -32767 - 1);
+        return;
+    }
+}";
+        let emulator = run(main, 40000);
+        assert_eq!(emulator.peek(8000) as i16, -42);
+        assert_eq!(emulator.peek(8001), 5535);
+        assert_eq!(emulator.peek(8002) as i16, -8);
+        assert_eq!(emulator.peek(8003), 123);
+        assert_eq!(emulator.peek(8004), 4);
+        assert_eq!(emulator.peek(8005) as i16, -16384);
+        assert_eq!(emulator.peek(8006) as i16, 10922);
+        assert_eq!(emulator.peek(8007), 1);
+        assert_eq!(emulator.peek(8008), 0);
+        assert_eq!(emulator.peek(7999), 0);
+    }
+
+    #[test]
+    fn test_string() {
+        let main = "
+class Main {
+    function void main() {
+        var Array out;
+        var String s;
+        let out = 8000;
+        let s = String.new(6);
+        do s.setInt(0);
+        let out[0] = s.intValue();
+        do s.setInt(7);
+        let out[1] = s.intValue();
+        do s.setInt(-1);
+        let out[2] = s.intValue();
+        do s.setInt(32767);
+        let out[3] = s.intValue();
+        do s.setInt(-32767 - 1);
+        let out[4] = s.intValue();
+        let out[5] = s.length();
+        let out[6] = s.charAt(1);
+        let out[7] = s.charAt(5);
+        do s.eraseLastChar();
+        do s.eraseLastChar();
+        do s.appendChar(49);
+        let out[8] = s.intValue();
+        do s.eraseLastChar();
+        do s.appendChar(65);
+        let out[9] = s.intValue();
+        return;
+    }
+}";
+        let emulator = run(main, 200000);
+        assert_eq!(emulator.peek(8000), 0);
+        assert_eq!(emulator.peek(8001), 7);
+        assert_eq!(emulator.peek(8002) as i16, -1);
+        assert_eq!(emulator.peek(8003), 32767);
+        assert_eq!(emulator.peek(8004) as i16, -32768);
+        // "-32768"
+        assert_eq!(emulator.peek(8005), 6);
+        assert_eq!(emulator.peek(8006), '3' as u16);
+        assert_eq!(emulator.peek(8007), '8' as u16);
+        assert_eq!(emulator.peek(8008) as i16, -3271);
+        // the digits stop at the first other character
+        assert_eq!(emulator.peek(8009) as i16, -327);
+        assert_eq!(emulator.peek(7999), 0);
+    }
+
+    #[test]
+    fn test_print_int() {
+        let main = "
+class Main {
+    function void main() {
+        do Output.init();
+        do Output.printInt(-32767 - 1);
+        return;
+    }
+}";
+        let emulator = run(main, 800000);
+        // two characters to a word, the first in the low byte: "-3", "27", "68"
+        let rows = |address: u16| -> Vec<u16> {
+            (0..11).map(|j| emulator.peek(address + 32 * j)).collect()
+        };
+        let glyphs = |low: [u16; 11], high: [u16; 11]| -> Vec<u16> {
+            (0..11).map(|j| low[j] | high[j] << 8).collect()
+        };
+        let minus = [0, 0, 0, 0, 0, 63, 0, 0, 0, 0, 0];
+        let two = [30, 51, 48, 24, 12, 6, 3, 51, 63, 0, 0];
+        let three = [30, 51, 48, 48, 28, 48, 48, 51, 30, 0, 0];
+        let six = [28, 6, 3, 3, 31, 51, 51, 51, 30, 0, 0];
+        let seven = [63, 49, 48, 48, 24, 12, 12, 12, 12, 0, 0];
+        let eight = [30, 51, 51, 51, 30, 51, 51, 51, 30, 0, 0];
+        assert_eq!(rows(16384), glyphs(minus, three));
+        assert_eq!(rows(16385), glyphs(two, seven));
+        assert_eq!(rows(16386), glyphs(six, eight));
+        assert_eq!(emulator.peek(7999), 0);
+    }
+
+    #[test]
+    fn test_memory() {
+        let main = "
+class Main {
+    function void main() {
+        var Array out, a, b;
+        let out = 8000;
+        let a = Memory.alloc(3);
+        let b = Array.new(5);
+        do Memory.deAlloc(a);
+        let out[0] = a;
+        let out[1] = b;
+        // an exact fit reuses the freed block
+        let out[2] = Memory.alloc(3);
+        // anything else is carved from the heap
+        let out[3] = Memory.alloc(2);
+        do Memory.alloc(0);
+        return;
+    }
+}";
+        let emulator = run(main, 10000);
+        // the heap ends with the 17 words Math.init allocates, and a header
+        assert_eq!(emulator.peek(8000), 16384 - 18 - 3);
+        assert_eq!(emulator.peek(8001), 16384 - 18 - 4 - 5);
+        assert_eq!(emulator.peek(8002), 16384 - 18 - 3);
+        assert_eq!(emulator.peek(8003), 16384 - 18 - 4 - 6 - 2);
+        assert_eq!(emulator.peek(7999), 5);
+    }
+}
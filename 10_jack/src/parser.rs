@@ -0,0 +1,592 @@
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Position};
+use crate::tokenizer::{Keyword, Token};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A recursive-descent parser with one token of lookahead,
+/// two for telling variables, array entries and calls apart.
+pub struct Parser {
+    tokens: Vec<(Position, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<(Position, Token)>) -> Parser {
+        Parser { tokens, index: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, t)| t)
+    }
+
+    fn peek_second(&self) -> Option<&Token> {
+        self.tokens.get(self.index + 1).map(|(_, t)| t)
+    }
+
+    /// Where the next token starts, or the last one at the end of the file.
+    fn position(&self) -> Position {
+        self.tokens
+            .get(self.index)
+            .or(self.tokens.last())
+            .map(|&(p, _)| p)
+            .unwrap_or_default()
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T> {
+        let found = match self.peek() {
+            Some(token) => format!("'{}'", token),
+            None => "end of file".to_string(),
+        };
+        Err(Error {
+            position: self.position(),
+            kind: ErrorKind::Expected {
+                expected: expected.to_string(),
+                found,
+            },
+        })
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.index += 1;
+        token
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Symbol(c))
+    }
+
+    fn is_keyword(&self, keyword: Keyword) -> bool {
+        self.peek() == Some(&Token::Keyword(keyword))
+    }
+
+    fn symbol(&mut self, c: char) -> Result<()> {
+        if self.is_symbol(c) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", c))
+        }
+    }
+
+    fn keyword(&mut self, keyword: Keyword) -> Result<()> {
+        if self.is_keyword(keyword) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", keyword))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.index += 1;
+                Ok(name)
+            }
+            _ => self.error("an identifier"),
+        }
+    }
+
+    fn ty(&mut self) -> Result<Type> {
+        let ty = match self.peek() {
+            Some(Token::Keyword(Keyword::Int)) => Type::Int,
+            Some(Token::Keyword(Keyword::Char)) => Type::Char,
+            Some(Token::Keyword(Keyword::Boolean)) => Type::Boolean,
+            Some(Token::Identifier(name)) => Type::Class(name.clone()),
+            _ => return self.error("a type"),
+        };
+        self.index += 1;
+        Ok(ty)
+    }
+
+    /// `name (',' name)* ';'`
+    fn names(&mut self) -> Result<Vec<String>> {
+        let mut names = vec![self.identifier()?];
+        while self.is_symbol(',') {
+            self.next();
+            names.push(self.identifier()?);
+        }
+        self.symbol(';')?;
+        Ok(names)
+    }
+
+    /// 'class' className '{' classVarDec* subroutineDec* '}'
+    pub fn class(&mut self) -> Result<Class> {
+        self.keyword(Keyword::Class)?;
+        let position = self.position();
+        let name = self.identifier()?;
+        self.symbol('{')?;
+
+        let mut vars = Vec::new();
+        loop {
+            let kind = match self.peek() {
+                Some(Token::Keyword(Keyword::Static)) => ClassVarKind::Static,
+                Some(Token::Keyword(Keyword::Field)) => ClassVarKind::Field,
+                _ => break,
+            };
+            self.next();
+            let ty = self.ty()?;
+            let names = self.names()?;
+            vars.push(ClassVarDec { kind, ty, names });
+        }
+
+        let mut subroutines = Vec::new();
+        while !self.is_symbol('}') {
+            subroutines.push(self.subroutine()?);
+        }
+        self.symbol('}')?;
+
+        if self.peek().is_some() {
+            return self.error("end of file");
+        }
+        Ok(Class {
+            position,
+            name,
+            vars,
+            subroutines,
+        })
+    }
+
+    fn subroutine(&mut self) -> Result<Subroutine> {
+        let kind = match self.peek() {
+            Some(Token::Keyword(Keyword::Constructor)) => SubroutineKind::Constructor,
+            Some(Token::Keyword(Keyword::Function)) => SubroutineKind::Function,
+            Some(Token::Keyword(Keyword::Method)) => SubroutineKind::Method,
+            _ => return self.error("a subroutine declaration or '}'"),
+        };
+        self.next();
+        let return_type = if self.is_keyword(Keyword::Void) {
+            self.next();
+            None
+        } else {
+            Some(self.ty()?)
+        };
+        let position = self.position();
+        let name = self.identifier()?;
+
+        self.symbol('(')?;
+        let mut parameters = Vec::new();
+        if !self.is_symbol(')') {
+            loop {
+                let ty = self.ty()?;
+                parameters.push((ty, self.identifier()?));
+                if !self.is_symbol(',') {
+                    break;
+                }
+                self.next();
+            }
+        }
+        self.symbol(')')?;
+
+        self.symbol('{')?;
+        let mut vars = Vec::new();
+        while self.is_keyword(Keyword::Var) {
+            self.next();
+            let ty = self.ty()?;
+            let names = self.names()?;
+            vars.push(VarDec { ty, names });
+        }
+        let statements = self.statements()?;
+        self.symbol('}')?;
+
+        Ok(Subroutine {
+            position,
+            kind,
+            return_type,
+            name,
+            parameters,
+            vars,
+            statements,
+        })
+    }
+
+    /// Statements up to the closing '}', which is left for the caller.
+    fn statements(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = Vec::new();
+        while !self.is_symbol('}') {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>> {
+        self.symbol('{')?;
+        let statements = self.statements()?;
+        self.symbol('}')?;
+        Ok(statements)
+    }
+
+    fn condition(&mut self) -> Result<Expression> {
+        self.symbol('(')?;
+        let condition = self.expression()?;
+        self.symbol(')')?;
+        Ok(condition)
+    }
+
+    fn statement(&mut self) -> Result<Statement> {
+        let keyword = match self.peek() {
+            Some(Token::Keyword(k)) => *k,
+            _ => return self.error("a statement"),
+        };
+        let position = self.position();
+        match keyword {
+            Keyword::Let => {
+                self.next();
+                let position = self.position();
+                let name = self.identifier()?;
+                let index = if self.is_symbol('[') {
+                    self.next();
+                    let index = self.expression()?;
+                    self.symbol(']')?;
+                    Some(index)
+                } else {
+                    None
+                };
+                self.symbol('=')?;
+                let value = self.expression()?;
+                self.symbol(';')?;
+                Ok(Statement::Let {
+                    position,
+                    name,
+                    index,
+                    value,
+                })
+            }
+            Keyword::If => {
+                self.next();
+                let condition = self.condition()?;
+                let then = self.block()?;
+                let otherwise = if self.is_keyword(Keyword::Else) {
+                    self.next();
+                    Some(self.block()?)
+                } else {
+                    None
+                };
+                Ok(Statement::If {
+                    condition,
+                    then,
+                    otherwise,
+                })
+            }
+            Keyword::While => {
+                self.next();
+                let condition = self.condition()?;
+                let body = self.block()?;
+                Ok(Statement::While { condition, body })
+            }
+            Keyword::Do => {
+                self.next();
+                let position = self.position();
+                let name = self.identifier()?;
+                let call = self.call(position, name)?;
+                self.symbol(';')?;
+                Ok(Statement::Do(call))
+            }
+            Keyword::Return => {
+                self.next();
+                let value = if self.is_symbol(';') {
+                    None
+                } else {
+                    Some(self.expression()?)
+                };
+                self.symbol(';')?;
+                Ok(Statement::Return { position, value })
+            }
+            _ => self.error("a statement"),
+        }
+    }
+
+    /// The rest of a call whose first identifier has been read.
+    fn call(&mut self, position: Position, first: String) -> Result<SubroutineCall> {
+        let (receiver, name) = if self.is_symbol('.') {
+            self.next();
+            (Some(first), self.identifier()?)
+        } else {
+            (None, first)
+        };
+        self.symbol('(')?;
+        let mut arguments = Vec::new();
+        if !self.is_symbol(')') {
+            arguments.push(self.expression()?);
+            while self.is_symbol(',') {
+                self.next();
+                arguments.push(self.expression()?);
+            }
+        }
+        self.symbol(')')?;
+        Ok(SubroutineCall {
+            position,
+            receiver,
+            name,
+            arguments,
+        })
+    }
+
+    pub fn expression(&mut self) -> Result<Expression> {
+        let term = self.term()?;
+        let mut rest = Vec::new();
+        while let Some(op) = match self.peek() {
+            Some(Token::Symbol(c)) => BinaryOp::parse(*c),
+            _ => None,
+        } {
+            self.next();
+            rest.push((op, self.term()?));
+        }
+        Ok(Expression { term, rest })
+    }
+
+    fn term(&mut self) -> Result<Term> {
+        let position = self.position();
+        let term = match self.peek() {
+            Some(Token::IntegerConstant(n)) => Term::IntegerConstant(*n),
+            Some(Token::StringConstant(s)) => Term::StringConstant(s.clone()),
+            Some(Token::Keyword(k)) => {
+                let constant = match k {
+                    Keyword::True => KeywordConstant::True,
+                    Keyword::False => KeywordConstant::False,
+                    Keyword::Null => KeywordConstant::Null,
+                    Keyword::This => KeywordConstant::This,
+                    _ => return self.error("an expression"),
+                };
+                Term::KeywordConstant(constant)
+            }
+            Some(Token::Symbol('(')) => {
+                self.next();
+                let expression = self.expression()?;
+                self.symbol(')')?;
+                return Ok(Term::Parenthesized(Box::new(expression)));
+            }
+            Some(Token::Symbol(c)) => match UnaryOp::parse(*c) {
+                Some(op) => {
+                    self.next();
+                    return Ok(Term::Unary(op, Box::new(self.term()?)));
+                }
+                None => return self.error("an expression"),
+            },
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                return match self.peek_second() {
+                    Some(Token::Symbol('[')) => {
+                        self.index += 2;
+                        let index = self.expression()?;
+                        self.symbol(']')?;
+                        Ok(Term::Index(position, name, Box::new(index)))
+                    }
+                    Some(Token::Symbol('(' | '.')) => {
+                        self.next();
+                        Ok(Term::Call(self.call(position, name)?))
+                    }
+                    _ => {
+                        self.next();
+                        Ok(Term::Variable(position, name))
+                    }
+                };
+            }
+            None => return self.error("an expression"),
+        };
+        self.next();
+        Ok(term)
+    }
+}
+
+/// Tokenizes and parses the source of one class.
+pub fn parse(source: &str) -> std::result::Result<Class, Vec<Error>> {
+    let tokens = crate::tokenizer::tokenize(source)?;
+    Parser::new(tokens).class().map_err(|e| vec![e])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+
+    fn expression(source: &str) -> Expression {
+        let tokens = crate::tokenizer::tokenize(source).unwrap();
+        let mut parser = Parser::new(tokens);
+        let expression = parser.expression().unwrap();
+        assert_eq!(parser.peek(), None);
+        expression
+    }
+
+    fn single(term: Term) -> Expression {
+        Expression { term, rest: vec![] }
+    }
+
+    fn error(source: &str) -> String {
+        parse(source).unwrap_err()[0].to_string()
+    }
+
+    #[test]
+    fn test_expression() {
+        assert_eq!(
+            expression("1 + x * -2"),
+            Expression {
+                term: Term::IntegerConstant(1),
+                rest: vec![
+                    (BinaryOp::Add, Term::Variable(p(1, 5), "x".to_string())),
+                    (
+                        BinaryOp::Mul,
+                        Term::Unary(UnaryOp::Neg, Box::new(Term::IntegerConstant(2)))
+                    ),
+                ],
+            }
+        );
+        assert_eq!(
+            expression("a[i] = (null)"),
+            Expression {
+                term: Term::Index(
+                    p(1, 1),
+                    "a".to_string(),
+                    Box::new(single(Term::Variable(p(1, 3), "i".to_string())))
+                ),
+                rest: vec![(
+                    BinaryOp::Eq,
+                    Term::Parenthesized(Box::new(single(Term::KeywordConstant(
+                        KeywordConstant::Null
+                    ))))
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn test_calls() {
+        assert_eq!(
+            expression("Math.max(1, \"s\")"),
+            single(Term::Call(SubroutineCall {
+                position: p(1, 1),
+                receiver: Some("Math".to_string()),
+                name: "max".to_string(),
+                arguments: vec![
+                    single(Term::IntegerConstant(1)),
+                    single(Term::StringConstant("s".to_string())),
+                ],
+            }))
+        );
+        assert_eq!(
+            expression("draw()"),
+            single(Term::Call(SubroutineCall {
+                position: p(1, 1),
+                receiver: None,
+                name: "draw".to_string(),
+                arguments: vec![],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_class() {
+        let source = "
+class Point {
+    field int x, y;
+    static Point origin;
+
+    constructor Point new(int ax, int ay) {
+        let x = ax;
+        let y = ay;
+        return this;
+    }
+
+    method void move(int dx) {
+        var int i;
+        let i = 0;
+        while (i < dx) {
+            if (x > 100) { let x = 0; } else { let x = x + 1; }
+            let i = i + 1;
+        }
+        do Output.printInt(x);
+        return;
+    }
+}";
+        let class = parse(source).unwrap();
+        assert_eq!(class.name, "Point");
+        assert_eq!(class.position, p(2, 7));
+        assert_eq!(
+            class.vars,
+            vec![
+                ClassVarDec {
+                    kind: ClassVarKind::Field,
+                    ty: Type::Int,
+                    names: vec!["x".to_string(), "y".to_string()],
+                },
+                ClassVarDec {
+                    kind: ClassVarKind::Static,
+                    ty: Type::Class("Point".to_string()),
+                    names: vec!["origin".to_string()],
+                },
+            ]
+        );
+
+        let new = &class.subroutines[0];
+        assert_eq!(new.kind, SubroutineKind::Constructor);
+        assert_eq!(new.return_type, Some(Type::Class("Point".to_string())));
+        assert_eq!(
+            new.parameters,
+            vec![(Type::Int, "ax".to_string()), (Type::Int, "ay".to_string())]
+        );
+        assert_eq!(new.statements.len(), 3);
+        assert_eq!(
+            new.statements[2],
+            Statement::Return {
+                position: p(9, 9),
+                value: Some(single(Term::KeywordConstant(KeywordConstant::This))),
+            }
+        );
+
+        let moves = &class.subroutines[1];
+        assert_eq!(moves.kind, SubroutineKind::Method);
+        assert_eq!(moves.return_type, None);
+        assert_eq!(moves.vars.len(), 1);
+        assert_eq!(moves.statements.len(), 4);
+        match &moves.statements[1] {
+            Statement::While { body, .. } => match &body[0] {
+                Statement::If {
+                    then, otherwise, ..
+                } => {
+                    assert_eq!(then.len(), 1);
+                    assert_eq!(otherwise.as_ref().map(|s| s.len()), Some(1));
+                }
+                s => panic!("{:?}", s),
+            },
+            s => panic!("{:?}", s),
+        }
+        assert!(matches!(moves.statements[2], Statement::Do(_)));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("class Main { function void main() { let x = 1 } }"),
+            "1:47: expected ';', found '}'"
+        );
+        assert_eq!(
+            error("class Main { function void main() { let x = ; } }"),
+            "1:45: expected an expression, found ';'"
+        );
+        assert_eq!(
+            error("class Main { function void main() { x = 1; } }"),
+            "1:37: expected a statement, found 'x'"
+        );
+        assert_eq!(
+            error("class Main { var int x; }"),
+            "1:14: expected a subroutine declaration or '}', found 'var'"
+        );
+        assert_eq!(
+            error("class Main {"),
+            "1:12: expected a subroutine declaration or '}', found end of file"
+        );
+        assert_eq!(error("class 1"), "1:7: expected an identifier, found '1'");
+        assert_eq!(
+            error("class A {} class B {}"),
+            "1:12: expected end of file, found 'class'"
+        );
+        // tokenizer errors are reported too
+        assert_eq!(error("class A { # }"), "1:11: unexpected character '#'");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::ast::Type;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Static,
+    Field,
+    Argument,
+    Var,
+}
+
+impl Kind {
+    /// The VM memory segment variables of this kind live in.
+    pub fn segment(self) -> &'static str {
+        match self {
+            Kind::Static => "static",
+            Kind::Field => "this",
+            Kind::Argument => "argument",
+            Kind::Var => "local",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub ty: Type,
+    pub kind: Kind,
+    pub index: u16,
+}
+
+/// Statics and fields have class scope, arguments and vars subroutine
+/// scope, which hides the class one. Each kind is numbered from 0.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    class: HashMap<String, Symbol>,
+    subroutine: HashMap<String, Symbol>,
+    counts: HashMap<Kind, u16>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+
+    /// Forgets the arguments and vars of the previous subroutine.
+    pub fn start_subroutine(&mut self) {
+        self.subroutine.clear();
+        self.counts.remove(&Kind::Argument);
+        self.counts.remove(&Kind::Var);
+    }
+
+    /// Returns false if the name is already defined in the same scope.
+    pub fn define(&mut self, name: &str, ty: &Type, kind: Kind) -> bool {
+        let scope = match kind {
+            Kind::Static | Kind::Field => &mut self.class,
+            Kind::Argument | Kind::Var => &mut self.subroutine,
+        };
+        if scope.contains_key(name) {
+            return false;
+        }
+        let count = self.counts.entry(kind).or_insert(0);
+        let symbol = Symbol {
+            ty: ty.clone(),
+            kind,
+            index: *count,
+        };
+        *count += 1;
+        scope.insert(name.to_string(), symbol);
+        true
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.subroutine.get(name).or_else(|| self.class.get(name))
+    }
+
+    pub fn count(&self, kind: Kind) -> u16 {
+        self.counts.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_table() {
+        let mut symbols = SymbolTable::new();
+        let point = Type::Class("Point".to_string());
+        assert!(symbols.define("x", &Type::Int, Kind::Field));
+        assert!(symbols.define("y", &Type::Int, Kind::Field));
+        assert!(symbols.define("count", &Type::Int, Kind::Static));
+        assert!(!symbols.define("x", &Type::Char, Kind::Static));
+        assert_eq!(symbols.count(Kind::Field), 2);
+        assert_eq!(symbols.count(Kind::Static), 1);
+
+        symbols.start_subroutine();
+        assert!(symbols.define("this", &point, Kind::Argument));
+        assert!(symbols.define("x", &Type::Boolean, Kind::Argument));
+        assert!(symbols.define("i", &Type::Int, Kind::Var));
+        assert!(!symbols.define("i", &Type::Int, Kind::Var));
+        assert_eq!(
+            symbols.get("x"),
+            Some(&Symbol {
+                ty: Type::Boolean,
+                kind: Kind::Argument,
+                index: 1
+            })
+        );
+        assert_eq!(
+            symbols.get("y").map(|s| (s.kind, s.index)),
+            Some((Kind::Field, 1))
+        );
+        assert_eq!(symbols.get("i").map(|s| s.kind.segment()), Some("local"));
+
+        symbols.start_subroutine();
+        assert_eq!(symbols.get("i"), None);
+        assert_eq!(symbols.get("x").map(|s| s.kind), Some(Kind::Field));
+        assert_eq!(symbols.count(Kind::Argument), 0);
+        assert_eq!(symbols.count(Kind::Field), 2);
+    }
+}
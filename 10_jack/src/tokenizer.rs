@@ -0,0 +1,354 @@
+use crate::error::{Error, ErrorKind, Position};
+use std::fmt;
+
+pub const MAX_INTEGER: u16 = 32767;
+
+pub const SYMBOLS: &str = "{}()[].,;+-*/&|<>=~";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Keyword {
+    Class,
+    Constructor,
+    Function,
+    Method,
+    Field,
+    Static,
+    Var,
+    Int,
+    Char,
+    Boolean,
+    Void,
+    True,
+    False,
+    Null,
+    This,
+    Let,
+    Do,
+    If,
+    Else,
+    While,
+    Return,
+}
+
+const KEYWORDS: [(&str, Keyword); 21] = [
+    ("class", Keyword::Class),
+    ("constructor", Keyword::Constructor),
+    ("function", Keyword::Function),
+    ("method", Keyword::Method),
+    ("field", Keyword::Field),
+    ("static", Keyword::Static),
+    ("var", Keyword::Var),
+    ("int", Keyword::Int),
+    ("char", Keyword::Char),
+    ("boolean", Keyword::Boolean),
+    ("void", Keyword::Void),
+    ("true", Keyword::True),
+    ("false", Keyword::False),
+    ("null", Keyword::Null),
+    ("this", Keyword::This),
+    ("let", Keyword::Let),
+    ("do", Keyword::Do),
+    ("if", Keyword::If),
+    ("else", Keyword::Else),
+    ("while", Keyword::While),
+    ("return", Keyword::Return),
+];
+
+impl Keyword {
+    pub fn parse(word: &str) -> Option<Keyword> {
+        KEYWORDS.iter().find(|(w, _)| *w == word).map(|&(_, k)| k)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        KEYWORDS.iter().find(|(_, k)| *k == self).unwrap().0
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Keyword(Keyword),
+    Symbol(char),
+    IntegerConstant(u16),
+    StringConstant(String),
+    Identifier(String),
+}
+
+impl Token {
+    /// The element name of the token in the course's XML files.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Token::Keyword(_) => "keyword",
+            Token::Symbol(_) => "symbol",
+            Token::IntegerConstant(_) => "integerConstant",
+            Token::StringConstant(_) => "stringConstant",
+            Token::Identifier(_) => "identifier",
+        }
+    }
+
+    /// The token as written in the XML files: string constants lose their quotes.
+    pub fn text(&self) -> String {
+        match self {
+            Token::Keyword(k) => k.to_string(),
+            Token::Symbol(c) => c.to_string(),
+            Token::IntegerConstant(n) => n.to_string(),
+            Token::StringConstant(s) | Token::Identifier(s) => s.clone(),
+        }
+    }
+}
+
+/// The token as written in Jack source.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::StringConstant(s) => write!(f, "\"{}\"", s),
+            _ => write!(f, "{}", self.text()),
+        }
+    }
+}
+
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+struct Cursor {
+    chars: Vec<char>,
+    index: usize,
+    position: Position,
+}
+
+impl Cursor {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.index += 1;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek(0).filter(|&c| f(c)) {
+            text.push(c);
+            self.bump();
+        }
+        text
+    }
+}
+
+/// Splits Jack source into tokens, dropping whitespace and comments.
+///
+/// Every error in the source is returned, not only the first one.
+pub fn tokenize(source: &str) -> Result<Vec<(Position, Token)>, Vec<Error>> {
+    let mut cursor = Cursor {
+        chars: source.chars().collect(),
+        index: 0,
+        position: Position { line: 1, column: 1 },
+    };
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    while let Some(c) = cursor.peek(0) {
+        let position = cursor.position;
+        let error = |kind| Error { position, kind };
+
+        if c.is_whitespace() {
+            cursor.bump();
+        } else if c == '/' && cursor.peek(1) == Some('/') {
+            cursor.take_while(|c| c != '\n');
+        } else if c == '/' && cursor.peek(1) == Some('*') {
+            cursor.bump();
+            cursor.bump();
+            loop {
+                match cursor.bump() {
+                    Some('*') if cursor.peek(0) == Some('/') => {
+                        cursor.bump();
+                        break;
+                    }
+                    Some(_) => {}
+                    None => {
+                        errors.push(error(ErrorKind::UnterminatedComment));
+                        break;
+                    }
+                }
+            }
+        } else if c == '"' {
+            cursor.bump();
+            let text = cursor.take_while(|c| c != '"' && c != '\n');
+            if cursor.peek(0) == Some('"') {
+                cursor.bump();
+                tokens.push((position, Token::StringConstant(text)));
+            } else {
+                errors.push(error(ErrorKind::UnterminatedString));
+            }
+        } else if c.is_ascii_digit() {
+            let digits = cursor.take_while(|c| c.is_ascii_digit());
+            match digits.parse::<u16>() {
+                Ok(n) if n <= MAX_INTEGER => tokens.push((position, Token::IntegerConstant(n))),
+                _ => errors.push(error(ErrorKind::IntegerTooLarge(digits))),
+            }
+        } else if is_identifier_start(c) {
+            let word = cursor.take_while(is_identifier_char);
+            let token = match Keyword::parse(&word) {
+                Some(keyword) => Token::Keyword(keyword),
+                None => Token::Identifier(word),
+            };
+            tokens.push((position, token));
+        } else if SYMBOLS.contains(c) {
+            cursor.bump();
+            tokens.push((position, Token::Symbol(c)));
+        } else {
+            cursor.bump();
+            errors.push(error(ErrorKind::UnexpectedCharacter(c)));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect()
+    }
+
+    fn ident(s: &str) -> Token {
+        Token::Identifier(s.to_string())
+    }
+
+    #[test]
+    fn test_keywords() {
+        for (word, keyword) in KEYWORDS {
+            assert_eq!(Keyword::parse(word), Some(keyword));
+            assert_eq!(keyword.as_str(), word);
+        }
+        assert_eq!(Keyword::parse("Class"), None);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("let x[i] = \"a < b\" + 12;"),
+            vec![
+                Token::Keyword(Keyword::Let),
+                ident("x"),
+                Token::Symbol('['),
+                ident("i"),
+                Token::Symbol(']'),
+                Token::Symbol('='),
+                Token::StringConstant("a < b".to_string()),
+                Token::Symbol('+'),
+                Token::IntegerConstant(12),
+                Token::Symbol(';'),
+            ]
+        );
+        assert_eq!(
+            tokens("do Output.printInt(-x_1/2);"),
+            vec![
+                Token::Keyword(Keyword::Do),
+                ident("Output"),
+                Token::Symbol('.'),
+                ident("printInt"),
+                Token::Symbol('('),
+                Token::Symbol('-'),
+                ident("x_1"),
+                Token::Symbol('/'),
+                Token::IntegerConstant(2),
+                Token::Symbol(')'),
+                Token::Symbol(';'),
+            ]
+        );
+        // keywords are only whole words
+        assert_eq!(tokens("classy"), vec![ident("classy")]);
+    }
+
+    #[test]
+    fn test_comments() {
+        let source = "
+            /** API comment
+             * spanning lines */
+            class /* inline */ Main { // to the end of the line
+            }
+        ";
+        assert_eq!(
+            tokens(source),
+            vec![
+                Token::Keyword(Keyword::Class),
+                ident("Main"),
+                Token::Symbol('{'),
+                Token::Symbol('}'),
+            ]
+        );
+        assert_eq!(tokens("/**/x"), vec![ident("x")]);
+        assert_eq!(tokens("x // \"not a string\""), vec![ident("x")]);
+    }
+
+    #[test]
+    fn test_positions() {
+        let positions: Vec<Position> = tokenize("let x\n  = 1;")
+            .unwrap()
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        let expected = [(1, 1), (1, 5), (2, 3), (2, 5), (2, 6)];
+        let expected: Vec<Position> = expected
+            .iter()
+            .map(|&(line, column)| Position { line, column })
+            .collect();
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(tokens("32767"), vec![Token::IntegerConstant(32767)]);
+        let errors = tokenize("32768 99999999").unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>(),
+            vec![
+                ErrorKind::IntegerTooLarge("32768".to_string()),
+                ErrorKind::IntegerTooLarge("99999999".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = tokenize("let s = \"open;\nlet c = #;\n/* never closed").unwrap_err();
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            [
+                "1:9: string constant is missing its closing '\"'",
+                "2:9: unexpected character '#'",
+                "3:1: comment is missing its closing '*/'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,501 @@
+use crate::ast::*;
+use crate::error::Position;
+use crate::tokenizer::{Keyword, Token};
+
+/// Escapes the characters XML reserves, as the course's comparison files do.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `<tag> text </tag>`, the line every token is written as.
+pub fn token(token: &Token) -> String {
+    format!("<{0}> {1} </{0}>", token.tag(), escape(&token.text()))
+}
+
+/// The token file (XxxT.xml) of the course's comparison files.
+pub fn tokens(tokens: &[(Position, Token)]) -> String {
+    let mut xml = String::from("<tokens>\n");
+    for (_, t) in tokens {
+        xml.push_str(&token(t));
+        xml.push('\n');
+    }
+    xml.push_str("</tokens>\n");
+    xml
+}
+
+/// The parse tree file (Xxx.xml) of the course's comparison files.
+pub fn class(class: &Class) -> String {
+    let mut writer = Writer {
+        xml: String::new(),
+        depth: 0,
+    };
+    writer.class(class);
+    writer.xml
+}
+
+/// Writes nested elements, indented by two spaces per level.
+struct Writer {
+    xml: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn line(&mut self, line: &str) {
+        self.xml.push_str(&"  ".repeat(self.depth));
+        self.xml.push_str(line);
+        self.xml.push('\n');
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("<{}>", tag));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{}>", tag));
+    }
+
+    fn token(&mut self, t: Token) {
+        self.line(&token(&t));
+    }
+
+    fn keyword(&mut self, keyword: Keyword) {
+        self.token(Token::Keyword(keyword));
+    }
+
+    fn symbol(&mut self, c: char) {
+        self.token(Token::Symbol(c));
+    }
+
+    fn identifier(&mut self, name: &str) {
+        self.token(Token::Identifier(name.to_string()));
+    }
+
+    fn ty(&mut self, ty: &Type) {
+        match ty {
+            Type::Int => self.keyword(Keyword::Int),
+            Type::Char => self.keyword(Keyword::Char),
+            Type::Boolean => self.keyword(Keyword::Boolean),
+            Type::Class(name) => self.identifier(name),
+        }
+    }
+
+    /// `name, name;`
+    fn names(&mut self, names: &[String]) {
+        for (k, name) in names.iter().enumerate() {
+            if k > 0 {
+                self.symbol(',');
+            }
+            self.identifier(name);
+        }
+        self.symbol(';');
+    }
+
+    fn class(&mut self, class: &Class) {
+        self.open("class");
+        self.keyword(Keyword::Class);
+        self.identifier(&class.name);
+        self.symbol('{');
+        for var in class.vars.iter() {
+            self.open("classVarDec");
+            self.keyword(match var.kind {
+                ClassVarKind::Static => Keyword::Static,
+                ClassVarKind::Field => Keyword::Field,
+            });
+            self.ty(&var.ty);
+            self.names(&var.names);
+            self.close("classVarDec");
+        }
+        for subroutine in class.subroutines.iter() {
+            self.subroutine(subroutine);
+        }
+        self.symbol('}');
+        self.close("class");
+    }
+
+    fn subroutine(&mut self, subroutine: &Subroutine) {
+        self.open("subroutineDec");
+        self.keyword(match subroutine.kind {
+            SubroutineKind::Constructor => Keyword::Constructor,
+            SubroutineKind::Function => Keyword::Function,
+            SubroutineKind::Method => Keyword::Method,
+        });
+        match &subroutine.return_type {
+            Some(ty) => self.ty(ty),
+            None => self.keyword(Keyword::Void),
+        }
+        self.identifier(&subroutine.name);
+        self.symbol('(');
+        self.open("parameterList");
+        for (k, (ty, name)) in subroutine.parameters.iter().enumerate() {
+            if k > 0 {
+                self.symbol(',');
+            }
+            self.ty(ty);
+            self.identifier(name);
+        }
+        self.close("parameterList");
+        self.symbol(')');
+
+        self.open("subroutineBody");
+        self.symbol('{');
+        for var in subroutine.vars.iter() {
+            self.open("varDec");
+            self.keyword(Keyword::Var);
+            self.ty(&var.ty);
+            self.names(&var.names);
+            self.close("varDec");
+        }
+        self.statements(&subroutine.statements);
+        self.symbol('}');
+        self.close("subroutineBody");
+        self.close("subroutineDec");
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        self.open("statements");
+        for statement in statements {
+            self.statement(statement);
+        }
+        self.close("statements");
+    }
+
+    /// `{ statements }`
+    fn block(&mut self, statements: &[Statement]) {
+        self.symbol('{');
+        self.statements(statements);
+        self.symbol('}');
+    }
+
+    /// `( expression )`
+    fn condition(&mut self, condition: &Expression) {
+        self.symbol('(');
+        self.expression(condition);
+        self.symbol(')');
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                name, index, value, ..
+            } => {
+                self.open("letStatement");
+                self.keyword(Keyword::Let);
+                self.identifier(name);
+                if let Some(index) = index {
+                    self.symbol('[');
+                    self.expression(index);
+                    self.symbol(']');
+                }
+                self.symbol('=');
+                self.expression(value);
+                self.symbol(';');
+                self.close("letStatement");
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.open("ifStatement");
+                self.keyword(Keyword::If);
+                self.condition(condition);
+                self.block(then);
+                if let Some(otherwise) = otherwise {
+                    self.keyword(Keyword::Else);
+                    self.block(otherwise);
+                }
+                self.close("ifStatement");
+            }
+            Statement::While { condition, body } => {
+                self.open("whileStatement");
+                self.keyword(Keyword::While);
+                self.condition(condition);
+                self.block(body);
+                self.close("whileStatement");
+            }
+            Statement::Do(call) => {
+                self.open("doStatement");
+                self.keyword(Keyword::Do);
+                self.call(call);
+                self.symbol(';');
+                self.close("doStatement");
+            }
+            Statement::Return { value, .. } => {
+                self.open("returnStatement");
+                self.keyword(Keyword::Return);
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+                self.symbol(';');
+                self.close("returnStatement");
+            }
+        }
+    }
+
+    fn call(&mut self, call: &SubroutineCall) {
+        if let Some(receiver) = &call.receiver {
+            self.identifier(receiver);
+            self.symbol('.');
+        }
+        self.identifier(&call.name);
+        self.symbol('(');
+        self.open("expressionList");
+        for (k, argument) in call.arguments.iter().enumerate() {
+            if k > 0 {
+                self.symbol(',');
+            }
+            self.expression(argument);
+        }
+        self.close("expressionList");
+        self.symbol(')');
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        self.open("expression");
+        self.term(&expression.term);
+        for (op, term) in expression.rest.iter() {
+            self.symbol(op.symbol());
+            self.term(term);
+        }
+        self.close("expression");
+    }
+
+    fn term(&mut self, term: &Term) {
+        self.open("term");
+        match term {
+            Term::IntegerConstant(n) => self.token(Token::IntegerConstant(*n)),
+            Term::StringConstant(s) => self.token(Token::StringConstant(s.clone())),
+            Term::KeywordConstant(k) => self.keyword(match k {
+                KeywordConstant::True => Keyword::True,
+                KeywordConstant::False => Keyword::False,
+                KeywordConstant::Null => Keyword::Null,
+                KeywordConstant::This => Keyword::This,
+            }),
+            Term::Variable(_, name) => self.identifier(name),
+            Term::Index(_, name, index) => {
+                self.identifier(name);
+                self.symbol('[');
+                self.expression(index);
+                self.symbol(']');
+            }
+            Term::Call(call) => self.call(call),
+            Term::Parenthesized(expression) => self.condition(expression),
+            Term::Unary(op, term) => {
+                self.symbol(op.symbol());
+                self.term(term);
+            }
+        }
+        self.close("term");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a<b>c&\"d\""), "a&lt;b&gt;c&amp;&quot;d&quot;");
+        assert_eq!(escape("plain"), "plain");
+    }
+
+    #[test]
+    fn test_tokens() {
+        let source = "if (x < 0) { let s = \"a & b\"; }";
+        let expected = "\
+<tokens>
+<keyword> if </keyword>
+<symbol> ( </symbol>
+<identifier> x </identifier>
+<symbol> &lt; </symbol>
+<integerConstant> 0 </integerConstant>
+<symbol> ) </symbol>
+<symbol> { </symbol>
+<keyword> let </keyword>
+<identifier> s </identifier>
+<symbol> = </symbol>
+<stringConstant> a &amp; b </stringConstant>
+<symbol> ; </symbol>
+<symbol> } </symbol>
+</tokens>
+";
+        assert_eq!(tokens(&tokenize(source).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_class() {
+        let source = "
+class Main {
+    static int n;
+    function void main() {
+        var Array a;
+        let a[0] = -n;
+        if (~(n < 1)) { do Main.run(a, \"x\"); } else { return; }
+        return;
+    }
+}";
+        let expected = "\
+<class>
+  <keyword> class </keyword>
+  <identifier> Main </identifier>
+  <symbol> { </symbol>
+  <classVarDec>
+    <keyword> static </keyword>
+    <keyword> int </keyword>
+    <identifier> n </identifier>
+    <symbol> ; </symbol>
+  </classVarDec>
+  <subroutineDec>
+    <keyword> function </keyword>
+    <keyword> void </keyword>
+    <identifier> main </identifier>
+    <symbol> ( </symbol>
+    <parameterList>
+    </parameterList>
+    <symbol> ) </symbol>
+    <subroutineBody>
+      <symbol> { </symbol>
+      <varDec>
+        <keyword> var </keyword>
+        <identifier> Array </identifier>
+        <identifier> a </identifier>
+        <symbol> ; </symbol>
+      </varDec>
+      <statements>
+        <letStatement>
+          <keyword> let </keyword>
+          <identifier> a </identifier>
+          <symbol> [ </symbol>
+          <expression>
+            <term>
+              <integerConstant> 0 </integerConstant>
+            </term>
+          </expression>
+          <symbol> ] </symbol>
+          <symbol> = </symbol>
+          <expression>
+            <term>
+              <symbol> - </symbol>
+              <term>
+                <identifier> n </identifier>
+              </term>
+            </term>
+          </expression>
+          <symbol> ; </symbol>
+        </letStatement>
+        <ifStatement>
+          <keyword> if </keyword>
+          <symbol> ( </symbol>
+          <expression>
+            <term>
+              <symbol> ~ </symbol>
+              <term>
+                <symbol> ( </symbol>
+                <expression>
+                  <term>
+                    <identifier> n </identifier>
+                  </term>
+                  <symbol> &lt; </symbol>
+                  <term>
+                    <integerConstant> 1 </integerConstant>
+                  </term>
+                </expression>
+                <symbol> ) </symbol>
+              </term>
+            </term>
+          </expression>
+          <symbol> ) </symbol>
+          <symbol> { </symbol>
+          <statements>
+            <doStatement>
+              <keyword> do </keyword>
+              <identifier> Main </identifier>
+              <symbol> . </symbol>
+              <identifier> run </identifier>
+              <symbol> ( </symbol>
+              <expressionList>
+                <expression>
+                  <term>
+                    <identifier> a </identifier>
+                  </term>
+                </expression>
+                <symbol> , </symbol>
+                <expression>
+                  <term>
+                    <stringConstant> x </stringConstant>
+                  </term>
+                </expression>
+              </expressionList>
+              <symbol> ) </symbol>
+              <symbol> ; </symbol>
+            </doStatement>
+          </statements>
+          <symbol> } </symbol>
+          <keyword> else </keyword>
+          <symbol> { </symbol>
+          <statements>
+            <returnStatement>
+              <keyword> return </keyword>
+              <symbol> ; </symbol>
+            </returnStatement>
+          </statements>
+          <symbol> } </symbol>
+        </ifStatement>
+        <returnStatement>
+          <keyword> return </keyword>
+          <symbol> ; </symbol>
+        </returnStatement>
+      </statements>
+      <symbol> } </symbol>
+    </subroutineBody>
+  </subroutineDec>
+  <symbol> } </symbol>
+</class>
+";
+        assert_eq!(class(&parse(source).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_class_keeps_every_token() {
+        // the parse tree lists the tokens of the source in order
+        let source = "
+class Square {
+    field int x, y, size;
+    constructor Square new(int ax, int ay, int asize) {
+        let x = ax; let y = ay; let size = asize;
+        do draw();
+        return this;
+    }
+    method boolean fits(Array limits, char c) {
+        while ((x + size) > limits[0]) { let size = size / 2; }
+        return (size = 0) | false & true & (this = null);
+    }
+}";
+        let tree = class(&parse(source).unwrap());
+        let tree: Vec<&str> = tree
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| line.contains("</") && !line.starts_with("</"))
+            .collect();
+        let expected = tokens(&tokenize(source).unwrap());
+        let expected: Vec<&str> = expected
+            .lines()
+            .filter(|line| !line.ends_with("tokens>"))
+            .collect();
+        assert_eq!(tree, expected);
+    }
+}
@@ -2,73 +2,119 @@
 /// Everything here is built from a simple NAND gate,
 /// which will be the only part of the system that
 /// won't be contructed from our "chips"
-use crate::nand::nand;
+use crate::nand::{Signal, nand};
+
+// The truth tables are doctests printing the gates with
+// truth_table::format, so they can't drift from the gates.
 
 /// NOT truth table
-/// 0 1
-/// 1 0
-pub fn not(a: bool) -> bool {
-    nand(a, a)
+/// ```
+/// # use logic_gates::{basic_gates::not, truth_table::format};
+/// let table = format(&[("a", 1)], &[("out", 1)], |[a]| [not(a)]);
+/// assert_eq!(table, "\
+/// a | out
+/// 0 |  1
+/// 1 |  0
+/// ");
+/// ```
+pub fn not<S: Signal>(a: S) -> S {
+    S::scope("not", || nand(a, a))
 }
 
 /// AND truth table
-/// 00 0
-/// 01 0
-/// 10 0
-/// 11 1
-pub fn and(a: bool, b: bool) -> bool {
-    not(nand(a, b))
+/// ```
+/// # use logic_gates::{basic_gates::and, truth_table::format};
+/// let table = format(&[("a", 1), ("b", 1)], &[("out", 1)], |[a, b]| [and(a, b)]);
+/// assert_eq!(table, "\
+/// a b | out
+/// 0 0 |  0
+/// 0 1 |  0
+/// 1 0 |  0
+/// 1 1 |  1
+/// ");
+/// ```
+pub fn and<S: Signal>(a: S, b: S) -> S {
+    S::scope("and", || not(nand(a, b)))
 }
 
 /// OR truth table
-/// 00 0
-/// 01 1
-/// 10 1
-/// 11 1
-pub fn or(a: bool, b: bool) -> bool {
+/// ```
+/// # use logic_gates::{basic_gates::or, truth_table::format};
+/// let table = format(&[("a", 1), ("b", 1)], &[("out", 1)], |[a, b]| [or(a, b)]);
+/// assert_eq!(table, "\
+/// a b | out
+/// 0 0 |  0
+/// 0 1 |  1
+/// 1 0 |  1
+/// 1 1 |  1
+/// ");
+/// ```
+pub fn or<S: Signal>(a: S, b: S) -> S {
     // original, inefficient. Uses 5 nand gates
     // not(and(not(a), not(b)))
 
     // optimized, uses 3 nand gates
-    nand(not(a), not(b))
+    S::scope("or", || nand(not(a), not(b)))
 }
 
 /// XOR truth table
-/// 00 0
-/// 01 1
-/// 10 1
-/// 11 0
-pub fn xor(a: bool, b: bool) -> bool {
-    not(and(or(not(a), b), or(a, not(b))))
+/// ```
+/// # use logic_gates::{basic_gates::xor, truth_table::format};
+/// let table = format(&[("a", 1), ("b", 1)], &[("out", 1)], |[a, b]| [xor(a, b)]);
+/// assert_eq!(table, "\
+/// a b | out
+/// 0 0 |  0
+/// 0 1 |  1
+/// 1 0 |  1
+/// 1 1 |  0
+/// ");
+/// ```
+pub fn xor<S: Signal>(a: S, b: S) -> S {
+    S::scope("xor", || not(and(or(not(a), b), or(a, not(b)))))
 }
 
 /// MULTIPLEXER truth table
+/// ```
+/// # use logic_gates::{basic_gates::mux, truth_table::format};
+/// let inputs = [("a", 1), ("b", 1), ("s", 1)];
+/// let table = format(&inputs, &[("mux", 1)], |[a, b, s]| [mux(a, b, s)]);
+/// assert_eq!(table, "\
 /// a b s | mux
-/// -------
 /// 0 0 0 |  0
-/// 0 1 0 |  0
-/// 1 0 0 |  1
-/// 1 1 0 |  1
 /// 0 0 1 |  0
+/// 0 1 0 |  0
 /// 0 1 1 |  1
+/// 1 0 0 |  1
 /// 1 0 1 |  0
+/// 1 1 0 |  1
 /// 1 1 1 |  1
-pub fn mux(a: bool, b: bool, s: bool) -> bool {
-    not(xor(or(s, a), or(not(s), b)))
+/// ");
+/// ```
+pub fn mux<S: Signal>(a: S, b: S, s: S) -> S {
+    S::scope("mux", || not(xor(or(s, a), or(not(s), b))))
 }
 
-/// i s |
+/// DEMULTIPLEXER truth table
+/// ```
+/// # use logic_gates::{basic_gates::demux, truth_table::format};
+/// let table = format(&[("i", 1), ("s", 1)], &[("out", 2)], |[i, s]| demux(i, s));
+/// assert_eq!(table, "\
+/// i s | out
 /// 0 0 | 00
 /// 0 1 | 00
 /// 1 0 | 01
 /// 1 1 | 10
+/// ");
+/// ```
 ///
 /// s
 /// 0 | 01
 /// 1 | 10
 /// if i == 0, out = 00
-pub fn demux(i: bool, s: bool) -> [bool; 2] {
-    [not(xor(s, or(not(s), i))), not(xor(not(s), or(s, i)))]
+pub fn demux<S: Signal>(i: S, s: S) -> [S; 2] {
+    S::scope("demux", || {
+        [not(xor(s, or(not(s), i))), not(xor(not(s), or(s, i)))]
+    })
 }
 
 #[cfg(test)]
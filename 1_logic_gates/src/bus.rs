@@ -0,0 +1,95 @@
+use crate::basic_gates as bg;
+use crate::nand::Signal;
+
+/// N wires carried together, MSB-first like Arr16.
+///
+/// The gates here apply the basic gates bit by bit, so a chip of any
+/// width costs the same per bit as the 16 bit ones.
+pub type Bus<const N: usize, S = bool> = [S; N];
+
+pub fn not<S: Signal, const N: usize>(a: Bus<N, S>) -> Bus<N, S> {
+    std::array::from_fn(|k| bg::not(a[k]))
+}
+
+pub fn and<S: Signal, const N: usize>(a: Bus<N, S>, b: Bus<N, S>) -> Bus<N, S> {
+    std::array::from_fn(|k| bg::and(a[k], b[k]))
+}
+
+pub fn or<S: Signal, const N: usize>(a: Bus<N, S>, b: Bus<N, S>) -> Bus<N, S> {
+    std::array::from_fn(|k| bg::or(a[k], b[k]))
+}
+
+pub fn xor<S: Signal, const N: usize>(a: Bus<N, S>, b: Bus<N, S>) -> Bus<N, S> {
+    std::array::from_fn(|k| bg::xor(a[k], b[k]))
+}
+
+/// a when s is 0, b when s is 1.
+pub fn mux<S: Signal, const N: usize>(a: Bus<N, S>, b: Bus<N, S>, s: S) -> Bus<N, S> {
+    std::array::from_fn(|k| bg::mux(a[k], b[k], s))
+}
+
+/// Combines the halves of i with f, as a balanced tree: or8way is
+/// or(or(or(i0, i1), or(i2, i3)), or(or(i4, i5), or(i6, i7))).
+fn tree<S: Signal>(i: &[S], f: fn(S, S) -> S) -> S {
+    if i.len() == 1 {
+        return i[0];
+    }
+    let (a, b) = i.split_at(i.len() / 2);
+    f(tree(a, f), tree(b, f))
+}
+
+/// 1 if any bit is 1, 0 for an empty bus.
+pub fn or_nway<S: Signal, const N: usize>(i: Bus<N, S>) -> S {
+    if N == 0 {
+        return S::constant(false);
+    }
+    tree(&i, bg::or)
+}
+
+/// 1 if every bit is 1, 1 for an empty bus.
+pub fn and_nway<S: Signal, const N: usize>(i: Bus<N, S>) -> S {
+    if N == 0 {
+        return S::constant(true);
+    }
+    tree(&i, bg::and)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{bits, value};
+
+    #[test]
+    fn test_bitwise() {
+        let a: Bus<12> = bits(0b1100_1010_0110);
+        let b: Bus<12> = bits(0b1010_0101_1100);
+        assert_eq!(value(&not(a)), 0b0011_0101_1001);
+        assert_eq!(value(&and(a, b)), 0b1000_0000_0100);
+        assert_eq!(value(&or(a, b)), 0b1110_1111_1110);
+        assert_eq!(value(&xor(a, b)), 0b0110_1111_1010);
+        assert_eq!(mux(a, b, false), a);
+        assert_eq!(mux(a, b, true), b);
+
+        let mut wide = [false; 32];
+        wide[31] = true;
+        assert_eq!(not(not(wide)), wide);
+        assert_eq!(and(wide, not(wide)), [false; 32]);
+        assert_eq!(or(wide, not(wide)), [true; 32]);
+    }
+
+    #[test]
+    fn test_nway() {
+        for n in 0..32 {
+            let mut i = [false; 32];
+            i[n] = true;
+            assert_eq!(or_nway(i), true);
+            assert_eq!(and_nway(not(i)), false);
+        }
+        assert_eq!(or_nway([false; 32]), false);
+        assert_eq!(and_nway([true; 32]), true);
+        assert_eq!(or_nway([true; 1]), true);
+        assert_eq!(and_nway([false; 3]), false);
+        assert_eq!(or_nway::<bool, 0>([]), false);
+        assert_eq!(and_nway::<bool, 0>([]), true);
+    }
+}
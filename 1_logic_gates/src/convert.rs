@@ -0,0 +1,167 @@
+use crate::{Arr4, Arr8, Arr16};
+use std::error::Error;
+use std::fmt;
+
+/// The low N bits of n, MSB-first, for buses of any width up to 16.
+pub fn bits<const N: usize>(n: u16) -> [bool; N] {
+    std::array::from_fn(|k| (n >> (N - 1 - k)) & 1 == 1)
+}
+
+/// The number on a bus of up to 16 bits, MSB-first.
+pub fn value(a: &[bool]) -> u16 {
+    a.iter().fold(0, |n, &b| (n << 1) | b as u16)
+}
+
+pub fn from_u16(n: u16) -> Arr16 {
+    bits(n)
+}
+
+pub fn to_u16(a: Arr16) -> u16 {
+    value(&a)
+}
+
+/// Two's complement, as the ALU computes.
+pub fn from_i16(n: i16) -> Arr16 {
+    bits(n as u16)
+}
+
+pub fn to_i16(a: Arr16) -> i16 {
+    value(&a) as i16
+}
+
+pub fn from_u8(n: u8) -> Arr8 {
+    bits(n as u16)
+}
+
+pub fn to_u8(a: Arr8) -> u8 {
+    value(&a) as u8
+}
+
+/// The low 4 bits of n.
+pub fn from_u4(n: u8) -> Arr4 {
+    bits(n as u16)
+}
+
+pub fn to_u4(a: Arr4) -> u8 {
+    value(&a) as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    Length { expected: usize, found: usize },
+    Character(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Length { expected, found } => {
+                write!(f, "expected {} bits, found {}", expected, found)
+            }
+            ParseError::Character(c) => write!(f, "'{}' is not a bit", c),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a binary string of exactly N '0' and '1', MSB first,
+/// like the words of .hack files: "0000000000000101" is 5.
+pub fn parse<const N: usize>(s: &str) -> Result<[bool; N], ParseError> {
+    let found = s.chars().count();
+    if found != N {
+        return Err(ParseError::Length { expected: N, found });
+    }
+    let mut a = [false; N];
+    for (bit, c) in a.iter_mut().zip(s.chars()) {
+        *bit = match c {
+            '0' => false,
+            '1' => true,
+            _ => return Err(ParseError::Character(c)),
+        };
+    }
+    Ok(a)
+}
+
+/// The bus as a binary string, the way parse reads it.
+pub fn format(a: &[bool]) -> String {
+    a.iter().map(|&b| if b { '1' } else { '0' }).collect()
+}
+
+/// The bus in upper case hexadecimal, with a digit for every 4 bits
+/// or part of it: "0005" for 5 on 16 bits.
+pub fn hex(a: &[bool]) -> String {
+    let digits = a.len().div_ceil(4);
+    let mut text = String::with_capacity(digits);
+    for k in 0..digits {
+        let end = a.len() - 4 * (digits - 1 - k);
+        let digit = value(&a[end.saturating_sub(4)..end]);
+        text.push_str(&format!("{:X}", digit));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integers() {
+        let mut five = [false; 16];
+        five[13] = true;
+        five[15] = true;
+        assert_eq!(from_u16(5), five);
+        assert_eq!(to_u16(five), 5);
+        assert_eq!(from_i16(-1), [true; 16]);
+        assert_eq!(to_i16([true; 16]), -1);
+        assert_eq!(to_i16(from_u16(0x8000)), i16::MIN);
+        assert_eq!(
+            from_u8(0x81),
+            [true, false, false, false, false, false, false, true]
+        );
+        assert_eq!(
+            to_u8([false, true, false, false, false, false, false, false]),
+            64
+        );
+        assert_eq!(from_u4(0x1A), [true, false, true, false]);
+        assert_eq!(to_u4([false, false, true, true]), 3);
+        assert_eq!(bits::<3>(6), [true, true, false]);
+
+        for n in [0, 1, 2, 1234, 0x7FFF, 0x8000, 0xFFFF] {
+            assert_eq!(to_u16(from_u16(n)), n);
+            assert_eq!(to_i16(from_i16(n as i16)), n as i16);
+        }
+        for n in 0..=255 {
+            assert_eq!(to_u8(from_u8(n)), n);
+        }
+        for n in 0..16 {
+            assert_eq!(to_u4(from_u4(n)), n);
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(parse::<16>("0000000000000101"), Ok(from_u16(5)));
+        assert_eq!(parse::<4>("1001"), Ok(from_u4(9)));
+        assert_eq!(
+            parse::<16>("101"),
+            Err(ParseError::Length {
+                expected: 16,
+                found: 3
+            })
+        );
+        assert_eq!(parse::<4>("10x1"), Err(ParseError::Character('x')));
+        assert_eq!(
+            parse::<4>("1").unwrap_err().to_string(),
+            "expected 4 bits, found 1"
+        );
+
+        assert_eq!(format(&from_u16(5)), "0000000000000101");
+        assert_eq!(format(&[]), "");
+        assert_eq!(hex(&from_u16(5)), "0005");
+        assert_eq!(hex(&from_i16(-2)), "FFFE");
+        assert_eq!(hex(&from_u8(0xA7)), "A7");
+        assert_eq!(hex(&bits::<15>(0x7ABC)), "7ABC");
+        assert_eq!(hex(&bits::<6>(0x2F)), "2F");
+    }
+}
@@ -0,0 +1,42 @@
+use crate::nand::Signal;
+
+/// The length of the longest path of NANDs from the inputs of a chip to
+/// a wire, which is how long the wire takes to settle after the inputs
+/// change. Evaluating a chip on depths gives the depth of its outputs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Depth(pub u32);
+
+impl Signal for Depth {
+    fn nand(a: Depth, b: Depth) -> Depth {
+        Depth(a.0.max(b.0) + 1)
+    }
+
+    /// Constants are there from the start, like the inputs.
+    fn constant(_: bool) -> Depth {
+        Depth(0)
+    }
+}
+
+/// Inputs of a chip, all at depth 0.
+pub fn inputs<const N: usize>() -> [Depth; N] {
+    [Depth(0); N]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_gates as bg;
+    use crate::bus;
+
+    #[test]
+    fn test_depth() {
+        let i = Depth(0);
+        assert_eq!(bg::not(i), Depth(1));
+        assert_eq!(bg::and(i, i), Depth(2));
+        assert_eq!(bg::or(i, i), Depth(2));
+        // the longest path counts, not the number of gates
+        assert_eq!(bg::or(bg::not(bg::not(i)), i), Depth(4));
+        assert_eq!(bus::or_nway(inputs::<16>()), Depth(8));
+        assert_eq!(bus::or_nway(inputs::<1>()), Depth(0));
+    }
+}
@@ -1,7 +1,12 @@
 pub mod basic_gates;
+pub mod bus;
+pub mod convert;
+pub mod depth;
 pub mod multibit_basic_gates;
 pub mod multiway_basic_gates;
 pub mod nand;
-pub type Arr16 = [bool; 16];
-pub type Arr8 = [bool; 8];
-pub type Arr4 = [bool; 4];
+pub mod netlist;
+pub mod truth_table;
+pub type Arr16 = bus::Bus<16>;
+pub type Arr8 = bus::Bus<8>;
+pub type Arr4 = bus::Bus<4>;
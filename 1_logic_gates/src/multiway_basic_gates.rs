@@ -1,32 +1,18 @@
 use crate::basic_gates::*;
+use crate::bus;
 use crate::multibit_basic_gates::*;
-use crate::{Arr4, Arr8, Arr16};
+use crate::nand::Signal;
 
-pub fn or8way(i: Arr8) -> bool {
-    or(
-        or(or(i[0], i[1]), or(i[2], i[3])),
-        or(or(i[4], i[5]), or(i[6], i[7])),
-    )
+pub fn or8way<S: Signal>(i: [S; 8]) -> S {
+    S::scope("or8way", || bus::or_nway(i))
 }
 
-pub fn or16way(i: Arr16) -> bool {
-    or(
-        or(
-            or(or(i[0], i[1]), or(i[2], i[3])),
-            or(or(i[4], i[5]), or(i[6], i[7])),
-        ),
-        or(
-            or(or(i[8], i[9]), or(i[10], i[11])),
-            or(or(i[12], i[13]), or(i[14], i[15])),
-        ),
-    )
+pub fn or16way<S: Signal>(i: [S; 16]) -> S {
+    S::scope("or16way", || bus::or_nway(i))
 }
 
-pub fn and8way(i: Arr8) -> bool {
-    and(
-        and(and(i[0], i[1]), and(i[2], i[3])),
-        and(and(i[4], i[5]), and(i[6], i[7])),
-    )
+pub fn and8way<S: Signal>(i: [S; 8]) -> S {
+    S::scope("and8way", || bus::and_nway(i))
 }
 
 /// s.0 s.1 |   |
@@ -35,8 +21,10 @@ pub fn and8way(i: Arr8) -> bool {
 ///  0   1  | b |
 ///  1   0  | c |
 ///  1   1  | d |
-pub fn mux4way16(a: Arr16, b: Arr16, c: Arr16, d: Arr16, s: [bool; 2]) -> Arr16 {
-    mux16(mux16(a, b, s[1]), mux16(c, d, s[1]), s[0])
+pub fn mux4way16<S: Signal>(a: [S; 16], b: [S; 16], c: [S; 16], d: [S; 16], s: [S; 2]) -> [S; 16] {
+    S::scope("mux4way16", || {
+        mux16(mux16(a, b, s[1]), mux16(c, d, s[1]), s[0])
+    })
 }
 
 /// s.0 s.1 s.2 |
@@ -48,22 +36,24 @@ pub fn mux4way16(a: Arr16, b: Arr16, c: Arr16, d: Arr16, s: [bool; 2]) -> Arr16
 ///  1   0   1  | f
 ///  1   1   0  | g
 ///  1   1   1  | h
-pub fn mux8way16(
-    a: Arr16,
-    b: Arr16,
-    c: Arr16,
-    d: Arr16,
-    e: Arr16,
-    f: Arr16,
-    g: Arr16,
-    h: Arr16,
-    s: [bool; 3],
-) -> Arr16 {
-    mux16(
-        mux4way16(a, b, c, d, [s[1], s[2]]),
-        mux4way16(e, f, g, h, [s[1], s[2]]),
-        s[0],
-    )
+pub fn mux8way16<S: Signal>(
+    a: [S; 16],
+    b: [S; 16],
+    c: [S; 16],
+    d: [S; 16],
+    e: [S; 16],
+    f: [S; 16],
+    g: [S; 16],
+    h: [S; 16],
+    s: [S; 3],
+) -> [S; 16] {
+    S::scope("mux8way16", || {
+        mux16(
+            mux4way16(a, b, c, d, [s[1], s[2]]),
+            mux4way16(e, f, g, h, [s[1], s[2]]),
+            s[0],
+        )
+    })
 }
 
 /// s.0 s.1
@@ -71,15 +61,17 @@ pub fn mux8way16(
 ///  0   1  | 00i0
 ///  1   0  | 0i00
 ///  1   1  | i000
-pub fn demux4way(i: bool, s: [bool; 2]) -> Arr4 {
-    let not_s0 = not(s[0]);
-    let not_s1 = not(s[1]);
-    [
-        demux(i, or(not_s0, not_s1))[1],
-        demux(i, or(not_s0, s[1]))[1],
-        demux(i, or(s[0], not_s1))[1],
-        demux(i, or(s[0], s[1]))[1],
-    ]
+pub fn demux4way<S: Signal>(i: S, s: [S; 2]) -> [S; 4] {
+    S::scope("demux4way", || {
+        let not_s0 = not(s[0]);
+        let not_s1 = not(s[1]);
+        [
+            demux(i, or(not_s0, not_s1))[1],
+            demux(i, or(not_s0, s[1]))[1],
+            demux(i, or(s[0], not_s1))[1],
+            demux(i, or(s[0], s[1]))[1],
+        ]
+    })
 }
 
 /// s.0 s.1 s.2   
@@ -91,29 +83,33 @@ pub fn demux4way(i: bool, s: [bool; 2]) -> Arr4 {
 ///  1   0   1  | 00i0 0000
 ///  1   1   0  | 0i00 0000
 ///  1   1   1  | i000 0000
-pub fn demux8way(i: bool, s: [bool; 3]) -> Arr8 {
-    let not_s0 = not(s[0]);
-    let not_s1 = not(s[1]);
-    let not_s2 = not(s[2]);
-    let or_nots0s1 = or(not_s0, not_s1);
-    let or_nots0_s1 = or(not_s0, s[1]);
-    let or_s0_nots1 = or(s[0], not_s1);
-    let or_s0s1 = or(s[0], s[1]);
-    [
-        demux(i, or(or_nots0s1, not_s2))[1],
-        demux(i, or(or_nots0s1, s[2]))[1],
-        demux(i, or(or_nots0_s1, not_s2))[1],
-        demux(i, or(or_nots0_s1, s[2]))[1],
-        demux(i, or(or_s0_nots1, not_s2))[1],
-        demux(i, or(or_s0_nots1, s[2]))[1],
-        demux(i, or(or_s0s1, not_s2))[1],
-        demux(i, or(or_s0s1, s[2]))[1],
-    ]
+pub fn demux8way<S: Signal>(i: S, s: [S; 3]) -> [S; 8] {
+    S::scope("demux8way", || {
+        let not_s0 = not(s[0]);
+        let not_s1 = not(s[1]);
+        let not_s2 = not(s[2]);
+        let or_nots0s1 = or(not_s0, not_s1);
+        let or_nots0_s1 = or(not_s0, s[1]);
+        let or_s0_nots1 = or(s[0], not_s1);
+        let or_s0s1 = or(s[0], s[1]);
+        [
+            demux(i, or(or_nots0s1, not_s2))[1],
+            demux(i, or(or_nots0s1, s[2]))[1],
+            demux(i, or(or_nots0_s1, not_s2))[1],
+            demux(i, or(or_nots0_s1, s[2]))[1],
+            demux(i, or(or_s0_nots1, not_s2))[1],
+            demux(i, or(or_s0_nots1, s[2]))[1],
+            demux(i, or(or_s0s1, not_s2))[1],
+            demux(i, or(or_s0s1, s[2]))[1],
+        ]
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::convert::value;
+    use crate::truth_table::check;
 
     #[test]
     fn test_or8way() {
@@ -366,4 +362,23 @@ mod tests {
             output
         );
     }
+
+    #[test]
+    fn test_demux_truth_tables() {
+        // out[N - 1 - k] is i when s is k, the other outputs are 0
+        assert_eq!(
+            check(
+                |[i, s0, s1]| demux4way(i, [s0, s1]),
+                |[i, s @ ..]| std::array::from_fn(|n| i && n == 3 - value(&s) as usize)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(
+                |[i, s0, s1, s2]| demux8way(i, [s0, s1, s2]),
+                |[i, s @ ..]| std::array::from_fn(|n| i && n == 7 - value(&s) as usize)
+            ),
+            Ok(())
+        );
+    }
 }
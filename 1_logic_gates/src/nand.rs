@@ -1,3 +1,65 @@
-pub fn nand(a: bool, b: bool) -> bool {
-    if a & b { false } else { true }
+#[cfg(feature = "count")]
+thread_local! {
+    static COUNT: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+/// What a wire carries. Chips are generic over it so that besides
+/// simulating them on bools, they can be evaluated on anything a NAND
+/// can be defined for, like the depth of the wires.
+pub trait Signal: Copy {
+    fn nand(a: Self, b: Self) -> Self;
+
+    /// A wire tied to 0 or 1.
+    fn constant(value: bool) -> Self;
+
+    /// Evaluates the body of the chip called name. Only signals that
+    /// record the structure of a chip, like netlist wires, look at it.
+    fn scope<T>(_name: &'static str, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+}
+
+/// With the count feature, every NAND is tallied on the thread that
+/// evaluates it, for count to measure how many NANDs a chip takes. It
+/// is opt-in as it slows down every simulation.
+impl Signal for bool {
+    fn nand(a: bool, b: bool) -> bool {
+        #[cfg(feature = "count")]
+        COUNT.with(|count| count.set(count.get() + 1));
+        !(a & b)
+    }
+
+    fn constant(value: bool) -> bool {
+        value
+    }
+}
+
+/// The one primitive gate, which every other chip is built from.
+pub fn nand<S: Signal>(a: S, b: S) -> S {
+    S::nand(a, b)
+}
+
+/// Runs f, returning its result and the number of NANDs it evaluated.
+#[cfg(feature = "count")]
+pub fn count<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let start = COUNT.with(|count| count.get());
+    let result = f();
+    (result, COUNT.with(|count| count.get()) - start)
+}
+
+#[cfg(all(test, feature = "count"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count(|| nand(true, true)), (false, 1));
+        assert_eq!(count(|| nand(nand(true, false), false)).1, 2);
+        // nested counts each see their own gates
+        let ((_, inner), outer) = count(|| {
+            nand(false, false);
+            count(|| nand(true, true))
+        });
+        assert_eq!((inner, outer), (1, 2));
+    }
 }
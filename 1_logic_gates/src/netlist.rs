@@ -0,0 +1,601 @@
+use crate::nand::Signal;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+/// A wire of a chip being traced: the index of the node driving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Wire(pub usize);
+
+/// What drives a wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    /// A bit of an input pin.
+    Input,
+    Constant(bool),
+    Nand(Wire, Wire),
+}
+
+/// A pin of a netlist and its wires, MSB-first like the arrays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub wires: Vec<Wire>,
+}
+
+/// A sub-chip evaluated while tracing and the nodes it added. Chips
+/// run one after the other, so their nodes are a range, and the scopes
+/// of the chips they use are ranges inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    pub name: String,
+    pub nodes: Range<usize>,
+}
+
+/// The NAND gates of a chip and how they are wired, as traced by
+/// evaluating the chip on Wires.
+///
+/// Nodes only read wires of nodes before them, so evaluating them in
+/// order evaluates the chip. Scopes are in the order their chips were
+/// called, so a scope comes before the ones nested in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Netlist {
+    pub inputs: Vec<Pin>,
+    pub outputs: Vec<Pin>,
+    pub nodes: Vec<Node>,
+    pub scopes: Vec<Scope>,
+}
+
+thread_local! {
+    static TRACE: RefCell<Option<Netlist>> = const { RefCell::new(None) };
+}
+
+/// Runs f on the netlist being traced on this thread.
+fn traced<T>(f: impl FnOnce(&mut Netlist) -> T) -> T {
+    TRACE.with(|trace| {
+        let mut trace = trace.borrow_mut();
+        f(trace.as_mut().expect("wires only exist inside trace"))
+    })
+}
+
+/// Adds a node to the netlist being traced.
+fn push(node: Node) -> Wire {
+    traced(|netlist| {
+        netlist.nodes.push(node);
+        Wire(netlist.nodes.len() - 1)
+    })
+}
+
+/// Every NAND on wires adds a node to the netlist being traced.
+impl Signal for Wire {
+    fn nand(a: Wire, b: Wire) -> Wire {
+        push(Node::Nand(a, b))
+    }
+
+    fn constant(value: bool) -> Wire {
+        push(Node::Constant(value))
+    }
+
+    /// Chips that add no nodes, only wiring, leave no scope.
+    fn scope<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+        let k = traced(|netlist| {
+            let start = netlist.nodes.len();
+            netlist.scopes.push(Scope {
+                name: name.to_string(),
+                nodes: start..start,
+            });
+            netlist.scopes.len() - 1
+        });
+        let result = f();
+        traced(|netlist| {
+            let end = netlist.nodes.len();
+            if netlist.scopes[k].nodes.start == end {
+                // the scopes nested in it are empty too, and gone
+                netlist.scopes.truncate(k);
+            } else {
+                netlist.scopes[k].nodes.end = end;
+            }
+        });
+        result
+    }
+}
+
+/// Declares the pins of the chip being traced.
+pub struct Tracer {
+    _private: (),
+}
+
+impl Tracer {
+    /// A new input pin of N bits.
+    pub fn input<const N: usize>(&mut self, name: &str) -> [Wire; N] {
+        let wires = std::array::from_fn(|_| push(Node::Input));
+        traced(|netlist| {
+            netlist.inputs.push(Pin {
+                name: name.to_string(),
+                wires: wires.to_vec(),
+            })
+        });
+        wires
+    }
+
+    /// Makes wires an output pin.
+    pub fn output(&mut self, name: &str, wires: &[Wire]) {
+        traced(|netlist| {
+            netlist.outputs.push(Pin {
+                name: name.to_string(),
+                wires: wires.to_vec(),
+            })
+        });
+    }
+}
+
+/// Clears the trace of this thread even if the traced chip panics.
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        TRACE.with(|trace| trace.borrow_mut().take());
+    }
+}
+
+/// The netlist of the chip f evaluates on the inputs it declares:
+///
+/// trace(|t| {
+///     let [a, b] = t.input("in");
+///     t.output("out", &[bg::xor(a, b)]);
+/// })
+pub fn trace(f: impl FnOnce(&mut Tracer)) -> Netlist {
+    TRACE.with(|trace| {
+        let mut trace = trace.borrow_mut();
+        assert!(trace.is_none(), "trace can't be nested");
+        *trace = Some(Netlist::default());
+    });
+    let _guard = Guard;
+    f(&mut Tracer { _private: () });
+    TRACE.with(|trace| trace.borrow_mut().take().unwrap())
+}
+
+impl Netlist {
+    /// The number of NAND gates.
+    pub fn nands(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Nand(..)))
+            .count()
+    }
+
+    /// The value of every output pin for the values of the input pins.
+    pub fn eval(&self, inputs: &[&[bool]]) -> Vec<Vec<bool>> {
+        assert_eq!(inputs.len(), self.inputs.len(), "one value per input pin");
+        let mut values = vec![false; self.nodes.len()];
+        for (pin, bits) in self.inputs.iter().zip(inputs) {
+            assert_eq!(pin.wires.len(), bits.len(), "width of pin {}", pin.name);
+            for (wire, &bit) in pin.wires.iter().zip(bits.iter()) {
+                values[wire.0] = bit;
+            }
+        }
+        for (k, node) in self.nodes.iter().enumerate() {
+            match *node {
+                Node::Input => {}
+                Node::Constant(value) => values[k] = value,
+                Node::Nand(a, b) => values[k] = !(values[a.0] && values[b.0]),
+            }
+        }
+        self.outputs
+            .iter()
+            .map(|pin| pin.wires.iter().map(|w| values[w.0]).collect())
+            .collect()
+    }
+
+    /// Reads a netlist in the format Display writes.
+    pub fn parse(text: &str) -> Result<Netlist, NetlistError> {
+        let mut netlist = Netlist::default();
+        // the scopes begun and not ended yet, with their lines
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        for (k, line) in text.lines().enumerate() {
+            let error = |message: &str| NetlistError {
+                line: k + 1,
+                message: message.to_string(),
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let wires = netlist.nodes.len();
+            let wire = |word: &str| match word.parse() {
+                Ok(w) if w < wires => Ok(Wire(w)),
+                Ok(_) => Err(error(&format!("wire {} is not defined yet", word))),
+                Err(_) => Err(error(&format!("'{}' is not a wire", word))),
+            };
+            match words[..] {
+                [] => {}
+                ["const", "0"] => netlist.nodes.push(Node::Constant(false)),
+                ["const", "1"] => netlist.nodes.push(Node::Constant(true)),
+                ["nand", a, b] => netlist.nodes.push(Node::Nand(wire(a)?, wire(b)?)),
+                ["input", name, width] => {
+                    let width: usize = width.parse().map_err(|_| error("bad input width"))?;
+                    if width == 0 {
+                        return Err(error(&format!("input {} has no wires", name)));
+                    }
+                    let wires = (wires..wires + width).map(Wire).collect();
+                    netlist.nodes.extend((0..width).map(|_| Node::Input));
+                    netlist.inputs.push(Pin {
+                        name: name.to_string(),
+                        wires,
+                    });
+                }
+                ["begin", name] => {
+                    open.push((netlist.scopes.len(), k + 1));
+                    netlist.scopes.push(Scope {
+                        name: name.to_string(),
+                        nodes: wires..wires,
+                    });
+                }
+                ["end"] => {
+                    let (scope, _) = open.pop().ok_or_else(|| error("end without begin"))?;
+                    // as in a trace, where chips without nodes leave no scope
+                    if netlist.scopes[scope].nodes.start == wires {
+                        let name = &netlist.scopes[scope].name;
+                        return Err(error(&format!("{} has no nodes", name)));
+                    }
+                    netlist.scopes[scope].nodes.end = wires;
+                }
+                ["output", name, ref bits @ ..] => {
+                    let wires = bits.iter().map(|w| wire(w)).collect::<Result<_, _>>()?;
+                    netlist.outputs.push(Pin {
+                        name: name.to_string(),
+                        wires,
+                    });
+                }
+                _ => return Err(error(&format!("unknown line '{}'", line))),
+            }
+        }
+        if let Some(&(scope, line)) = open.last() {
+            return Err(NetlistError {
+                line,
+                message: format!("{} has no end", netlist.scopes[scope].name),
+            });
+        }
+        Ok(netlist)
+    }
+
+    /// The netlist as a Graphviz graph, for `dot -Tsvg`, with the
+    /// sub-chips up to levels deep drawn as clusters. A scope holding
+    /// every gate is the chip itself, and names the graph instead.
+    pub fn dot(&self, levels: usize) -> String {
+        let mut dot = String::from("digraph {\n");
+        dot.push_str("    rankdir=LR;\n    node [shape=box, label=\"nand\"];\n");
+        let gates = self.nodes.iter().enumerate();
+        let mut gates = gates.filter(|(_, node)| !matches!(node, Node::Input));
+        let mut first = 0;
+        if let Some(chip) = self.scopes.first()
+            && gates.all(|(k, _)| chip.nodes.contains(&k))
+        {
+            writeln!(dot, "    label=\"{}\";", chip.name).unwrap();
+            first = 1;
+        }
+        self.cluster(
+            &mut dot,
+            0..self.nodes.len(),
+            first..self.scopes.len(),
+            levels,
+            1,
+        );
+
+        for (p, pin) in self.outputs.iter().enumerate() {
+            for (i, wire) in pin.wires.iter().enumerate() {
+                let label = bit(pin, i);
+                writeln!(
+                    dot,
+                    "    o{}_{} [label=\"{}\", shape=plaintext];",
+                    p, i, label
+                )
+                .unwrap();
+                writeln!(dot, "    n{} -> o{}_{};", wire.0, p, i).unwrap();
+            }
+        }
+        for (k, node) in self.nodes.iter().enumerate() {
+            if let Node::Nand(a, b) = node {
+                writeln!(dot, "    n{} -> n{};", a.0, k).unwrap();
+                // a NOT is drawn with one edge
+                if b != a {
+                    writeln!(dot, "    n{} -> n{};", b.0, k).unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the nodes of a range, the scopes among them that start
+    /// a sub-chip as clusters while levels are left.
+    fn cluster(
+        &self,
+        dot: &mut String,
+        nodes: Range<usize>,
+        scopes: Range<usize>,
+        levels: usize,
+        depth: usize,
+    ) {
+        let indent = "    ".repeat(depth);
+        let mut k = nodes.start;
+        let mut i = scopes.start;
+        while k < nodes.end {
+            match self.scopes.get(i) {
+                Some(scope) if i < scopes.end && levels > 0 && scope.nodes.start == k => {
+                    // the scopes nested in this one follow it
+                    let inner = self.scopes[i + 1..scopes.end]
+                        .iter()
+                        .take_while(|s| s.nodes.start < scope.nodes.end)
+                        .count();
+                    writeln!(dot, "{}subgraph cluster_{} {{", indent, i).unwrap();
+                    writeln!(dot, "{}    label=\"{}\";", indent, scope.name).unwrap();
+                    let inner = i + 1..i + 1 + inner;
+                    self.cluster(
+                        dot,
+                        scope.nodes.clone(),
+                        inner.clone(),
+                        levels - 1,
+                        depth + 1,
+                    );
+                    writeln!(dot, "{}}}", indent).unwrap();
+                    k = scope.nodes.end;
+                    i = inner.end;
+                }
+                _ => {
+                    match self.nodes[k] {
+                        Node::Input => {
+                            let (pin, i) = self.input(Wire(k));
+                            let label = bit(pin, i);
+                            writeln!(
+                                dot,
+                                "{}n{} [label=\"{}\", shape=plaintext];",
+                                indent, k, label
+                            )
+                        }
+                        Node::Constant(value) => {
+                            let value = value as u8;
+                            writeln!(
+                                dot,
+                                "{}n{} [label=\"{}\", shape=plaintext];",
+                                indent, k, value
+                            )
+                        }
+                        Node::Nand(..) => writeln!(dot, "{}n{};", indent, k),
+                    }
+                    .unwrap();
+                    k += 1;
+                }
+            }
+        }
+    }
+
+    /// The input pin a wire is a bit of, and its index in the pin.
+    fn input(&self, wire: Wire) -> (&Pin, usize) {
+        self.inputs
+            .iter()
+            .find_map(|pin| Some((pin, pin.wires.iter().position(|&w| w == wire)?)))
+            .expect("input wires belong to a pin")
+    }
+}
+
+/// The name of the bit at index i of a pin, numbered like in HDL with
+/// bit 0 the LSB: out[15] is the first wire of a 16 bit out.
+fn bit(pin: &Pin, i: usize) -> String {
+    match pin.wires.len() {
+        1 => pin.name.clone(),
+        n => format!("{}[{}]", pin.name, n - 1 - i),
+    }
+}
+
+/// One line per node, a node driving the wire numbered after the nodes
+/// before it, then the output pins:
+///
+/// input in 2
+/// begin and
+///   nand 0 1
+///   begin not
+///     nand 2 2
+///   end
+/// end
+/// output out 3
+///
+/// Inputs are written where their first wire is, their wires in a row.
+/// The nodes of a scope are between its begin and end, indented.
+impl fmt::Display for Netlist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut scopes = self.scopes.iter().peekable();
+        // the ends of the scopes begun and not ended yet
+        let mut open: Vec<usize> = Vec::new();
+        for (k, node) in self.nodes.iter().enumerate() {
+            while let Some(scope) = scopes.next_if(|s| s.nodes.start == k) {
+                writeln!(f, "{:2$}begin {}", "", scope.name, 2 * open.len())?;
+                open.push(scope.nodes.end);
+            }
+            let indent = " ".repeat(2 * open.len());
+            match node {
+                Node::Input => {
+                    if let Some(pin) = self
+                        .inputs
+                        .iter()
+                        .find(|p| p.wires.first() == Some(&Wire(k)))
+                    {
+                        writeln!(f, "{}input {} {}", indent, pin.name, pin.wires.len())?;
+                    }
+                }
+                Node::Constant(value) => writeln!(f, "{}const {}", indent, *value as u8)?,
+                Node::Nand(a, b) => writeln!(f, "{}nand {} {}", indent, a.0, b.0)?,
+            }
+            while open.last() == Some(&(k + 1)) {
+                open.pop();
+                writeln!(f, "{:1$}end", "", 2 * open.len())?;
+            }
+        }
+        for pin in &self.outputs {
+            write!(f, "output {}", pin.name)?;
+            for wire in &pin.wires {
+                write!(f, " {}", wire.0)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetlistError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for NetlistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for NetlistError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_gates as bg;
+    use crate::nand::nand;
+    use crate::truth_table::check;
+
+    fn xor() -> Netlist {
+        trace(|t| {
+            let [a, b] = t.input("in");
+            t.output("out", &[bg::xor(a, b)]);
+        })
+    }
+
+    #[test]
+    fn test_trace() {
+        let and = trace(|t| {
+            let [a] = t.input("a");
+            let [b] = t.input("b");
+            t.output("out", &[bg::and(a, b)]);
+        });
+        assert_eq!(
+            and.nodes,
+            [
+                Node::Input,
+                Node::Input,
+                Node::Nand(Wire(0), Wire(1)),
+                Node::Nand(Wire(2), Wire(2)),
+            ]
+        );
+        assert_eq!(and.outputs[0].wires, [Wire(3)]);
+
+        let xor = xor();
+        assert_eq!(xor.nands(), 11);
+        let eval = |[a, b]: [bool; 2]| [xor.eval(&[&[a, b]])[0][0]];
+        assert_eq!(check(eval, |[a, b]| [a != b]), Ok(()));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let scope = |name: &str, nodes| Scope {
+            name: name.to_string(),
+            nodes,
+        };
+        let netlist = trace(|t| {
+            let [a, b] = t.input("in");
+            // wiring alone leaves no scope
+            let [b, a] = Wire::scope("swap", || [a, b]);
+            t.output("out", &[bg::or(a, b)]);
+        });
+        assert_eq!(
+            netlist.scopes,
+            [scope("or", 2..5), scope("not", 2..3), scope("not", 3..4)]
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let netlist = trace(|t| {
+            let [a] = t.input("a");
+            let [b, c] = t.input("bc");
+            t.output("out", &[bg::and(a, b), c]);
+        });
+        let dot = "\
+digraph {
+    rankdir=LR;
+    node [shape=box, label=\"nand\"];
+    label=\"and\";
+    n0 [label=\"a\", shape=plaintext];
+    n1 [label=\"bc[1]\", shape=plaintext];
+    n2 [label=\"bc[0]\", shape=plaintext];
+    n3;
+    subgraph cluster_1 {
+        label=\"not\";
+        n4;
+    }
+    o0_0 [label=\"out[1]\", shape=plaintext];
+    n4 -> o0_0;
+    o0_1 [label=\"out[0]\", shape=plaintext];
+    n2 -> o0_1;
+    n0 -> n3;
+    n1 -> n3;
+    n3 -> n4;
+}
+";
+        assert_eq!(netlist.dot(usize::MAX), dot);
+        // without levels the NOT is not a cluster, only the chip is named
+        assert!(netlist.dot(0).contains("    n3;\n    n4;\n"));
+        assert!(!netlist.dot(0).contains("subgraph"));
+    }
+
+    #[test]
+    fn test_format() {
+        let netlist = trace(|t| {
+            let [a] = t.input("a");
+            let and = bg::and(a, a);
+            let [b, c] = t.input("bc");
+            t.output("out", &[and, nand(b, c), Wire::constant(true)]);
+        });
+        let text = "\
+input a 1
+begin and
+  nand 0 0
+  begin not
+    nand 1 1
+  end
+end
+input bc 2
+nand 3 4
+const 1
+output out 2 5 6
+";
+        assert_eq!(netlist.to_string(), text);
+        assert_eq!(Netlist::parse(text), Ok(netlist));
+        let xor = xor();
+        assert_eq!(Netlist::parse(&xor.to_string()), Ok(xor));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text| Netlist::parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("input a 1\nnand 0 1"),
+            "line 2: wire 1 is not defined yet"
+        );
+        assert_eq!(error("const 2"), "line 1: unknown line 'const 2'");
+        assert_eq!(error("input a 1\noutput x a"), "line 2: 'a' is not a wire");
+        assert_eq!(error("input a 1\nend"), "line 2: end without begin");
+        assert_eq!(
+            error("input a 1\nbegin not\nnand 0 0"),
+            "line 2: not has no end"
+        );
+        assert_eq!(
+            error("input a 1\nbegin not\nend"),
+            "line 3: not has no nodes"
+        );
+        assert_eq!(error("input a 0"), "line 1: input a has no wires");
+    }
+
+    #[test]
+    #[should_panic(expected = "wires only exist inside trace")]
+    fn test_outside_trace() {
+        bg::not(Wire(0));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::convert::{bits, format as binary};
+use std::fmt;
+
+/// Every combination of I inputs, counting from all 0s to all 1s with
+/// the first input as the MSB, with the outputs of the chip for it.
+///
+/// I is at most 16, 65536 rows.
+pub fn rows<const I: usize, const O: usize>(
+    chip: impl Fn([bool; I]) -> [bool; O],
+) -> Vec<([bool; I], [bool; O])> {
+    assert!(I <= 16, "{} inputs is too many to enumerate", I);
+    (0..1u32 << I)
+        .map(|n| {
+            let inputs = bits::<I>(n as u16);
+            (inputs, chip(inputs))
+        })
+        .collect()
+}
+
+/// A row where the chip and the reference disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch<const I: usize, const O: usize> {
+    pub inputs: [bool; I],
+    pub found: [bool; O],
+    pub expected: [bool; O],
+}
+
+impl<const I: usize, const O: usize> fmt::Display for Mismatch<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "for inputs {}: found {}, expected {}",
+            binary(&self.inputs),
+            binary(&self.found),
+            binary(&self.expected)
+        )
+    }
+}
+
+/// Compares the chip with a reference on every row, stopping at the
+/// first one where they differ.
+pub fn check<const I: usize, const O: usize>(
+    chip: impl Fn([bool; I]) -> [bool; O],
+    reference: impl Fn([bool; I]) -> [bool; O],
+) -> Result<(), Mismatch<I, O>> {
+    for (inputs, found) in rows(chip) {
+        let expected = reference(inputs);
+        if found != expected {
+            return Err(Mismatch {
+                inputs,
+                found,
+                expected,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The text of a column, centered on a field as wide as the wider of
+/// the name and the value, as in `carry` over `  0  `.
+fn cell(text: &str, name: &str, width: usize) -> String {
+    let space = name.len().max(width);
+    let left = (space - text.len()) / 2;
+    format!(
+        "{}{}{}",
+        " ".repeat(left),
+        text,
+        " ".repeat(space - left - text.len())
+    )
+}
+
+/// One line of a table, the columns and their texts side by side.
+fn line(columns: &[(&str, usize)], texts: &[String]) -> String {
+    let cells: Vec<String> = columns
+        .iter()
+        .zip(texts)
+        .map(|(&(name, width), text)| cell(text, name, width))
+        .collect();
+    cells.join(" ")
+}
+
+/// The truth table of a chip as in the doc comments of the gates:
+///
+/// a b | carry sum
+/// 0 0 |   0    0
+/// ...
+///
+/// Columns are (name, bits), the bits of a column shown as one binary
+/// number, and they add up to the inputs and outputs of the chip.
+pub fn format<const I: usize, const O: usize>(
+    inputs: &[(&str, usize)],
+    outputs: &[(&str, usize)],
+    chip: impl Fn([bool; I]) -> [bool; O],
+) -> String {
+    let width = |columns: &[(&str, usize)]| columns.iter().map(|&(_, w)| w).sum::<usize>();
+    assert_eq!(width(inputs), I, "the input columns have {} bits", I);
+    assert_eq!(width(outputs), O, "the output columns have {} bits", O);
+
+    let names = |columns: &[(&str, usize)]| -> Vec<String> {
+        columns.iter().map(|&(name, _)| name.to_string()).collect()
+    };
+    let values = |columns: &[(&str, usize)], bits: &[bool]| -> Vec<String> {
+        let mut start = 0;
+        columns
+            .iter()
+            .map(|&(_, width)| {
+                start += width;
+                binary(&bits[start - width..start])
+            })
+            .collect()
+    };
+    let row = |i: Vec<String>, o: Vec<String>| {
+        let text = format!("{} | {}", line(inputs, &i), line(outputs, &o));
+        text.trim_end().to_string()
+    };
+
+    let mut table = row(names(inputs), names(outputs));
+    table.push('\n');
+    for (i, o) in rows(chip) {
+        table.push_str(&row(values(inputs, &i), values(outputs, &o)));
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let rows = rows(|[a, b]| [a && !b]);
+        assert_eq!(
+            rows,
+            [
+                ([false, false], [false]),
+                ([false, true], [false]),
+                ([true, false], [true]),
+                ([true, true], [false]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check(|[a, b]| [a != b], |[a, b]| [a ^ b]), Ok(()));
+        let mismatch = check(|[a, b, c]| [a || b, c], |[a, b, c]| [a || b, !c]).unwrap_err();
+        assert_eq!(mismatch.inputs, [false, false, false]);
+        assert_eq!(
+            mismatch.to_string(),
+            "for inputs 000: found 00, expected 01"
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let table = format(&[("in", 1), ("sel", 2)], &[("out", 1)], |[i, a, b]| {
+            [i && a && b]
+        });
+        assert_eq!(
+            table,
+            "\
+in sel | out
+0  00  |  0
+0  01  |  0
+0  10  |  0
+0  11  |  0
+1  00  |  0
+1  01  |  0
+1  10  |  0
+1  11  |  1
+"
+        );
+    }
+}
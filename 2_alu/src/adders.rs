@@ -1,28 +1,51 @@
 use logic_gates::basic_gates as bg;
-use logic_gates::{Arr4, Arr8, Arr16};
-
+use logic_gates::bus::Bus;
+use logic_gates::nand::Signal;
+
+/// ```
+/// # use alu::adders::half_adder;
+/// # use logic_gates::truth_table::format;
+/// let outputs = [("carry", 1), ("sum", 1)];
+/// let table = format(&[("a", 1), ("b", 1)], &outputs, |[a, b]| half_adder(a, b));
+/// assert_eq!(table, "\
 /// a b | carry sum
 /// 0 0 |   0    0
 /// 0 1 |   0    1
 /// 1 0 |   0    1
 /// 1 1 |   1    0
+/// ");
+/// ```
 ///
 /// Carry is [0], Sum is [1]
-pub fn half_adder(a: bool, b: bool) -> [bool; 2] {
-    [bg::and(a, b), bg::xor(a, b)]
+pub fn half_adder<S: Signal>(a: S, b: S) -> [S; 2] {
+    S::scope("half_adder", || [bg::and(a, b), bg::xor(a, b)])
 }
 
-/// a b c | ab c | absc | carry sum
-///
-/// 0 0 0 | 00 0 |  00  |   0    0
-/// 0 0 1 | 00 1 |  01  |   0    1
-/// 0 1 0 | 01 0 |  01  |   0    1
-/// 1 0 0 | 01 0 |  01  |   0    1
-/// 0 1 1 | 01 1 |  10  |   1    0
-/// 1 0 1 | 01 1 |  10  |   1    0
-/// 1 1 0 | 10 0 |  00  |   1    0
-/// 1 1 1 | 10 1 |  01  |   1    1
-pub fn full_adder(a: bool, b: bool, c: bool) -> [bool; 2] {
+/// ab is half_adder(a, b), absc half_adder of the sum of ab and c:
+/// ```
+/// # use alu::adders::{full_adder, half_adder};
+/// # use logic_gates::truth_table::format;
+/// let inputs = [("a", 1), ("b", 1), ("c", 1)];
+/// let outputs = [("ab", 2), ("absc", 2), ("carry", 1), ("sum", 1)];
+/// let table = format(&inputs, &outputs, |[a, b, c]| {
+///     let ab = half_adder(a, b);
+///     let absc = half_adder(ab[1], c);
+///     let [carry, sum] = full_adder(a, b, c);
+///     [ab[0], ab[1], absc[0], absc[1], carry, sum]
+/// });
+/// assert_eq!(table, "\
+/// a b c | ab absc carry sum
+/// 0 0 0 | 00  00    0    0
+/// 0 0 1 | 00  01    0    1
+/// 0 1 0 | 01  01    0    1
+/// 0 1 1 | 01  10    1    0
+/// 1 0 0 | 01  01    0    1
+/// 1 0 1 | 01  10    1    0
+/// 1 1 0 | 10  00    1    0
+/// 1 1 1 | 10  01    1    1
+/// ");
+/// ```
+pub fn full_adder<S: Signal>(a: S, b: S, c: S) -> [S; 2] {
     // original solution:
     // [
     //     bg::xor(half_adder(a, b)[0], half_adder(half_adder(a, b)[1], c)[0]),
@@ -41,79 +64,64 @@ pub fn full_adder(a: bool, b: bool, c: bool) -> [bool; 2] {
     // I use variable asaignment now because that better
     // simulates how the gates are contructed physically.
 
-    let a_b = half_adder(a, b);
-    let absum_c = half_adder(a_b[1], c);
-    [bg::or(a_b[0], absum_c[0]), absum_c[1]]
+    S::scope("full_adder", || {
+        let a_b = half_adder(a, b);
+        let absum_c = half_adder(a_b[1], c);
+        [bg::or(a_b[0], absum_c[0]), absum_c[1]]
+    })
 }
 
-pub fn adder4(a: Arr4, b: Arr4) -> Arr4 {
-    let _0 = full_adder(a[3], b[3], false);
-    let _1 = full_adder(a[2], b[2], _0[0]);
-    let _2 = full_adder(a[1], b[1], _1[0]);
-    let _3 = full_adder(a[0], b[0], _2[0]);
-    [_3[1], _2[1], _1[1], _0[1]]
+/// Ripple carry adder: each full adder takes the carry of the bit on
+/// its right, the first one a 0. The carry out of the MSB is dropped.
+pub fn adder<S: Signal, const N: usize>(a: Bus<N, S>, b: Bus<N, S>) -> Bus<N, S> {
+    // no placeholder constants for the sum, a netlist would keep them
+    let mut sum = [None; N];
+    let mut carry = S::constant(false);
+    for k in (0..N).rev() {
+        let [c, s] = full_adder(a[k], b[k], carry);
+        sum[k] = Some(s);
+        carry = c;
+    }
+    sum.map(Option::unwrap)
 }
 
-pub fn adder8(a: Arr8, b: Arr8) -> Arr8 {
-    let _0 = full_adder(a[7], b[7], false);
-    let _1 = full_adder(a[6], b[6], _0[0]);
-    let _2 = full_adder(a[5], b[5], _1[0]);
-    let _3 = full_adder(a[4], b[4], _2[0]);
-
-    let _4 = full_adder(a[3], b[3], _3[0]);
-    let _5 = full_adder(a[2], b[2], _4[0]);
-    let _6 = full_adder(a[1], b[1], _5[0]);
-    let _7 = full_adder(a[0], b[0], _6[0]);
-    [_7[1], _6[1], _5[1], _4[1], _3[1], _2[1], _1[1], _0[1]]
+pub fn incrementer<S: Signal, const N: usize>(a: Bus<N, S>) -> Bus<N, S> {
+    let mut one = [S::constant(false); N];
+    if N > 0 {
+        one[N - 1] = S::constant(true);
+    }
+    adder(a, one)
 }
 
-pub fn adder16(a: Arr16, b: Arr16) -> Arr16 {
-    let _0 = full_adder(a[15], b[15], false);
-    let _1 = full_adder(a[14], b[14], _0[0]);
-    let _2 = full_adder(a[13], b[13], _1[0]);
-    let _3 = full_adder(a[12], b[12], _2[0]);
-
-    let _4 = full_adder(a[11], b[11], _3[0]);
-    let _5 = full_adder(a[10], b[10], _4[0]);
-    let _6 = full_adder(a[9], b[9], _5[0]);
-    let _7 = full_adder(a[8], b[8], _6[0]);
+pub fn adder4<S: Signal>(a: [S; 4], b: [S; 4]) -> [S; 4] {
+    S::scope("adder4", || adder(a, b))
+}
 
-    let _8 = full_adder(a[7], b[7], _7[0]);
-    let _9 = full_adder(a[6], b[6], _8[0]);
-    let _10 = full_adder(a[5], b[5], _9[0]);
-    let _11 = full_adder(a[4], b[4], _10[0]);
+pub fn adder8<S: Signal>(a: [S; 8], b: [S; 8]) -> [S; 8] {
+    S::scope("adder8", || adder(a, b))
+}
 
-    let _12 = full_adder(a[3], b[3], _11[0]);
-    let _13 = full_adder(a[2], b[2], _12[0]);
-    let _14 = full_adder(a[1], b[1], _13[0]);
-    let _15 = full_adder(a[0], b[0], _14[0]);
-    [
-        _15[1], _14[1], _13[1], _12[1], _11[1], _10[1], _9[1], _8[1], _7[1], _6[1], _5[1], _4[1],
-        _3[1], _2[1], _1[1], _0[1],
-    ]
+pub fn adder16<S: Signal>(a: [S; 16], b: [S; 16]) -> [S; 16] {
+    S::scope("adder16", || adder(a, b))
 }
 
-pub fn incrementer4(a: Arr4) -> Arr4 {
-    adder4(a, [false, false, false, true])
+pub fn incrementer4<S: Signal>(a: [S; 4]) -> [S; 4] {
+    S::scope("incrementer4", || incrementer(a))
 }
 
-pub fn incrementer8(a: Arr8) -> Arr8 {
-    adder8(a, [false, false, false, false, false, false, false, true])
+pub fn incrementer8<S: Signal>(a: [S; 8]) -> [S; 8] {
+    S::scope("incrementer8", || incrementer(a))
 }
 
-pub fn incrementer16(a: Arr16) -> Arr16 {
-    adder16(
-        a,
-        [
-            false, false, false, false, false, false, false, false, false, false, false, false,
-            false, false, false, true,
-        ],
-    )
+pub fn incrementer16<S: Signal>(a: [S; 16]) -> [S; 16] {
+    S::scope("incrementer16", || incrementer(a))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use logic_gates::convert::*;
+    use logic_gates::truth_table::check;
 
     #[test]
     pub fn test_half_adder() {
@@ -136,155 +144,103 @@ mod tests {
     }
 
     #[test]
-    pub fn test_adder4() {
-        let _0 = [false, false, false, false];
-        let _1 = [false, false, false, true];
-        let _15 = [true, true, true, true];
-
-        let _1 = adder4(_0, _1);
-        let _2 = adder4(_1, _1);
-        let _4 = adder4(_2, _2);
-        let _8 = adder4(_4, _4);
-
-        let r = _0;
-        let r = adder4(r, _8);
-        let r = adder4(r, _4);
-        let r = adder4(r, _2);
-        let r = adder4(r, _1);
-        let r = adder4(r, _0);
+    pub fn test_truth_tables() {
+        // carry and sum are the two bits of the number of inputs set
+        let count = |i: [bool; 3]| bits::<2>(i.iter().filter(|&&b| b).count() as u16);
+        assert_eq!(check(|[a, b, c]| full_adder(a, b, c), count), Ok(()));
+    }
 
-        assert_eq!(r, _15);
-        assert_eq!(adder4(r, _1), _0);
+    #[test]
+    pub fn test_adder4() {
+        for x in 0..16 {
+            for y in 0..16 {
+                let sum = adder4(from_u4(x), from_u4(y));
+                assert_eq!(to_u4(sum), (x + y) % 16, "{} + {}", x, y);
+            }
+        }
     }
 
     #[test]
     pub fn test_adder8() {
-        let _0 = [false, false, false, false, false, false, false, false];
-        let _1 = [false, false, false, false, false, false, false, true];
-        let _255 = [true, true, true, true, true, true, true, true];
-
-        let _0 = adder8(_0, _0);
-        let _1 = adder8(_0, _1);
-        let _2 = adder8(_1, _1);
-        let _4 = adder8(_2, _2);
-        let _8 = adder8(_4, _4);
-        let _16 = adder8(_8, _8);
-        let _32 = adder8(_16, _16);
-        let _64 = adder8(_32, _32);
-        let _128 = adder8(_64, _64);
-
-        let r = _0;
-        let r = adder8(r, _8);
-        let r = adder8(r, _4);
-        let r = adder8(r, _2);
-        let r = adder8(r, _1);
-
-        let r = adder8(r, _128);
-        let r = adder8(r, _64);
-        let r = adder8(r, _32);
-        let r = adder8(r, _16);
-
-        let r = adder8(r, _0);
-
-        assert_eq!(r, _255);
-        assert_eq!(adder8(r, _1), _0);
+        for x in 0..=255 {
+            for y in 0..=255 {
+                let sum = adder8(from_u8(x), from_u8(y));
+                assert_eq!(to_u8(sum), x.wrapping_add(y), "{} + {}", x, y);
+            }
+        }
     }
 
     #[test]
     pub fn test_adder16() {
-        let _0 = [
-            false, false, false, false, false, false, false, false, false, false, false, false,
-            false, false, false, false,
-        ];
-        let _1 = [
-            false, false, false, false, false, false, false, false, false, false, false, false,
-            false, false, false, true,
+        let values = [
+            0, 1, 2, 0x00F0, 0x0F0F, 0x7FFF, 0x8000, 0xAAAA, 0x5555, 0xFFFE, 0xFFFF,
         ];
-        let _65535 = [
-            true, true, true, true, true, true, true, true, true, true, true, true, true, true,
-            true, true,
-        ];
-
-        let _0 = adder16(_0, _0);
-        let _1 = adder16(_0, _1);
-        let _2 = adder16(_1, _1);
-        let _4 = adder16(_2, _2);
-        let _8 = adder16(_4, _4);
-        let _16 = adder16(_8, _8);
-        let _32 = adder16(_16, _16);
-        let _64 = adder16(_32, _32);
-        let _128 = adder16(_64, _64);
-        let _256 = adder16(_128, _128);
-        let _512 = adder16(_256, _256);
-        let _1024 = adder16(_512, _512);
-        let _2048 = adder16(_1024, _1024);
-        let _4096 = adder16(_2048, _2048);
-        let _8192 = adder16(_4096, _4096);
-        let _16384 = adder16(_8192, _8192);
-        let _32768 = adder16(_16384, _16384);
-
-        let r = _0;
-        let r = adder16(r, _128);
-        let r = adder16(r, _64);
-        let r = adder16(r, _32);
-        let r = adder16(r, _16);
-        let r = adder16(r, _0);
-
-        let r = adder16(r, _2048);
-        let r = adder16(r, _1024);
-        let r = adder16(r, _512);
-        let r = adder16(r, _256);
+        for x in values {
+            for y in values {
+                let sum = adder16(from_u16(x), from_u16(y));
+                assert_eq!(to_u16(sum), x.wrapping_add(y), "{} + {}", x, y);
+            }
+        }
+        // every bit carries into the next one
+        assert_eq!(adder16(from_u16(0xFFFF), from_u16(1)), from_u16(0));
+    }
 
-        let r = adder16(r, _32768);
-        let r = adder16(r, _16384);
-        let r = adder16(r, _8192);
-        let r = adder16(r, _4096);
+    /// MSB-first bits of n, for the buses wider than 16.
+    fn bits32(n: u32) -> Bus<32> {
+        std::array::from_fn(|k| (n >> (31 - k)) & 1 == 1)
+    }
 
-        let r = adder16(r, _8);
-        let r = adder16(r, _4);
-        let r = adder16(r, _2);
-        let r = adder16(r, _1);
+    #[test]
+    pub fn test_adder() {
+        let values = [
+            0,
+            1,
+            0xFFFF,
+            0x1_0000,
+            0x7FFF_FFFF,
+            0x8000_0000,
+            0xDEAD_BEEF,
+            u32::MAX,
+        ];
+        for x in values {
+            for y in values {
+                let sum = adder(bits32(x), bits32(y));
+                assert_eq!(sum, bits32(x.wrapping_add(y)), "{} + {}", x, y);
+            }
+        }
+        assert_eq!(incrementer(bits32(0x0000_FFFF)), bits32(0x0001_0000));
+        assert_eq!(incrementer(bits32(u32::MAX)), bits32(0));
 
-        assert_eq!(r, _65535);
-        assert_eq!(adder16(r, _1), _0);
+        let sum: Bus<12> = adder(bits(0xFFF), bits(2));
+        assert_eq!(value(&sum), 1);
+        assert_eq!(value(&incrementer::<bool, 12>(bits(0x7FF))), 0x800);
+        assert_eq!(incrementer([true]), [false]);
     }
 
     #[test]
     pub fn test_incrementer4() {
-        let _0 = [false, false, false, false];
-        let _15 = [true, true, true, true];
-        let mut r = _0;
+        let mut r = from_u4(0);
         for _ in 0..15 {
             r = incrementer4(r);
         }
-        assert_eq!(r, _15)
+        assert_eq!(r, from_u4(15))
     }
 
     #[test]
     pub fn test_incrementer8() {
-        let _0 = [false, false, false, false, false, false, false, false];
-        let _255 = [true, true, true, true, true, true, true, true];
-        let mut r = _0;
+        let mut r = from_u8(0);
         for _ in 0..255 {
             r = incrementer8(r);
         }
-        assert_eq!(r, _255)
+        assert_eq!(r, from_u8(255))
     }
 
     #[test]
     pub fn test_incrementer16() {
-        let _0 = [
-            false, false, false, false, false, false, false, false, false, false, false, false,
-            false, false, false, false,
-        ];
-        let _65535 = [
-            true, true, true, true, true, true, true, true, true, true, true, true, true, true,
-            true, true,
-        ];
-        let mut r = _0;
+        let mut r = from_u16(0);
         for _ in 0..65535 {
             r = incrementer16(r);
         }
-        assert_eq!(r, _65535)
+        assert_eq!(r, from_u16(65535))
     }
 }
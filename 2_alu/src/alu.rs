@@ -1,35 +1,124 @@
 use crate::adders;
-use logic_gates::Arr16;
 use logic_gates::basic_gates as bg;
 use logic_gates::multibit_basic_gates as mbg;
 use logic_gates::multiway_basic_gates as mwg;
+use logic_gates::nand::Signal;
 
-const ARR16_0: Arr16 = [
-    false, false, false, false, false, false, false, false, false, false, false, false, false,
-    false, false, false,
-];
-
-pub fn alu(
-    x: Arr16,
-    y: Arr16,
-    zx: bool,
-    nx: bool,
-    zy: bool,
-    ny: bool,
-    f: bool,
-    no: bool,
-) -> (Arr16, bool, bool) {
-    let x = mbg::mux16(x, ARR16_0, zx);
-    let x = mbg::mux16(x, mbg::not16(x), nx);
-
-    let y = mbg::mux16(y, ARR16_0, zy);
-    let y = mbg::mux16(y, mbg::not16(y), ny);
-
-    let result = mbg::mux16(adders::adder16(x, y), mbg::and16(x, y), f);
-    let result = mbg::mux16(mbg::not16(result), result, no);
-
-    let zr = bg::not(mwg::or16way(result));
-    let ng = result[0];
-
-    (result, zr, ng)
+/// zx nx zy ny f no | out
+/// ----------------------
+///  1  0  1  0 1  0 | 0
+///  1  1  1  1 1  1 | 1
+///  1  1  1  0 1  0 | -1
+///  0  0  1  1 0  0 | x
+///  1  1  0  0 0  0 | y
+///  0  0  1  1 0  1 | !x
+///  1  1  0  0 0  1 | !y
+///  0  0  1  1 1  1 | -x
+///  1  1  0  0 1  1 | -y
+///  0  1  1  1 1  1 | x + 1
+///  1  1  0  1 1  1 | y + 1
+///  0  0  1  1 1  0 | x - 1
+///  1  1  0  0 1  0 | y - 1
+///  0  0  0  0 1  0 | x + y
+///  0  1  0  0 1  1 | x - y
+///  0  0  0  1 1  1 | y - x
+///  0  0  0  0 0  0 | x & y
+///  0  1  0  1 0  1 | x | y
+///
+/// zr is 1 if out is 0, ng is 1 if out is negative
+pub fn alu<S: Signal>(
+    x: [S; 16],
+    y: [S; 16],
+    zx: S,
+    nx: S,
+    zy: S,
+    ny: S,
+    f: S,
+    no: S,
+) -> ([S; 16], S, S) {
+    S::scope("alu", || {
+        let zero = [S::constant(false); 16];
+        let x = mbg::mux16(x, zero, zx);
+        let x = mbg::mux16(x, mbg::not16(x), nx);
+
+        let y = mbg::mux16(y, zero, zy);
+        let y = mbg::mux16(y, mbg::not16(y), ny);
+
+        let result = mbg::mux16(mbg::and16(x, y), adders::adder16(x, y), f);
+        let result = mbg::mux16(result, mbg::not16(result), no);
+
+        let zr = bg::not(mwg::or16way(result));
+        let ng = result[0];
+
+        (result, zr, ng)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logic_gates::convert::{from_i16, to_i16};
+
+    type Function = fn(i16, i16) -> i16;
+
+    #[test]
+    fn test_alu() {
+        let table: [([bool; 6], Function); 18] = [
+            ([true, false, true, false, true, false], |_, _| 0),
+            ([true, true, true, true, true, true], |_, _| 1),
+            ([true, true, true, false, true, false], |_, _| -1),
+            ([false, false, true, true, false, false], |x, _| x),
+            ([true, true, false, false, false, false], |_, y| y),
+            ([false, false, true, true, false, true], |x, _| !x),
+            ([true, true, false, false, false, true], |_, y| !y),
+            ([false, false, true, true, true, true], |x, _| {
+                x.wrapping_neg()
+            }),
+            ([true, true, false, false, true, true], |_, y| {
+                y.wrapping_neg()
+            }),
+            ([false, true, true, true, true, true], |x, _| {
+                x.wrapping_add(1)
+            }),
+            ([true, true, false, true, true, true], |_, y| {
+                y.wrapping_add(1)
+            }),
+            ([false, false, true, true, true, false], |x, _| {
+                x.wrapping_sub(1)
+            }),
+            ([true, true, false, false, true, false], |_, y| {
+                y.wrapping_sub(1)
+            }),
+            ([false, false, false, false, true, false], |x, y| {
+                x.wrapping_add(y)
+            }),
+            ([false, true, false, false, true, true], |x, y| {
+                x.wrapping_sub(y)
+            }),
+            ([false, false, false, true, true, true], |x, y| {
+                y.wrapping_sub(x)
+            }),
+            ([false, false, false, false, false, false], |x, y| x & y),
+            ([false, true, false, true, false, true], |x, y| x | y),
+        ];
+        let inputs = [
+            (0, 0),
+            (17, 3),
+            (3, 17),
+            (-5, 12),
+            (i16::MIN, i16::MAX),
+            (-1, -1),
+        ];
+
+        for (c, expected) in table {
+            for (x, y) in inputs {
+                let (out, zr, ng) =
+                    alu(from_i16(x), from_i16(y), c[0], c[1], c[2], c[3], c[4], c[5]);
+                let expected = expected(x, y);
+                assert_eq!(to_i16(out), expected, "{:?} x={} y={}", c, x, y);
+                assert_eq!(zr, expected == 0);
+                assert_eq!(ng, expected < 0);
+            }
+        }
+    }
 }
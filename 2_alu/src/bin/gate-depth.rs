@@ -0,0 +1,6 @@
+use alu::gate_depth;
+
+/// Prints the longest NAND path to each output bit of the chips.
+fn main() {
+    print!("{}", gate_depth::format(&gate_depth::report()));
+}
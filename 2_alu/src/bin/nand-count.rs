@@ -0,0 +1,8 @@
+use alu::nand_count;
+
+/// Prints how many NANDs each chip of the library takes.
+///
+/// cargo run -p alu --features count --bin nand-count
+fn main() {
+    print!("{}", nand_count::format(&nand_count::report()));
+}
@@ -0,0 +1,39 @@
+use alu::netlists;
+use std::env;
+use std::process;
+
+const USAGE: &str = "usage: netlist [--dot [levels]] <chip>
+
+Prints the NAND netlist of a chip, traced from its function. With
+--dot, prints it as a Graphviz graph instead, its sub-chips levels deep
+as clusters (1 by default):
+
+    netlist --dot alu | dot -Tsvg > alu.svg";
+
+/// cargo run -p alu --bin netlist -- alu
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (name, levels) = match args[..] {
+        [name] => (name, None),
+        ["--dot", name] => (name, Some(1)),
+        ["--dot", levels, name] => match levels.parse() {
+            Ok(levels) => (name, Some(levels)),
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    };
+    let Some(netlist) = netlists::chip(name) else {
+        usage()
+    };
+    match levels {
+        Some(levels) => print!("{}", netlist.dot(levels)),
+        None => print!("{}", netlist),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    eprintln!("\nchips: {}", netlists::CHIPS.join(", "));
+    process::exit(2);
+}
@@ -0,0 +1,125 @@
+use crate::{adders, alu};
+use logic_gates::basic_gates as bg;
+use logic_gates::depth::{Depth, inputs};
+use logic_gates::multiway_basic_gates as mwg;
+
+/// An output pin of a chip with the depth of each of its bits,
+/// index 0 being the MSB like the arrays.
+pub type Pin = (&'static str, Vec<u32>);
+
+fn pin(name: &'static str, bits: &[Depth]) -> Pin {
+    (name, bits.iter().map(|d| d.0).collect())
+}
+
+fn adder_pins([carry, sum]: [Depth; 2]) -> Vec<Pin> {
+    vec![pin("carry", &[carry]), pin("sum", &[sum])]
+}
+
+/// The depth of the longest path of NANDs to every output bit of the
+/// chips, from the gates up to the ALU.
+pub fn report() -> Vec<(&'static str, Vec<Pin>)> {
+    let i = Depth(0);
+    let a = inputs::<16>();
+    let alu = alu::alu(a, a, i, i, i, i, i, i);
+    vec![
+        ("not", vec![pin("out", &[bg::not(i)])]),
+        ("and", vec![pin("out", &[bg::and(i, i)])]),
+        ("or", vec![pin("out", &[bg::or(i, i)])]),
+        ("xor", vec![pin("out", &[bg::xor(i, i)])]),
+        ("mux", vec![pin("out", &[bg::mux(i, i, i)])]),
+        ("demux", vec![pin("out", &bg::demux(i, i))]),
+        ("or8way", vec![pin("out", &[mwg::or8way(inputs())])]),
+        ("or16way", vec![pin("out", &[mwg::or16way(a)])]),
+        (
+            "mux4way16",
+            vec![pin("out", &mwg::mux4way16(a, a, a, a, inputs()))],
+        ),
+        ("demux8way", vec![pin("out", &mwg::demux8way(i, inputs()))]),
+        ("half_adder", adder_pins(adders::half_adder(i, i))),
+        ("full_adder", adder_pins(adders::full_adder(i, i, i))),
+        (
+            "adder4",
+            vec![pin("out", &adders::adder4(inputs(), inputs()))],
+        ),
+        (
+            "adder8",
+            vec![pin("out", &adders::adder8(inputs(), inputs()))],
+        ),
+        ("adder16", vec![pin("out", &adders::adder16(a, a))]),
+        ("incrementer16", vec![pin("out", &adders::incrementer16(a))]),
+        (
+            "alu",
+            vec![pin("out", &alu.0), pin("zr", &[alu.1]), pin("ng", &[alu.2])],
+        ),
+    ]
+}
+
+/// The longest path through the chip, to any of its outputs.
+pub fn critical_path(pins: &[Pin]) -> u32 {
+    pins.iter()
+        .flat_map(|(_, bits)| bits)
+        .copied()
+        .max()
+        .unwrap_or(0)
+}
+
+/// The report as a table, one chip per line with its critical path
+/// first, then the depth of each output bit.
+pub fn format(report: &[(&str, Vec<Pin>)]) -> String {
+    let width = report.iter().map(|(chip, _)| chip.len()).max().unwrap_or(0);
+    let mut table = String::new();
+    for (chip, pins) in report {
+        table.push_str(&format!("{:width$} {:>4}", chip, critical_path(pins)));
+        for (name, bits) in pins {
+            let bits: Vec<String> = bits.iter().map(|d| d.to_string()).collect();
+            table.push_str(&format!("  {}: {}", name, bits.join(" ")));
+        }
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(report: &'a [(&str, Vec<Pin>)], chip: &str) -> &'a [Pin] {
+        &report.iter().find(|(c, _)| *c == chip).unwrap().1
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report();
+        assert_eq!(get(&report, "xor"), [("out", vec![6])]);
+        assert_eq!(
+            get(&report, "full_adder"),
+            [("carry", vec![10]), ("sum", vec![12])]
+        );
+        // the carry ripples through 4 NANDs per bit, from the LSB up
+        let adder16: Vec<u32> = (0..16).map(|k| 12 + 4 * (15 - k)).collect();
+        assert_eq!(get(&report, "adder16"), [("out", adder16)]);
+        assert_eq!(critical_path(get(&report, "alu")), 120);
+        assert_eq!(get(&report, "alu")[1], ("zr", vec![120]));
+    }
+
+    #[test]
+    fn test_ripple_carry() {
+        // the MSB of a ripple carry adder grows linearly with its width
+        let msb = |out: &[Depth]| out[0].0;
+        assert_eq!(msb(&adders::adder(inputs::<1>(), inputs())), 12);
+        assert_eq!(msb(&adders::adder(inputs::<12>(), inputs())), 56);
+        assert_eq!(msb(&adders::adder(inputs::<32>(), inputs())), 136);
+    }
+
+    #[test]
+    fn test_format() {
+        let report = [
+            ("not", vec![("out", vec![1])]),
+            ("adder2", vec![("out", vec![16, 12]), ("carry", vec![14])]),
+        ];
+        assert_eq!(
+            format(&report),
+            "not       1  out: 1\nadder2   16  out: 16 12  carry: 14\n"
+        );
+    }
+}
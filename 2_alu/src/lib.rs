@@ -1,2 +1,6 @@
 pub mod adders;
 pub mod alu;
+pub mod gate_depth;
+#[cfg(feature = "count")]
+pub mod nand_count;
+pub mod netlists;
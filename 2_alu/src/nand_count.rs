@@ -0,0 +1,113 @@
+use crate::{adders, alu};
+use logic_gates::basic_gates as bg;
+use logic_gates::multibit_basic_gates as mbg;
+use logic_gates::multiway_basic_gates as mwg;
+use logic_gates::nand::{count, nand};
+
+fn nands<T>(f: impl FnOnce() -> T) -> u64 {
+    count(f).1
+}
+
+/// The number of NANDs each chip is made of, from the gates up to the ALU.
+///
+/// Chips have no branches, so the count is the same for any input.
+pub fn report() -> Vec<(&'static str, u64)> {
+    let a = [true; 16];
+    let b = [false; 16];
+    vec![
+        ("nand", nands(|| nand(true, false))),
+        ("not", nands(|| bg::not(true))),
+        ("and", nands(|| bg::and(true, false))),
+        ("or", nands(|| bg::or(true, false))),
+        ("xor", nands(|| bg::xor(true, false))),
+        ("mux", nands(|| bg::mux(true, false, true))),
+        ("demux", nands(|| bg::demux(true, false))),
+        ("not16", nands(|| mbg::not16(a))),
+        ("and16", nands(|| mbg::and16(a, b))),
+        ("or16", nands(|| mbg::or16(a, b))),
+        ("xor16", nands(|| mbg::xor16(a, b))),
+        ("mux16", nands(|| mbg::mux16(a, b, true))),
+        ("or8way", nands(|| mwg::or8way([true; 8]))),
+        ("or16way", nands(|| mwg::or16way(a))),
+        ("and8way", nands(|| mwg::and8way([true; 8]))),
+        ("mux4way16", nands(|| mwg::mux4way16(a, b, a, b, [true; 2]))),
+        (
+            "mux8way16",
+            nands(|| mwg::mux8way16(a, b, a, b, a, b, a, b, [true; 3])),
+        ),
+        ("demux4way", nands(|| mwg::demux4way(true, [true; 2]))),
+        ("demux8way", nands(|| mwg::demux8way(true, [true; 3]))),
+        ("half_adder", nands(|| adders::half_adder(true, false))),
+        (
+            "full_adder",
+            nands(|| adders::full_adder(true, false, true)),
+        ),
+        ("adder16", nands(|| adders::adder16(a, b))),
+        ("incrementer16", nands(|| adders::incrementer16(a))),
+        (
+            "alu",
+            nands(|| alu::alu(a, b, true, false, true, false, true, false)),
+        ),
+    ]
+}
+
+/// The report as a table, one chip per line.
+pub fn format(report: &[(&str, u64)]) -> String {
+    let width = report.iter().map(|(chip, _)| chip.len()).max().unwrap_or(0);
+    let mut table = String::new();
+    for (chip, nands) in report {
+        table.push_str(&format!("{:width$} {:>6}\n", chip, nands));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(report: &[(&str, u64)], chip: &str) -> u64 {
+        report.iter().find(|(c, _)| *c == chip).unwrap().1
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report();
+        let expected = [
+            ("nand", 1),
+            ("not", 1),
+            ("and", 2),
+            // the 3 NAND or, not the 5 NAND not(and(not(a), not(b)))
+            ("or", 3),
+            ("xor", 11),
+            ("mux", 19),
+            ("half_adder", 13),
+            ("full_adder", 29),
+            ("adder16", 464),
+            ("alu", 2414),
+        ];
+        for (chip, nands) in expected {
+            assert_eq!(get(&report, chip), nands, "{}", chip);
+        }
+
+        // the 16 bit chips are 16 of their 1 bit gate
+        for (chip, gate) in [
+            ("not16", "not"),
+            ("and16", "and"),
+            ("or16", "or"),
+            ("xor16", "xor"),
+            ("mux16", "mux"),
+            ("adder16", "full_adder"),
+        ] {
+            assert_eq!(get(&report, chip), 16 * get(&report, gate), "{}", chip);
+        }
+        // a tree of n - 1 gates
+        assert_eq!(get(&report, "or16way"), 15 * get(&report, "or"));
+        assert_eq!(get(&report, "and8way"), 7 * get(&report, "and"));
+    }
+
+    #[test]
+    fn test_format() {
+        let table = format(&[("not", 1), ("adder16", 464)]);
+        assert_eq!(table, "not          1\nadder16    464\n");
+    }
+}
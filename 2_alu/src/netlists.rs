@@ -0,0 +1,182 @@
+use crate::{adders, alu};
+use logic_gates::basic_gates as bg;
+use logic_gates::multibit_basic_gates as mbg;
+use logic_gates::multiway_basic_gates as mwg;
+use logic_gates::netlist::{Netlist, trace};
+
+/// The chips that can be traced, from the gates up to the ALU.
+pub const CHIPS: [&str; 12] = [
+    "not",
+    "and",
+    "or",
+    "xor",
+    "mux",
+    "demux",
+    "mux16",
+    "or8way",
+    "half_adder",
+    "full_adder",
+    "adder16",
+    "alu",
+];
+
+/// The netlist of a chip, traced from its function, with the pins named
+/// after its arguments.
+pub fn chip(name: &str) -> Option<Netlist> {
+    let netlist = match name {
+        "not" => trace(|t| {
+            let [a] = t.input("a");
+            t.output("out", &[bg::not(a)]);
+        }),
+        "and" | "or" | "xor" => {
+            let gate = match name {
+                "and" => bg::and,
+                "or" => bg::or,
+                _ => bg::xor,
+            };
+            trace(|t| {
+                let [a] = t.input("a");
+                let [b] = t.input("b");
+                t.output("out", &[gate(a, b)]);
+            })
+        }
+        "mux" => trace(|t| {
+            let [a] = t.input("a");
+            let [b] = t.input("b");
+            let [s] = t.input("s");
+            t.output("out", &[bg::mux(a, b, s)]);
+        }),
+        "demux" => trace(|t| {
+            let [i] = t.input("i");
+            let [s] = t.input("s");
+            t.output("out", &bg::demux(i, s));
+        }),
+        "mux16" => trace(|t| {
+            let a = t.input("a");
+            let b = t.input("b");
+            let [s] = t.input("s");
+            t.output("out", &mbg::mux16(a, b, s));
+        }),
+        "or8way" => trace(|t| {
+            let i = t.input("i");
+            t.output("out", &[mwg::or8way(i)]);
+        }),
+        "half_adder" => trace(|t| {
+            let [a] = t.input("a");
+            let [b] = t.input("b");
+            let [carry, sum] = adders::half_adder(a, b);
+            t.output("carry", &[carry]);
+            t.output("sum", &[sum]);
+        }),
+        "full_adder" => trace(|t| {
+            let [a] = t.input("a");
+            let [b] = t.input("b");
+            let [c] = t.input("c");
+            let [carry, sum] = adders::full_adder(a, b, c);
+            t.output("carry", &[carry]);
+            t.output("sum", &[sum]);
+        }),
+        "adder16" => trace(|t| {
+            let a = t.input("a");
+            let b = t.input("b");
+            t.output("out", &adders::adder16(a, b));
+        }),
+        "alu" => trace(|t| {
+            let x = t.input("x");
+            let y = t.input("y");
+            let [zx] = t.input("zx");
+            let [nx] = t.input("nx");
+            let [zy] = t.input("zy");
+            let [ny] = t.input("ny");
+            let [f] = t.input("f");
+            let [no] = t.input("no");
+            let (out, zr, ng) = alu::alu(x, y, zx, nx, zy, ny, f, no);
+            t.output("out", &out);
+            t.output("zr", &[zr]);
+            t.output("ng", &[ng]);
+        }),
+        _ => return None,
+    };
+    Some(netlist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logic_gates::convert::from_i16;
+    use logic_gates::netlist::Node;
+
+    /// The wires no NAND or output reads.
+    fn unread(netlist: &Netlist) -> Vec<usize> {
+        let mut read = vec![false; netlist.nodes.len()];
+        for node in netlist.nodes.iter() {
+            if let Node::Nand(a, b) = node {
+                read[a.0] = true;
+                read[b.0] = true;
+            }
+        }
+        for wire in netlist.outputs.iter().flat_map(|pin| pin.wires.iter()) {
+            read[wire.0] = true;
+        }
+        (0..read.len()).filter(|&k| !read[k]).collect()
+    }
+
+    #[test]
+    fn test_chips() {
+        for name in CHIPS {
+            let netlist = chip(name).unwrap();
+            let constants: Vec<usize> = unread(&netlist)
+                .into_iter()
+                .filter(|&k| matches!(netlist.nodes[k], Node::Constant(_)))
+                .collect();
+            assert_eq!(constants, [], "unread constants of {}", name);
+            assert_eq!(
+                Netlist::parse(&netlist.to_string()),
+                Ok(netlist),
+                "{}",
+                name
+            );
+        }
+        assert!(chip("nand").is_none());
+        // the same NANDs as nand_count counts when simulating
+        let nands: Vec<usize> = ["or", "xor", "mux", "full_adder", "adder16", "alu"]
+            .iter()
+            .map(|name| chip(name).unwrap().nands())
+            .collect();
+        assert_eq!(nands, [3, 11, 19, 29, 464, 2414]);
+    }
+
+    #[test]
+    fn test_dot() {
+        let clusters = |dot: &str, name: &str| dot.matches(&format!("label=\"{}\";", name)).count();
+        let adder16 = chip("adder16").unwrap().dot(1);
+        assert!(adder16.contains("    label=\"adder16\";"));
+        assert_eq!(clusters(&adder16, "full_adder"), 16);
+        assert_eq!(clusters(&adder16, "half_adder"), 0);
+
+        let alu = chip("alu").unwrap().dot(1);
+        assert_eq!(clusters(&alu, "mux16"), 6);
+        assert_eq!(clusters(&alu, "adder16"), 1);
+        assert_eq!(clusters(&alu, "full_adder"), 0);
+        // the full adders are one level inside adder16
+        let alu = chip("alu").unwrap().dot(2);
+        assert_eq!(clusters(&alu, "full_adder"), 16);
+    }
+
+    #[test]
+    fn test_alu() {
+        let netlist = chip("alu").unwrap();
+        for (x, y) in [(0, 0), (17, 3), (-5, 12), (i16::MIN, -1)] {
+            let (x, y) = (from_i16(x), from_i16(y));
+            // x - y, x | y and !x
+            for c in [[0, 1, 0, 0, 1, 1], [0, 1, 0, 1, 0, 1], [0, 0, 1, 1, 0, 1]] {
+                let c = c.map(|b| [b == 1]);
+                let (out, zr, ng) =
+                    alu::alu(x, y, c[0][0], c[1][0], c[2][0], c[3][0], c[4][0], c[5][0]);
+                let mut inputs: Vec<&[bool]> = vec![&x, &y];
+                inputs.extend(c.iter().map(|b| b.as_slice()));
+                assert_eq!(netlist.eval(&inputs), [out.to_vec(), vec![zr], vec![ng]]);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::clock::Clocked;
+use crate::dff::DFF;
+use logic_gates::basic_gates as bg;
+
+/// load | out(t + 1)
+/// -----------------
+///   0  | out(t)
+///   1  | in(t)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bit {
+    pub i: bool,
+    pub load: bool,
+    dff: DFF,
+}
+
+impl Bit {
+    pub fn new() -> Bit {
+        Bit::default()
+    }
+
+    pub fn out(&self) -> bool {
+        self.dff.out()
+    }
+}
+
+impl Clocked for Bit {
+    fn tick(&mut self) {
+        self.dff.i = bg::mux(self.dff.out(), self.i, self.load);
+        self.dff.tick();
+    }
+
+    fn tock(&mut self) {
+        self.dff.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+
+    #[test]
+    fn test_bit() {
+        let mut clock = Clock::new();
+        let mut bit = Bit::new();
+
+        bit.i = true;
+        bit.load = false;
+        clock.cycle(&mut bit);
+        assert_eq!(bit.out(), false);
+
+        bit.load = true;
+        clock.tick(&mut bit);
+        assert_eq!(bit.out(), false);
+        clock.tock(&mut bit);
+        assert_eq!(bit.out(), true);
+
+        bit.i = false;
+        bit.load = false;
+        for _ in 0..5 {
+            clock.cycle(&mut bit);
+            assert_eq!(bit.out(), true);
+        }
+
+        bit.load = true;
+        clock.cycle(&mut bit);
+        assert_eq!(bit.out(), false);
+    }
+}
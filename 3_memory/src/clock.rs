@@ -0,0 +1,170 @@
+use std::fmt;
+
+/// Every sequential chip is driven by the clock in two phases.
+/// On the tick (rising edge) the chip samples its inputs,
+/// on the tock (falling edge) the sampled state shows up at its outputs.
+///
+/// Between a tick and a tock the outputs keep their old value,
+/// which is what lets a chip feed its own output back into its input.
+pub trait Clocked {
+    fn tick(&mut self);
+    fn tock(&mut self);
+}
+
+impl<T: Clocked> Clocked for [T] {
+    fn tick(&mut self) {
+        for chip in self.iter_mut() {
+            chip.tick();
+        }
+    }
+
+    fn tock(&mut self) {
+        for chip in self.iter_mut() {
+            chip.tock();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Tick,
+    Tock,
+}
+
+/// time | phase
+/// ------------
+///  0   | tock   (clock starts low)
+///  0+  | tick
+///  1   | tock
+///  1+  | tick
+///  ...
+///
+/// The time is displayed the same way as in the course's simulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    time: u64,
+    phase: Phase,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            time: 0,
+            phase: Phase::Tock,
+        }
+    }
+
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn tick<C: Clocked + ?Sized>(&mut self, chip: &mut C) {
+        assert_eq!(self.phase, Phase::Tock, "tick while the clock is high");
+        chip.tick();
+        self.phase = Phase::Tick;
+    }
+
+    pub fn tock<C: Clocked + ?Sized>(&mut self, chip: &mut C) {
+        assert_eq!(self.phase, Phase::Tick, "tock while the clock is low");
+        chip.tock();
+        self.phase = Phase::Tock;
+        self.time += 1;
+    }
+
+    /// A full clock cycle, tick followed by tock.
+    pub fn cycle<C: Clocked + ?Sized>(&mut self, chip: &mut C) {
+        self.tick(chip);
+        self.tock(chip);
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.phase {
+            Phase::Tick => write!(f, "{}+", self.time),
+            Phase::Tock => write!(f, "{}", self.time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        ticks: u32,
+        tocks: u32,
+    }
+
+    impl Clocked for Counter {
+        fn tick(&mut self) {
+            self.ticks += 1;
+        }
+
+        fn tock(&mut self) {
+            self.tocks += 1;
+        }
+    }
+
+    #[test]
+    fn test_phases() {
+        let mut clock = Clock::new();
+        let mut chip = Counter::default();
+        assert_eq!(clock.phase(), Phase::Tock);
+        assert_eq!(clock.to_string(), "0");
+
+        clock.tick(&mut chip);
+        assert_eq!(clock.phase(), Phase::Tick);
+        assert_eq!(clock.to_string(), "0+");
+        assert_eq!((chip.ticks, chip.tocks), (1, 0));
+
+        clock.tock(&mut chip);
+        assert_eq!(clock.phase(), Phase::Tock);
+        assert_eq!(clock.to_string(), "1");
+        assert_eq!((chip.ticks, chip.tocks), (1, 1));
+
+        for _ in 0..9 {
+            clock.cycle(&mut chip);
+        }
+        assert_eq!(clock.time(), 10);
+        assert_eq!((chip.ticks, chip.tocks), (10, 10));
+    }
+
+    #[test]
+    fn test_slice_of_chips() {
+        let mut clock = Clock::new();
+        let mut chips = [Counter::default(), Counter::default()];
+        clock.cycle(&mut chips[..]);
+        for chip in chips.iter() {
+            assert_eq!((chip.ticks, chip.tocks), (1, 1));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_double_tick() {
+        let mut clock = Clock::new();
+        let mut chip = Counter::default();
+        clock.tick(&mut chip);
+        clock.tick(&mut chip);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tock_before_tick() {
+        let mut clock = Clock::new();
+        let mut chip = Counter::default();
+        clock.tock(&mut chip);
+    }
+}
@@ -0,0 +1,57 @@
+/// Data flip-flop, out(t) = in(t - 1).
+/// Like nand in the combinational chips, this is the one primitive
+/// of the sequential chips that is not built from anything else.
+use crate::clock::Clocked;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DFF {
+    pub i: bool,
+    sampled: bool,
+    out: bool,
+}
+
+impl DFF {
+    pub fn new() -> DFF {
+        DFF::default()
+    }
+
+    pub fn out(&self) -> bool {
+        self.out
+    }
+}
+
+impl Clocked for DFF {
+    fn tick(&mut self) {
+        self.sampled = self.i;
+    }
+
+    fn tock(&mut self) {
+        self.out = self.sampled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+
+    #[test]
+    fn test_dff() {
+        let mut clock = Clock::new();
+        let mut dff = DFF::new();
+        assert_eq!(dff.out(), false);
+
+        dff.i = true;
+        assert_eq!(dff.out(), false);
+        clock.tick(&mut dff);
+        assert_eq!(dff.out(), false);
+        clock.tock(&mut dff);
+        assert_eq!(dff.out(), true);
+
+        dff.i = false;
+        clock.tick(&mut dff);
+        dff.i = true;
+        clock.tock(&mut dff);
+        assert_eq!(dff.out(), false);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod bit;
+pub mod clock;
+pub mod dff;
+pub mod pc;
+pub mod ram;
+pub mod register;
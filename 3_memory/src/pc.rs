@@ -0,0 +1,133 @@
+use crate::clock::Clocked;
+use crate::register::Register;
+use alu::adders;
+use logic_gates::Arr16;
+use logic_gates::basic_gates as bg;
+use logic_gates::multibit_basic_gates as mbg;
+
+const ARR16_0: Arr16 = [false; 16];
+
+/// Program counter.
+///
+/// reset load inc | out(t + 1)
+/// ---------------------------
+///   1     *    *  | 0
+///   0     1    *  | in(t)
+///   0     0    1  | out(t) + 1
+///   0     0    0  | out(t)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PC {
+    pub i: Arr16,
+    pub load: bool,
+    pub inc: bool,
+    pub reset: bool,
+    register: Register,
+}
+
+impl PC {
+    pub fn new() -> PC {
+        PC::default()
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.register.out()
+    }
+}
+
+impl Clocked for PC {
+    fn tick(&mut self) {
+        let out = self.register.out();
+        let next = mbg::mux16(out, adders::incrementer16(out), self.inc);
+        let next = mbg::mux16(next, self.i, self.load);
+        let next = mbg::mux16(next, ARR16_0, self.reset);
+
+        self.register.i = next;
+        self.register.load = bg::or(bg::or(self.reset, self.load), self.inc);
+        self.register.tick();
+    }
+
+    fn tock(&mut self) {
+        self.register.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use logic_gates::convert::{from_u16, to_u16};
+
+    #[test]
+    fn test_pc_priority() {
+        let mut clock = Clock::new();
+        let mut pc = PC::new();
+
+        pc.inc = true;
+        for n in 1..=5 {
+            clock.cycle(&mut pc);
+            assert_eq!(to_u16(pc.out()), n);
+        }
+
+        pc.i = from_u16(1234);
+        pc.load = true;
+        clock.cycle(&mut pc);
+        assert_eq!(to_u16(pc.out()), 1234);
+
+        pc.reset = true;
+        clock.tick(&mut pc);
+        assert_eq!(to_u16(pc.out()), 1234);
+        clock.tock(&mut pc);
+        assert_eq!(to_u16(pc.out()), 0);
+
+        pc.reset = false;
+        pc.load = false;
+        pc.inc = false;
+        for _ in 0..5 {
+            clock.cycle(&mut pc);
+            assert_eq!(to_u16(pc.out()), 0);
+        }
+    }
+
+    #[test]
+    fn test_pc_wraps_around() {
+        let mut clock = Clock::new();
+        let mut pc = PC::new();
+
+        pc.i = from_u16(0xFFFF);
+        pc.load = true;
+        clock.cycle(&mut pc);
+        pc.load = false;
+        pc.inc = true;
+        clock.cycle(&mut pc);
+        assert_eq!(to_u16(pc.out()), 0);
+    }
+
+    #[test]
+    fn test_pc_every_combination() {
+        let mut clock = Clock::new();
+        let mut pc = PC::new();
+        let mut expected: u16 = 0;
+
+        for cycle in 0..400u16 {
+            // walk through every reset/load/inc combination many times,
+            // changing the input on every cycle.
+            let controls = cycle % 8;
+            pc.reset = controls & 0b100 != 0 && cycle % 3 == 0;
+            pc.load = controls & 0b010 != 0;
+            pc.inc = controls & 0b001 != 0;
+            pc.i = from_u16(cycle.wrapping_mul(7919));
+
+            expected = if pc.reset {
+                0
+            } else if pc.load {
+                cycle.wrapping_mul(7919)
+            } else if pc.inc {
+                expected.wrapping_add(1)
+            } else {
+                expected
+            };
+            clock.cycle(&mut pc);
+            assert_eq!(to_u16(pc.out()), expected, "cycle {}", cycle);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use crate::clock::Clocked;
+use crate::register::Register;
+use logic_gates::Arr16;
+use logic_gates::multiway_basic_gates as mwg;
+
+const ARR16_0: Arr16 = [false; 16];
+
+// demux8way and demux4way return their outputs MSB-first, so the
+// chip selected by address k is at index 7 - k (or 3 - k) of their output,
+// while mux8way16 and mux4way16 take their inputs in address order.
+//
+// From RAM64 up, the chips made with new evaluate every gate of every
+// sub-chip. The ones made with fast skip the sub-chips that are not
+// addressed: the mux discards their output and, with load = 0, their
+// registers keep their value anyway, so this gives the same result while
+// keeping a RAM16K fast enough to run programs on.
+
+/// Eight registers, addressed by 3 bits.
+///
+/// load | out(t + 1)
+/// -----------------------------------
+///   0  | RAM[address](t)
+///   1  | RAM[address](t + 1) = in(t)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RAM8 {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 3],
+    registers: [Register; 8],
+}
+
+impl RAM8 {
+    pub fn new() -> RAM8 {
+        RAM8::default()
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 3]) -> Arr16 {
+        let r = &self.registers;
+        mwg::mux8way16(
+            r[0].out(),
+            r[1].out(),
+            r[2].out(),
+            r[3].out(),
+            r[4].out(),
+            r[5].out(),
+            r[6].out(),
+            r[7].out(),
+            address,
+        )
+    }
+}
+
+impl Clocked for RAM8 {
+    fn tick(&mut self) {
+        let loads = mwg::demux8way(self.load, self.address);
+        for (k, register) in self.registers.iter_mut().enumerate() {
+            register.i = self.i;
+            register.load = loads[7 - k];
+            register.tick();
+        }
+    }
+
+    fn tock(&mut self) {
+        self.registers.tock();
+    }
+}
+
+/// Eight RAM8, the upper 3 bits of the address select the RAM8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RAM64 {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 6],
+    rams: Vec<RAM8>,
+    fast: bool,
+}
+
+impl Default for RAM64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RAM64 {
+    pub fn new() -> RAM64 {
+        RAM64 {
+            i: [false; 16],
+            load: false,
+            address: [false; 6],
+            rams: vec![RAM8::new(); 8],
+            fast: false,
+        }
+    }
+
+    /// A RAM64 that only evaluates the sub-chips that are addressed.
+    pub fn fast() -> RAM64 {
+        RAM64 {
+            i: [false; 16],
+            load: false,
+            address: [false; 6],
+            rams: vec![RAM8::new(); 8],
+            fast: true,
+        }
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 6]) -> Arr16 {
+        let high = [address[0], address[1], address[2]];
+        let low = [address[3], address[4], address[5]];
+        let r: [Arr16; 8] = match self.fast {
+            false => std::array::from_fn(|k| self.rams[k].read(low)),
+            true => {
+                let selected = mwg::demux8way(true, high);
+                std::array::from_fn(|k| match selected[7 - k] {
+                    true => self.rams[k].read(low),
+                    false => ARR16_0,
+                })
+            }
+        };
+        mwg::mux8way16(r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7], high)
+    }
+}
+
+impl Clocked for RAM64 {
+    fn tick(&mut self) {
+        let high = [self.address[0], self.address[1], self.address[2]];
+        let low = [self.address[3], self.address[4], self.address[5]];
+        let loads = mwg::demux8way(self.load, high);
+        for (k, ram) in self.rams.iter_mut().enumerate() {
+            ram.i = self.i;
+            ram.load = loads[7 - k];
+            ram.address = low;
+            if !self.fast || ram.load {
+                ram.tick();
+            }
+        }
+    }
+
+    fn tock(&mut self) {
+        for ram in self.rams.iter_mut() {
+            if !self.fast || ram.load {
+                ram.tock();
+            }
+        }
+    }
+}
+
+/// Eight RAM64, the upper 3 bits of the address select the RAM64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RAM512 {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 9],
+    rams: Vec<RAM64>,
+    fast: bool,
+}
+
+impl Default for RAM512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RAM512 {
+    pub fn new() -> RAM512 {
+        RAM512 {
+            i: [false; 16],
+            load: false,
+            address: [false; 9],
+            rams: vec![RAM64::new(); 8],
+            fast: false,
+        }
+    }
+
+    /// A RAM512 that only evaluates the sub-chips that are addressed.
+    pub fn fast() -> RAM512 {
+        RAM512 {
+            i: [false; 16],
+            load: false,
+            address: [false; 9],
+            rams: vec![RAM64::fast(); 8],
+            fast: true,
+        }
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 9]) -> Arr16 {
+        let high = [address[0], address[1], address[2]];
+        let low = [
+            address[3], address[4], address[5], address[6], address[7], address[8],
+        ];
+        let r: [Arr16; 8] = match self.fast {
+            false => std::array::from_fn(|k| self.rams[k].read(low)),
+            true => {
+                let selected = mwg::demux8way(true, high);
+                std::array::from_fn(|k| match selected[7 - k] {
+                    true => self.rams[k].read(low),
+                    false => ARR16_0,
+                })
+            }
+        };
+        mwg::mux8way16(r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7], high)
+    }
+}
+
+impl Clocked for RAM512 {
+    fn tick(&mut self) {
+        let a = self.address;
+        let high = [a[0], a[1], a[2]];
+        let low = [a[3], a[4], a[5], a[6], a[7], a[8]];
+        let loads = mwg::demux8way(self.load, high);
+        for (k, ram) in self.rams.iter_mut().enumerate() {
+            ram.i = self.i;
+            ram.load = loads[7 - k];
+            ram.address = low;
+            if !self.fast || ram.load {
+                ram.tick();
+            }
+        }
+    }
+
+    fn tock(&mut self) {
+        for ram in self.rams.iter_mut() {
+            if !self.fast || ram.load {
+                ram.tock();
+            }
+        }
+    }
+}
+
+/// Eight RAM512, the upper 3 bits of the address select the RAM512.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RAM4K {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 12],
+    rams: Vec<RAM512>,
+    fast: bool,
+}
+
+impl Default for RAM4K {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RAM4K {
+    pub fn new() -> RAM4K {
+        RAM4K {
+            i: [false; 16],
+            load: false,
+            address: [false; 12],
+            rams: vec![RAM512::new(); 8],
+            fast: false,
+        }
+    }
+
+    /// A RAM4K that only evaluates the sub-chips that are addressed.
+    pub fn fast() -> RAM4K {
+        RAM4K {
+            i: [false; 16],
+            load: false,
+            address: [false; 12],
+            rams: vec![RAM512::fast(); 8],
+            fast: true,
+        }
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 12]) -> Arr16 {
+        let a = address;
+        let high = [a[0], a[1], a[2]];
+        let low = [a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11]];
+        let r: [Arr16; 8] = match self.fast {
+            false => std::array::from_fn(|k| self.rams[k].read(low)),
+            true => {
+                let selected = mwg::demux8way(true, high);
+                std::array::from_fn(|k| match selected[7 - k] {
+                    true => self.rams[k].read(low),
+                    false => ARR16_0,
+                })
+            }
+        };
+        mwg::mux8way16(r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7], high)
+    }
+}
+
+impl Clocked for RAM4K {
+    fn tick(&mut self) {
+        let a = self.address;
+        let high = [a[0], a[1], a[2]];
+        let low = [a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11]];
+        let loads = mwg::demux8way(self.load, high);
+        for (k, ram) in self.rams.iter_mut().enumerate() {
+            ram.i = self.i;
+            ram.load = loads[7 - k];
+            ram.address = low;
+            if !self.fast || ram.load {
+                ram.tick();
+            }
+        }
+    }
+
+    fn tock(&mut self) {
+        for ram in self.rams.iter_mut() {
+            if !self.fast || ram.load {
+                ram.tock();
+            }
+        }
+    }
+}
+
+/// Four RAM4K, the upper 2 bits of the address select the RAM4K.
+/// This is the data memory of the Hack computer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RAM16K {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 14],
+    rams: Vec<RAM4K>,
+    fast: bool,
+}
+
+impl Default for RAM16K {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RAM16K {
+    pub fn new() -> RAM16K {
+        RAM16K {
+            i: [false; 16],
+            load: false,
+            address: [false; 14],
+            rams: vec![RAM4K::new(); 4],
+            fast: false,
+        }
+    }
+
+    /// A RAM16K that only evaluates the sub-chips that are addressed.
+    pub fn fast() -> RAM16K {
+        RAM16K {
+            i: [false; 16],
+            load: false,
+            address: [false; 14],
+            rams: vec![RAM4K::fast(); 4],
+            fast: true,
+        }
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 14]) -> Arr16 {
+        let a = address;
+        let high = [a[0], a[1]];
+        let low = [
+            a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11], a[12], a[13],
+        ];
+        let r: [Arr16; 4] = match self.fast {
+            false => std::array::from_fn(|k| self.rams[k].read(low)),
+            true => {
+                let selected = mwg::demux4way(true, high);
+                std::array::from_fn(|k| match selected[3 - k] {
+                    true => self.rams[k].read(low),
+                    false => ARR16_0,
+                })
+            }
+        };
+        mwg::mux4way16(r[0], r[1], r[2], r[3], high)
+    }
+}
+
+impl Clocked for RAM16K {
+    fn tick(&mut self) {
+        let a = self.address;
+        let high = [a[0], a[1]];
+        let low = [
+            a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11], a[12], a[13],
+        ];
+        let loads = mwg::demux4way(self.load, high);
+        for (k, ram) in self.rams.iter_mut().enumerate() {
+            ram.i = self.i;
+            ram.load = loads[3 - k];
+            ram.address = low;
+            if !self.fast || ram.load {
+                ram.tick();
+            }
+        }
+    }
+
+    fn tock(&mut self) {
+        for ram in self.rams.iter_mut() {
+            if !self.fast || ram.load {
+                ram.tock();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use logic_gates::convert::bits;
+
+    /// Addresses at the edges of every sub-chip of a RAM of the given size.
+    fn boundaries(size: u16) -> Vec<u16> {
+        let mut addresses = vec![0, size - 1];
+        let mut chunk = 8;
+        while chunk < size {
+            addresses.push(chunk - 1);
+            addresses.push(chunk);
+            addresses.push(size - chunk - 1);
+            addresses.push(size - chunk);
+            chunk *= 8;
+        }
+        addresses.sort();
+        addresses.dedup();
+        addresses
+    }
+
+    #[test]
+    fn test_ram8() {
+        let mut clock = Clock::new();
+        let mut ram = RAM8::new();
+
+        for address in 0..8 {
+            ram.address = bits(address);
+            ram.i = bits(address + 1);
+            ram.load = true;
+            clock.tick(&mut ram);
+            assert_eq!(ram.out(), [false; 16]);
+            clock.tock(&mut ram);
+            assert_eq!(ram.out(), bits::<16>(address + 1));
+        }
+
+        ram.i = [true; 16];
+        ram.load = false;
+        for address in 0..8 {
+            ram.address = bits(address);
+            clock.cycle(&mut ram);
+            assert_eq!(ram.out(), bits::<16>(address + 1));
+        }
+    }
+
+    #[test]
+    fn test_ram64() {
+        let mut clock = Clock::new();
+        let mut ram = RAM64::new();
+
+        for address in 0..64 {
+            ram.address = bits(address);
+            ram.i = bits(1000 + address);
+            ram.load = true;
+            clock.cycle(&mut ram);
+        }
+        ram.load = false;
+        for address in 0..64 {
+            ram.address = bits(address);
+            assert_eq!(ram.out(), bits::<16>(1000 + address));
+        }
+    }
+
+    #[test]
+    fn test_ram512() {
+        let mut clock = Clock::new();
+        let mut ram = RAM512::new();
+
+        let addresses = boundaries(512);
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            ram.i = bits(address ^ 0x5555);
+            ram.load = true;
+            clock.cycle(&mut ram);
+        }
+        ram.load = false;
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            assert_eq!(ram.out(), bits::<16>(address ^ 0x5555));
+            ram.address = bits(address ^ 1);
+            if !addresses.contains(&(address ^ 1)) {
+                assert_eq!(ram.out(), [false; 16]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ram4k() {
+        let mut clock = Clock::new();
+        let mut ram = RAM4K::new();
+
+        let addresses = boundaries(4096);
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            ram.i = bits(address ^ 0xAAAA);
+            ram.load = true;
+            clock.cycle(&mut ram);
+        }
+        ram.load = false;
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            assert_eq!(ram.out(), bits::<16>(address ^ 0xAAAA));
+        }
+    }
+
+    #[test]
+    fn test_fast() {
+        // the same outputs as the RAM evaluating every gate, with only the
+        // addressed chips evaluated
+        let mut rams = [RAM4K::new(), RAM4K::fast()];
+        for &address in boundaries(4096).iter() {
+            for ram in rams.iter_mut() {
+                ram.address = bits(address);
+                ram.i = bits(!address);
+                ram.load = true;
+                ram.tick();
+                ram.tock();
+            }
+        }
+        for address in (0..4096).step_by(7) {
+            let outs = rams.each_ref().map(|ram| ram.read(bits(address)));
+            assert_eq!(outs[0], outs[1], "{}", address);
+        }
+        assert_eq!(rams[1].read(bits(4095)), bits::<16>(!4095));
+    }
+
+    #[test]
+    fn test_ram16k() {
+        let mut clock = Clock::new();
+        let mut ram = RAM16K::new();
+
+        // the edges of every sub-chip, with the two middle RAM4Ks
+        let mut addresses = boundaries(16384);
+        addresses.extend([8191, 8192]);
+        addresses.sort();
+        let mut words = vec![0u16; 16384];
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            ram.i = bits(address ^ 0xAAAA);
+            ram.load = true;
+            clock.cycle(&mut ram);
+            words[address as usize] = address ^ 0xAAAA;
+            assert_eq!(ram.out(), bits::<16>(address ^ 0xAAAA));
+
+            // Writing to one address leaves its neighbours untouched.
+            let neighbours = [address.checked_sub(1), Some(address + 1)];
+            for n in neighbours.into_iter().flatten().filter(|&n| n < 16384) {
+                assert_eq!(ram.read(bits(n)), bits::<16>(words[n as usize]), "{}", n);
+            }
+        }
+
+        ram.load = false;
+        ram.i = [true; 16];
+        for &address in addresses.iter() {
+            ram.address = bits(address);
+            clock.cycle(&mut ram);
+            assert_eq!(ram.out(), bits::<16>(address ^ 0xAAAA));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::clock::Clocked;
+use crate::dff::DFF;
+use logic_gates::Arr16;
+use logic_gates::multibit_basic_gates as mbg;
+
+/// 16-bit version of Bit.
+///
+/// load | out(t + 1)
+/// -----------------
+///   0  | out(t)
+///   1  | in(t)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Register {
+    pub i: Arr16,
+    pub load: bool,
+    dffs: [DFF; 16],
+}
+
+impl Register {
+    pub fn new() -> Register {
+        Register::default()
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.dffs.map(|dff| dff.out())
+    }
+}
+
+impl Clocked for Register {
+    fn tick(&mut self) {
+        let next = mbg::mux16(self.out(), self.i, self.load);
+        for (dff, i) in self.dffs.iter_mut().zip(next) {
+            dff.i = i;
+        }
+        self.dffs.tick();
+    }
+
+    fn tock(&mut self) {
+        self.dffs.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+
+    #[test]
+    fn test_register() {
+        let mut clock = Clock::new();
+        let mut register = Register::new();
+        let a = [
+            true, true, false, true, true, true, true, false, false, true, false, true, true, true,
+            true, false,
+        ];
+        let b = [
+            false, false, true, false, false, false, false, false, false, false, false, false,
+            false, false, false, true,
+        ];
+
+        register.i = a;
+        register.load = false;
+        clock.cycle(&mut register);
+        assert_eq!(register.out(), [false; 16]);
+
+        register.load = true;
+        clock.tick(&mut register);
+        assert_eq!(register.out(), [false; 16]);
+        clock.tock(&mut register);
+        assert_eq!(register.out(), a);
+
+        register.i = b;
+        register.load = false;
+        for _ in 0..5 {
+            clock.cycle(&mut register);
+            assert_eq!(register.out(), a);
+        }
+
+        register.load = true;
+        clock.cycle(&mut register);
+        assert_eq!(register.out(), b);
+    }
+}
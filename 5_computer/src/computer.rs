@@ -0,0 +1,253 @@
+use crate::cpu::CPU;
+use crate::data_memory::Memory;
+use crate::keyboard::Keyboard;
+use crate::rom::{LoadError, ROM32K};
+use crate::screen::Screen;
+use logic_gates::Arr16;
+use logic_gates::convert::{bits, value};
+use memory::clock::{Clock, Clocked};
+use std::path::Path;
+
+/// The Hack computer: CPU, ROM32K with the program and the data memory.
+///
+/// Every cycle the CPU executes the instruction at pc, reading and
+/// writing the memory at address_m. While reset is 1 the program
+/// restarts from address 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Computer {
+    pub reset: bool,
+    cpu: CPU,
+    rom: ROM32K,
+    memory: Memory,
+    clock: Clock,
+    written: Option<(u16, u16)>,
+}
+
+impl Computer {
+    pub fn new(rom: ROM32K) -> Computer {
+        Computer {
+            reset: false,
+            cpu: CPU::new(),
+            rom,
+            memory: Memory::new(),
+            clock: Clock::new(),
+            written: None,
+        }
+    }
+
+    /// Computer running the program in a .hack file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Computer, LoadError> {
+        Ok(Computer::new(ROM32K::load(path)?))
+    }
+
+    pub fn step(&mut self, cycles: usize) {
+        let mut clock = self.clock;
+        for _ in 0..cycles {
+            clock.cycle(self);
+        }
+        self.clock = clock;
+    }
+
+    /// Number of cycles run so far.
+    pub fn time(&self) -> u64 {
+        self.clock.time()
+    }
+
+    pub fn a(&self) -> u16 {
+        value(&self.cpu.a())
+    }
+
+    pub fn d(&self) -> u16 {
+        value(&self.cpu.d())
+    }
+
+    pub fn pc(&self) -> u16 {
+        value(&self.cpu.pc())
+    }
+
+    /// The (address, value) the last cycle wrote to the data memory, if any.
+    pub fn written(&self) -> Option<(u16, u16)> {
+        self.written
+    }
+
+    /// Value at any address of the data memory, including the screen
+    /// and the keyboard.
+    pub fn peek(&self, address: u16) -> u16 {
+        value(&self.memory.read(bits(address)))
+    }
+
+    /// Writes to the data memory without running the CPU,
+    /// for setting up the inputs of a program.
+    pub fn poke(&mut self, address: u16, word: u16) {
+        let mut clock = Clock::new();
+        self.memory.address = bits(address);
+        self.memory.i = bits(word);
+        self.memory.load = true;
+        clock.cycle(&mut self.memory);
+        self.memory.load = false;
+    }
+
+    pub fn screen(&self) -> &Screen {
+        self.memory.screen()
+    }
+
+    pub fn keyboard(&mut self) -> &mut Keyboard {
+        self.memory.keyboard()
+    }
+
+    pub fn press(&mut self, key: u16) {
+        let key: Arr16 = bits(key);
+        self.keyboard().press(key);
+    }
+}
+
+impl Clocked for Computer {
+    fn tick(&mut self) {
+        self.rom.address = self.cpu.pc();
+        self.cpu.instruction = self.rom.out();
+        self.cpu.reset = self.reset;
+
+        self.memory.address = self.cpu.address_m();
+        self.cpu.in_m = self.memory.out();
+        self.memory.i = self.cpu.out_m();
+        self.memory.load = self.cpu.write_m();
+        self.written = self
+            .memory
+            .load
+            .then(|| (value(&self.memory.address), value(&self.memory.i)));
+
+        self.cpu.tick();
+        self.memory.tick();
+    }
+
+    fn tock(&mut self) {
+        self.cpu.tock();
+        self.memory.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_memory::{KBD, SCREEN};
+
+    const MAX: &str = "
+        0000000000000000
+        1111110000010000
+        0000000000000001
+        1111010011010000
+        0000000000001010
+        1110001100000001
+        0000000000000001
+        1111110000010000
+        0000000000001100
+        1110101010000111
+        0000000000000000
+        1111110000010000
+        0000000000000010
+        1110001100001000
+        0000000000001110
+        1110101010000111
+    ";
+
+    /// Blackens the first 16 pixels of the first R0 rows.
+    const RECT: &str = "
+        0000000000000000
+        1111110000010000
+        0000000000010111
+        1110001100000110
+        0000000000010000
+        1110001100001000
+        0100000000000000
+        1110110000010000
+        0000000000010001
+        1110001100001000
+        0000000000010001
+        1111110000100000
+        1110111010001000
+        0000000000010001
+        1111110000010000
+        0000000000100000
+        1110000010010000
+        0000000000010001
+        1110001100001000
+        0000000000010000
+        1111110010011000
+        0000000000001010
+        1110001100000001
+        0000000000010111
+        1110101010000111
+    ";
+
+    /// R0 = KBD, forever.
+    const KEY: &str = "
+        0110000000000000
+        1111110000010000
+        0000000000000000
+        1110001100001000
+        0000000000000000
+        1110101010000111
+    ";
+
+    #[test]
+    fn test_max() {
+        let mut computer = Computer::new(ROM32K::from_hack(MAX).unwrap());
+        computer.poke(0, 3);
+        computer.poke(1, 5);
+        computer.step(20);
+        assert_eq!(computer.peek(2), 5);
+        assert_eq!(computer.time(), 20);
+
+        computer.reset = true;
+        computer.step(1);
+        assert_eq!(computer.pc(), 0);
+        computer.reset = false;
+        computer.poke(0, 23456);
+        computer.poke(1, 12345);
+        computer.step(20);
+        assert_eq!(computer.peek(2), 23456);
+        assert_eq!(computer.pc(), 14);
+    }
+
+    #[test]
+    fn test_rect() {
+        let mut computer = Computer::new(ROM32K::from_hack(RECT).unwrap());
+        computer.poke(0, 4);
+        computer.step(100);
+        for row in 0..4 {
+            assert_eq!(computer.peek(SCREEN + 32 * row), 0xFFFF);
+        }
+        assert_eq!(computer.peek(SCREEN + 32 * 4), 0);
+        assert_eq!(computer.screen().pixel(3, 15), true);
+        assert_eq!(computer.screen().pixel(3, 16), false);
+        assert_eq!(computer.screen().pixel(4, 0), false);
+    }
+
+    #[test]
+    fn test_keyboard() {
+        let mut computer = Computer::new(ROM32K::from_hack(KEY).unwrap());
+        computer.step(6);
+        assert_eq!(computer.peek(0), 0);
+
+        computer.press(75);
+        assert_eq!(computer.peek(KBD), 75);
+        computer.step(6);
+        assert_eq!(computer.peek(0), 75);
+
+        computer.keyboard().release();
+        computer.step(6);
+        assert_eq!(computer.peek(0), 0);
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join("computer_test_load.hack");
+        std::fs::write(&path, MAX).unwrap();
+        let mut computer = Computer::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        computer.poke(0, 7);
+        computer.step(20);
+        assert_eq!(computer.peek(2), 7);
+    }
+}
@@ -0,0 +1,309 @@
+use alu::alu;
+use logic_gates::Arr16;
+use logic_gates::basic_gates as bg;
+use logic_gates::multibit_basic_gates as mbg;
+use memory::clock::Clocked;
+use memory::pc::PC;
+use memory::register::Register;
+
+/// Bits of a Hack instruction, MSB-first
+///
+///  0 | 1 2 | 3 | 4  5  6  7  8 9  | 10 11 12 | 13 14 15
+///  i | 1 1 | a | zx nx zy ny f no | A  D  M  | lt eq gt
+///
+/// i = 0 is an A-instruction, and the other 15 bits are the constant
+/// loaded into A. For i = 1 (C-instruction), the ALU computes on D and
+/// on A or M (selected by a), stores the result in any of the
+/// destinations, and jumps to A if the result matches the jump bits.
+///
+/// All the C-instruction fields are zero for A-instructions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Decoded {
+    pub c: bool,
+    pub a: bool,
+    pub zx: bool,
+    pub nx: bool,
+    pub zy: bool,
+    pub ny: bool,
+    pub f: bool,
+    pub no: bool,
+    pub dest_a: bool,
+    pub dest_d: bool,
+    pub dest_m: bool,
+    pub jlt: bool,
+    pub jeq: bool,
+    pub jgt: bool,
+}
+
+pub fn decode(instruction: Arr16) -> Decoded {
+    let c = instruction[0];
+    Decoded {
+        c,
+        a: bg::and(c, instruction[3]),
+        zx: bg::and(c, instruction[4]),
+        nx: bg::and(c, instruction[5]),
+        zy: bg::and(c, instruction[6]),
+        ny: bg::and(c, instruction[7]),
+        f: bg::and(c, instruction[8]),
+        no: bg::and(c, instruction[9]),
+        dest_a: bg::and(c, instruction[10]),
+        dest_d: bg::and(c, instruction[11]),
+        dest_m: bg::and(c, instruction[12]),
+        jlt: bg::and(c, instruction[13]),
+        jeq: bg::and(c, instruction[14]),
+        jgt: bg::and(c, instruction[15]),
+    }
+}
+
+/// zr ng | jump
+/// ------------------
+///  1  0 | jeq
+///  0  1 | jlt
+///  0  0 | jgt
+pub fn jump(d: Decoded, zr: bool, ng: bool) -> bool {
+    let pos = bg::and(bg::not(zr), bg::not(ng));
+    bg::or(
+        bg::or(bg::and(d.jlt, ng), bg::and(d.jeq, zr)),
+        bg::and(d.jgt, pos),
+    )
+}
+
+/// The Hack central processing unit.
+///
+/// Inputs are the value of the selected data memory word (in_m),
+/// the instruction fetched from ROM and reset.
+/// Outputs are the value to write to memory (out_m), whether to write it
+/// (write_m), the address of the selected memory word (address_m) and
+/// the address of the next instruction (pc).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CPU {
+    pub in_m: Arr16,
+    pub instruction: Arr16,
+    pub reset: bool,
+    a: Register,
+    d: Register,
+    pc: PC,
+}
+
+impl CPU {
+    pub fn new() -> CPU {
+        CPU::default()
+    }
+
+    pub fn a(&self) -> Arr16 {
+        self.a.out()
+    }
+
+    pub fn d(&self) -> Arr16 {
+        self.d.out()
+    }
+
+    /// The ALU output and its zr/ng flags for the current instruction.
+    fn compute(&self) -> (Arr16, bool, bool) {
+        let d = decode(self.instruction);
+        let y = mbg::mux16(self.a.out(), self.in_m, d.a);
+        alu::alu(self.d.out(), y, d.zx, d.nx, d.zy, d.ny, d.f, d.no)
+    }
+
+    pub fn out_m(&self) -> Arr16 {
+        self.compute().0
+    }
+
+    pub fn write_m(&self) -> bool {
+        decode(self.instruction).dest_m
+    }
+
+    pub fn address_m(&self) -> [bool; 15] {
+        let a = self.a.out();
+        std::array::from_fn(|k| a[k + 1])
+    }
+
+    pub fn pc(&self) -> [bool; 15] {
+        let pc = self.pc.out();
+        std::array::from_fn(|k| pc[k + 1])
+    }
+}
+
+impl Clocked for CPU {
+    fn tick(&mut self) {
+        let d = decode(self.instruction);
+        let (out, zr, ng) = self.compute();
+
+        self.a.i = mbg::mux16(self.instruction, out, d.c);
+        self.a.load = bg::or(bg::not(d.c), d.dest_a);
+
+        self.d.i = out;
+        self.d.load = d.dest_d;
+
+        self.pc.i = self.a.out();
+        self.pc.load = jump(d, zr, ng);
+        self.pc.inc = true;
+        self.pc.reset = self.reset;
+
+        self.a.tick();
+        self.d.tick();
+        self.pc.tick();
+    }
+
+    fn tock(&mut self) {
+        self.a.tock();
+        self.d.tock();
+        self.pc.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logic_gates::convert::{self, from_u16, value};
+    use memory::clock::Clock;
+
+    fn parse(word: &str) -> Arr16 {
+        convert::parse(word).unwrap()
+    }
+
+    /// Runs the program against a small data memory, returning the memory.
+    fn run(program: &[&str], ram: &[u16], cycles: usize) -> Vec<u16> {
+        let mut clock = Clock::new();
+        let mut cpu = CPU::new();
+        let mut ram = ram.to_vec();
+        ram.resize(32, 0);
+
+        for _ in 0..cycles {
+            let pc = value(&cpu.pc()) as usize;
+            cpu.instruction = program.get(pc).map(|w| parse(w)).unwrap_or([false; 16]);
+            let address = value(&cpu.address_m()) as usize;
+            cpu.in_m = from_u16(ram[address]);
+            if cpu.write_m() {
+                ram[address] = value(&cpu.out_m());
+            }
+            clock.cycle(&mut cpu);
+        }
+        ram
+    }
+
+    #[test]
+    fn test_decode() {
+        // D=D+A;JGT
+        let d = decode(parse("1110000010010001"));
+        assert_eq!(d.c, true);
+        assert_eq!(d.a, false);
+        assert_eq!(
+            (d.zx, d.nx, d.zy, d.ny, d.f, d.no),
+            (false, false, false, false, true, false)
+        );
+        assert_eq!((d.dest_a, d.dest_d, d.dest_m), (false, true, false));
+        assert_eq!((d.jlt, d.jeq, d.jgt), (false, false, true));
+
+        // @32767 sets every bit the C-instruction fields are read from
+        let d = decode(parse("0111111111111111"));
+        assert_eq!(d, Decoded::default());
+    }
+
+    #[test]
+    fn test_jump() {
+        let jumps = [
+            ("000", [false, false, false]),
+            ("001", [false, false, true]),
+            ("010", [false, true, false]),
+            ("011", [false, true, true]),
+            ("100", [true, false, false]),
+            ("101", [true, false, true]),
+            ("110", [true, true, false]),
+            ("111", [true, true, true]),
+        ];
+        // (zr, ng) for positive, zero and negative results
+        let results = [(false, false), (true, false), (false, true)];
+        for (bits, expected) in jumps {
+            let d = decode(parse(&format!("1110101010000{}", bits)));
+            for (k, (zr, ng)) in results.iter().enumerate() {
+                assert_eq!(jump(d, *zr, *ng), expected[2 - k], "{} {}", bits, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cpu_registers() {
+        let mut clock = Clock::new();
+        let mut cpu = CPU::new();
+
+        // @12345
+        cpu.instruction = parse("0011000000111001");
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.a()), 12345);
+        assert_eq!(value(&cpu.address_m()), 12345);
+        assert_eq!(value(&cpu.pc()), 1);
+
+        // D=A
+        cpu.instruction = parse("1110110000010000");
+        assert_eq!(cpu.write_m(), false);
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.d()), 12345);
+
+        // MD=D-1, with M = 11111
+        cpu.instruction = parse("1110001110011000");
+        cpu.in_m = from_u16(11111);
+        assert_eq!(cpu.write_m(), true);
+        assert_eq!(value(&cpu.out_m()), 12344);
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.d()), 12344);
+
+        // AM=M+1
+        cpu.instruction = parse("1111110111101000");
+        assert_eq!(value(&cpu.out_m()), 11112);
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.a()), 11112);
+        assert_eq!(value(&cpu.pc()), 4);
+
+        // 0;JMP
+        cpu.instruction = parse("1110101010000111");
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.pc()), 11112);
+
+        cpu.reset = true;
+        clock.cycle(&mut cpu);
+        assert_eq!(value(&cpu.pc()), 0);
+    }
+
+    #[test]
+    fn test_add_program() {
+        // R0 = 2 + 3
+        let program = [
+            "0000000000000010", // @2
+            "1110110000010000", // D=A
+            "0000000000000011", // @3
+            "1110000010010000", // D=D+A
+            "0000000000000000", // @0
+            "1110001100001000", // M=D
+        ];
+        let ram = run(&program, &[], 6);
+        assert_eq!(ram[0], 5);
+    }
+
+    #[test]
+    fn test_max_program() {
+        // R2 = max(R0, R1)
+        let program = [
+            "0000000000000000", // @R0
+            "1111110000010000", // D=M
+            "0000000000000001", // @R1
+            "1111010011010000", // D=D-M
+            "0000000000001010", // @OUTPUT_FIRST
+            "1110001100000001", // D;JGT
+            "0000000000000001", // @R1
+            "1111110000010000", // D=M
+            "0000000000001100", // @OUTPUT_D
+            "1110101010000111", // 0;JMP
+            "0000000000000000", // (OUTPUT_FIRST) @R0
+            "1111110000010000", // D=M
+            "0000000000000010", // (OUTPUT_D) @R2
+            "1110001100001000", // M=D
+            "0000000000001110", // (END) @END
+            "1110101010000111", // 0;JMP
+        ];
+        assert_eq!(run(&program, &[3, 5], 20)[2], 5);
+        assert_eq!(run(&program, &[23456, 12345], 20)[2], 23456);
+        // negative numbers in two's complement
+        assert_eq!(run(&program, &[0xFFFF, 0], 20)[2], 0);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::keyboard::Keyboard;
+use crate::screen::Screen;
+use logic_gates::Arr16;
+use logic_gates::basic_gates as bg;
+use logic_gates::multiway_basic_gates as mwg;
+use memory::clock::Clocked;
+use memory::ram::RAM16K;
+
+pub const SCREEN: u16 = 0x4000;
+pub const KBD: u16 = 0x6000;
+
+/// The data memory of the Hack computer, addressed by 15 bits.
+///
+/// a.0 a.1 | address       | chip
+/// ----------------------------------
+///  0   *  | 0x0000-0x3FFF | RAM16K
+///  1   0  | 0x4000-0x5FFF | Screen
+///  1   1  | 0x6000        | Keyboard
+///
+/// The keyboard can only be read. The RAM and the screen only evaluate
+/// the RAM chips that are addressed, see RAM16K::fast.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 15],
+    ram: RAM16K,
+    screen: Screen,
+    keyboard: Keyboard,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory {
+            i: [false; 16],
+            load: false,
+            address: [false; 15],
+            ram: RAM16K::fast(),
+            screen: Screen::new(),
+            keyboard: Keyboard::new(),
+        }
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    pub fn keyboard(&mut self) -> &mut Keyboard {
+        &mut self.keyboard
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 15]) -> Arr16 {
+        let ram = self.ram.read(std::array::from_fn(|k| address[k + 1]));
+        let screen = self.screen.read(std::array::from_fn(|k| address[k + 2]));
+        mwg::mux4way16(
+            ram,
+            ram,
+            screen,
+            self.keyboard.out(),
+            [address[0], address[1]],
+        )
+    }
+}
+
+impl Clocked for Memory {
+    fn tick(&mut self) {
+        let loads = mwg::demux4way(self.load, [self.address[0], self.address[1]]);
+
+        self.ram.i = self.i;
+        self.ram.load = bg::or(loads[3], loads[2]);
+        self.ram.address = std::array::from_fn(|k| self.address[k + 1]);
+        self.ram.tick();
+
+        self.screen.i = self.i;
+        self.screen.load = loads[1];
+        self.screen.address = std::array::from_fn(|k| self.address[k + 2]);
+        self.screen.tick();
+    }
+
+    fn tock(&mut self) {
+        self.ram.tock();
+        self.screen.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::clock::Clock;
+
+    fn address(n: u16) -> [bool; 15] {
+        std::array::from_fn(|k| (n >> (14 - k)) & 1 == 1)
+    }
+
+    fn write(memory: &mut Memory, at: u16, value: Arr16) {
+        let mut clock = Clock::new();
+        memory.address = address(at);
+        memory.i = value;
+        memory.load = true;
+        clock.cycle(memory);
+        memory.load = false;
+    }
+
+    #[test]
+    fn test_memory_map() {
+        let mut memory = Memory::new();
+        let mut a = [false; 16];
+        a[3] = true;
+        let mut b = [false; 16];
+        b[7] = true;
+        let mut c = [false; 16];
+        c[15] = true;
+
+        write(&mut memory, 0x3FFF, a);
+        write(&mut memory, SCREEN, b);
+        write(&mut memory, 0x5FFF, c);
+        assert_eq!(memory.read(address(0x3FFF)), a);
+        assert_eq!(memory.read(address(SCREEN)), b);
+        assert_eq!(memory.read(address(0x5FFF)), c);
+        assert_eq!(memory.read(address(0)), [false; 16]);
+        assert_eq!(memory.screen().pixel(255, 496), true);
+
+        // the keyboard is read only
+        write(&mut memory, KBD, a);
+        assert_eq!(memory.read(address(KBD)), [false; 16]);
+        memory.keyboard().press(b);
+        memory.address = address(KBD);
+        assert_eq!(memory.out(), b);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::computer::Computer;
+use crate::data_memory::{KBD, SCREEN};
+use crate::rom::{LoadError, ROM32K};
+use logic_gates::convert::{bits, value};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+/// The ALU on native words, with the control bits of a C-instruction.
+fn alu(x: u16, y: u16, c: u16) -> u16 {
+    let bit = |n: u16| c & (1 << n) != 0;
+    let x = if bit(5) { 0 } else { x };
+    let x = if bit(4) { !x } else { x };
+    let y = if bit(3) { 0 } else { y };
+    let y = if bit(2) { !y } else { y };
+    let out = if bit(1) { x.wrapping_add(y) } else { x & y };
+    if bit(0) { !out } else { out }
+}
+
+/// The Hack computer at the instruction level: it runs the same
+/// programs as Computer, with the same memory map, but computes every
+/// cycle on native words instead of simulating its gates.
+///
+/// It is fast enough to run games, while Computer stays the reference:
+/// lockstep runs both and finds where they disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emulator {
+    pub reset: bool,
+    rom: Vec<u16>,
+    /// RAM, screen and keyboard, in the order of the memory map.
+    memory: Vec<u16>,
+    a: u16,
+    d: u16,
+    pc: u16,
+    time: u64,
+    written: Option<(u16, u16)>,
+}
+
+impl Emulator {
+    pub fn new(rom: &ROM32K) -> Emulator {
+        Emulator {
+            reset: false,
+            rom: (0..rom.len() as u16)
+                .map(|n| value(&rom.read(bits(n))))
+                .collect(),
+            memory: vec![0; KBD as usize + 1],
+            a: 0,
+            d: 0,
+            pc: 0,
+            time: 0,
+            written: None,
+        }
+    }
+
+    /// Emulator running the program in a .hack file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Emulator, LoadError> {
+        Ok(Emulator::new(&ROM32K::load(path)?))
+    }
+
+    pub fn step(&mut self, cycles: usize) {
+        for _ in 0..cycles {
+            self.cycle();
+        }
+    }
+
+    fn cycle(&mut self) {
+        let instruction = self.rom.get(self.pc as usize & 0x7FFF).copied();
+        let instruction = instruction.unwrap_or(0);
+        let next = self.pc.wrapping_add(1);
+        self.written = None;
+
+        if instruction & 0x8000 == 0 {
+            self.a = instruction;
+            self.pc = next;
+        } else {
+            let y = if instruction & 0x1000 != 0 {
+                self.peek(self.a)
+            } else {
+                self.a
+            };
+            let out = alu(self.d, y, instruction >> 6);
+            let (lt, eq, gt) = ((out as i16) < 0, out == 0, (out as i16) > 0);
+            let jump = (instruction & 4 != 0 && lt)
+                || (instruction & 2 != 0 && eq)
+                || (instruction & 1 != 0 && gt);
+            self.pc = if jump { self.a } else { next };
+            if instruction & 0x0008 != 0 {
+                let address = self.a & 0x7FFF;
+                self.written = Some((address, out));
+                self.write(address, out);
+            }
+            if instruction & 0x0020 != 0 {
+                self.a = out;
+            }
+            if instruction & 0x0010 != 0 {
+                self.d = out;
+            }
+        }
+        if self.reset {
+            self.pc = 0;
+        }
+        self.time += 1;
+    }
+
+    /// Number of cycles run so far.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn a(&self) -> u16 {
+        self.a
+    }
+
+    pub fn d(&self) -> u16 {
+        self.d
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.pc & 0x7FFF
+    }
+
+    /// The (address, value) the last cycle wrote to the data memory, if any.
+    pub fn written(&self) -> Option<(u16, u16)> {
+        self.written
+    }
+
+    /// Value at any address of the data memory, including the screen
+    /// and the keyboard, which also answers past its own address.
+    pub fn peek(&self, address: u16) -> u16 {
+        self.memory[(address & 0x7FFF).min(KBD) as usize]
+    }
+
+    /// Writes to the data memory without running the program,
+    /// for setting up the inputs of a program.
+    pub fn poke(&mut self, address: u16, word: u16) {
+        self.write(address & 0x7FFF, word);
+    }
+
+    /// Writes to the keyboard are ignored, as on Computer.
+    fn write(&mut self, address: u16, word: u16) {
+        if address < KBD {
+            self.memory[address as usize] = word;
+        }
+    }
+
+    pub fn pixel(&self, row: usize, column: usize) -> bool {
+        let word = self.memory[SCREEN as usize + 32 * row + column / 16];
+        (word >> (column % 16)) & 1 == 1
+    }
+
+    pub fn press(&mut self, key: u16) {
+        self.memory[KBD as usize] = key;
+    }
+
+    pub fn release(&mut self) {
+        self.memory[KBD as usize] = 0;
+    }
+}
+
+/// What differs between Computer and Emulator, as (gate level, native).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    A(u16, u16),
+    D(u16, u16),
+    PC(u16, u16),
+    Written(Option<(u16, u16)>, Option<(u16, u16)>),
+}
+
+/// The first cycle after which Computer and Emulator disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub time: u64,
+    pub mismatch: Mismatch,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let written = |w: Option<(u16, u16)>| match w {
+            Some((address, word)) => format!("{} to {}", word, address),
+            None => "nothing".to_string(),
+        };
+        let (what, gates, native) = match self.mismatch {
+            Mismatch::A(g, n) => ("A is", g.to_string(), n.to_string()),
+            Mismatch::D(g, n) => ("D is", g.to_string(), n.to_string()),
+            Mismatch::PC(g, n) => ("PC is", g.to_string(), n.to_string()),
+            Mismatch::Written(g, n) => ("wrote", written(g), written(n)),
+        };
+        write!(
+            f,
+            "cycle {}: {} {} on the gates but {} natively",
+            self.time, what, gates, native
+        )
+    }
+}
+
+impl Error for Divergence {}
+
+/// Runs Computer and Emulator side by side for the given number of
+/// cycles, comparing A, D, PC and the memory write after each one.
+///
+/// Both should start from the same state, and their reset and keyboard
+/// are left to the caller. Stops at the first divergence.
+pub fn lockstep(
+    computer: &mut Computer,
+    emulator: &mut Emulator,
+    cycles: usize,
+) -> Result<(), Divergence> {
+    for _ in 0..cycles {
+        computer.step(1);
+        emulator.step(1);
+        let mismatch = if computer.a() != emulator.a() {
+            Mismatch::A(computer.a(), emulator.a())
+        } else if computer.d() != emulator.d() {
+            Mismatch::D(computer.d(), emulator.d())
+        } else if computer.pc() != emulator.pc() {
+            Mismatch::PC(computer.pc(), emulator.pc())
+        } else if computer.written() != emulator.written() {
+            Mismatch::Written(computer.written(), emulator.written())
+        } else {
+            continue;
+        };
+        return Err(Divergence {
+            time: computer.time(),
+            mismatch,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hack(words: &[u16]) -> ROM32K {
+        let lines: Vec<String> = words.iter().map(|w| format!("{:016b}", w)).collect();
+        ROM32K::from_hack(&lines.join("\n")).unwrap()
+    }
+
+    /// A C-instruction from its fields.
+    fn c(comp: u16, dest: u16, jump: u16) -> u16 {
+        0xE000 | (comp << 6) | (dest << 3) | jump
+    }
+
+    const D: u16 = 0b010;
+    const M: u16 = 0b001;
+    const DM: u16 = 0b011;
+
+    /// Every comp field into D and M, then with D=0 every jump condition
+    /// on a negative, zero and positive result, each skipping a D=D+1
+    /// when taken.
+    fn program() -> Vec<u16> {
+        let mut words = vec![1234, c(0b0110000, D, 0), 100, c(0b0001100, M, 0)];
+        for comp in 0..128 {
+            words.extend([100, c(comp, DM, 0)]);
+        }
+        words.push(c(0b0101010, D, 0));
+        for jump in 0..8 {
+            for comp in [0b0111010, 0b0101010, 0b0111111] {
+                let target = words.len() as u16 + 3;
+                words.extend([target, c(comp, 0, jump), c(0b0011111, D, 0)]);
+            }
+        }
+        words
+    }
+
+    #[test]
+    fn test_alu() {
+        // comp bits of D+1, D-A, D&A, !D and -1
+        assert_eq!(alu(5, 9, 0b011111), 6);
+        assert_eq!(alu(5, 9, 0b010011), 5u16.wrapping_sub(9));
+        assert_eq!(alu(5, 9, 0b000000), 1);
+        assert_eq!(alu(5, 9, 0b001101), !5);
+        assert_eq!(alu(5, 9, 0b111010), 0xFFFF);
+    }
+
+    #[test]
+    fn test_program() {
+        let words = program();
+        let mut emulator = Emulator::new(&hack(&words));
+        while (emulator.pc() as usize) < words.len() {
+            emulator.step(1);
+        }
+        // a D=D+1 is skipped for each jump taken: none for the null jump,
+        // one of the three results for JGT, JEQ and JLT, two for their
+        // complements, and all three for JMP
+        let skipped = [0, 1, 1, 2, 1, 2, 2, 3].iter().sum::<usize>();
+        assert_eq!(emulator.time() as usize, words.len() - skipped);
+        assert_eq!(emulator.d(), 24 - skipped as u16);
+    }
+
+    #[test]
+    fn test_memory_map() {
+        let mut emulator = Emulator::new(&ROM32K::new());
+        emulator.poke(SCREEN + 32 * 3, 1 << 15);
+        assert_eq!(emulator.pixel(3, 15), true);
+        assert_eq!(emulator.pixel(3, 14), false);
+
+        emulator.press(75);
+        assert_eq!(emulator.peek(KBD), 75);
+        assert_eq!(emulator.peek(0x7FFF), 75);
+        emulator.poke(KBD, 1);
+        assert_eq!(emulator.peek(KBD), 75);
+        emulator.release();
+        assert_eq!(emulator.peek(KBD), 0);
+        // addresses are 15 bits
+        emulator.poke(0x8005, 9);
+        assert_eq!(emulator.peek(5), 9);
+    }
+
+    #[test]
+    fn test_lockstep() {
+        let rom = hack(&program());
+        let mut computer = Computer::new(rom.clone());
+        let mut emulator = Emulator::new(&rom);
+        computer.poke(100, 7);
+        emulator.poke(100, 7);
+        assert_eq!(lockstep(&mut computer, &mut emulator, 400), Ok(()));
+
+        computer.reset = true;
+        emulator.reset = true;
+        assert_eq!(lockstep(&mut computer, &mut emulator, 2), Ok(()));
+        assert_eq!(emulator.pc(), 0);
+    }
+
+    #[test]
+    fn test_divergence() {
+        // D=M from R0, which differs
+        let rom = hack(&[0, c(0b1110000, D, 0), 1, c(0b0001100, M, 0)]);
+        let mut computer = Computer::new(rom.clone());
+        let mut emulator = Emulator::new(&rom);
+        computer.poke(0, 3);
+        emulator.poke(0, 4);
+        let divergence = lockstep(&mut computer, &mut emulator, 4).unwrap_err();
+        assert_eq!(
+            divergence,
+            Divergence {
+                time: 2,
+                mismatch: Mismatch::D(3, 4)
+            }
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "cycle 2: D is 3 on the gates but 4 natively"
+        );
+
+        let written = Divergence {
+            time: 4,
+            mismatch: Mismatch::Written(Some((1, 3)), None),
+        };
+        assert_eq!(
+            written.to_string(),
+            "cycle 4: wrote 3 to 1 on the gates but nothing natively"
+        );
+    }
+}
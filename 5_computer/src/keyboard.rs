@@ -0,0 +1,48 @@
+use logic_gates::Arr16;
+
+/// Holds the code of the key currently pressed, or 0 when no key is.
+/// Like the ROM, a built-in chip, set from outside the computer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Keyboard {
+    key: Arr16,
+}
+
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard::default()
+    }
+
+    pub fn press(&mut self, key: Arr16) {
+        self.key = key;
+    }
+
+    pub fn release(&mut self) {
+        self.key = [false; 16];
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyboard() {
+        let mut keyboard = Keyboard::new();
+        assert_eq!(keyboard.out(), [false; 16]);
+
+        let mut k = [false; 16];
+        k[9] = true;
+        k[12] = true;
+        k[14] = true;
+        k[15] = true;
+        keyboard.press(k);
+        assert_eq!(keyboard.out(), k);
+
+        keyboard.release();
+        assert_eq!(keyboard.out(), [false; 16]);
+    }
+}
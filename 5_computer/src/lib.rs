@@ -0,0 +1,7 @@
+pub mod computer;
+pub mod cpu;
+pub mod data_memory;
+pub mod emulator;
+pub mod keyboard;
+pub mod rom;
+pub mod screen;
@@ -0,0 +1,161 @@
+use logic_gates::Arr16;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const ROM_SIZE: usize = 32768;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// A line that is not made of exactly 16 '0' or '1' characters.
+    InvalidWord {
+        line: usize,
+        text: String,
+    },
+    /// More words than fit in the ROM.
+    TooLong {
+        words: usize,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::InvalidWord { line, text } => {
+                write!(f, "line {}: '{}' is not a 16-bit binary word", line, text)
+            }
+            LoadError::TooLong { words } => {
+                write!(f, "{} words don't fit in a {} word ROM", words, ROM_SIZE)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+/// Read-only memory holding the program, 32K words addressed by 15 bits.
+///
+/// Like the DFF, this is a built-in chip: its contents are loaded
+/// from a .hack file, one 16 character binary word per line,
+/// instead of being written through the clock.
+/// Addresses past the end of the program read as 0.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ROM32K {
+    pub address: [bool; 15],
+    words: Vec<Arr16>,
+}
+
+impl ROM32K {
+    pub fn new() -> ROM32K {
+        ROM32K::default()
+    }
+
+    pub fn from_hack(text: &str) -> Result<ROM32K, LoadError> {
+        let mut words = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || LoadError::InvalidWord {
+                line: n + 1,
+                text: line.to_string(),
+            };
+            if line.len() != 16 {
+                return Err(invalid());
+            }
+            let mut word = [false; 16];
+            for (bit, c) in word.iter_mut().zip(line.chars()) {
+                *bit = match c {
+                    '0' => false,
+                    '1' => true,
+                    _ => return Err(invalid()),
+                };
+            }
+            words.push(word);
+        }
+        if words.len() > ROM_SIZE {
+            return Err(LoadError::TooLong { words: words.len() });
+        }
+        Ok(ROM32K {
+            address: [false; 15],
+            words,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ROM32K, LoadError> {
+        ROM32K::from_hack(&fs::read_to_string(path)?)
+    }
+
+    /// Number of words loaded.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 15]) -> Arr16 {
+        let index = address.iter().fold(0, |n, &b| (n << 1) | b as usize);
+        self.words.get(index).copied().unwrap_or([false; 16])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_hack() {
+        let rom = ROM32K::from_hack("0000000000000010\n\n1110110000010000\r\n").unwrap();
+        assert_eq!(rom.len(), 2);
+        assert_eq!(rom.out()[14], true);
+
+        let mut address = [false; 15];
+        address[14] = true;
+        assert_eq!(rom.read(address)[..4], [true, true, true, false]);
+
+        address[0] = true;
+        assert_eq!(rom.read(address), [false; 16]);
+    }
+
+    #[test]
+    fn test_invalid_words() {
+        match ROM32K::from_hack("0000000000000010\n000000000000001\n") {
+            Err(LoadError::InvalidWord { line: 2, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+        match ROM32K::from_hack("000000000000002A") {
+            Err(LoadError::InvalidWord { line: 1, text }) => assert_eq!(text, "000000000000002A"),
+            other => panic!("{:?}", other),
+        }
+        let too_long = "0000000000000000\n".repeat(ROM_SIZE + 1);
+        assert!(matches!(
+            ROM32K::from_hack(&too_long),
+            Err(LoadError::TooLong { words: 32769 })
+        ));
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(matches!(
+            ROM32K::load("does/not/exist.hack"),
+            Err(LoadError::Io(_))
+        ));
+    }
+}
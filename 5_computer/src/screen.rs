@@ -0,0 +1,110 @@
+use logic_gates::Arr16;
+use logic_gates::basic_gates as bg;
+use logic_gates::multibit_basic_gates as mbg;
+use memory::clock::Clocked;
+use memory::ram::RAM4K;
+
+pub const ROWS: usize = 256;
+pub const COLUMNS: usize = 512;
+
+/// 256 rows of 512 black and white pixels, mapped to 8K words
+/// built from two RAM4K selected by the upper address bit.
+///
+/// The pixel at row r, column c is the bit c % 16 of the word
+/// 32 * r + c / 16, counting from the least significant bit.
+/// A 1 is a black pixel. Like the data memory, only the RAM chips that
+/// are addressed are evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    pub i: Arr16,
+    pub load: bool,
+    pub address: [bool; 13],
+    rams: Vec<RAM4K>,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen {
+    pub fn new() -> Screen {
+        Screen {
+            i: [false; 16],
+            load: false,
+            address: [false; 13],
+            rams: vec![RAM4K::fast(); 2],
+        }
+    }
+
+    pub fn out(&self) -> Arr16 {
+        self.read(self.address)
+    }
+
+    pub fn read(&self, address: [bool; 13]) -> Arr16 {
+        let low = std::array::from_fn(|k| address[k + 1]);
+        mbg::mux16(self.rams[0].read(low), self.rams[1].read(low), address[0])
+    }
+
+    pub fn pixel(&self, row: usize, column: usize) -> bool {
+        let word = 32 * row + column / 16;
+        let address = std::array::from_fn(|k| (word >> (12 - k)) & 1 == 1);
+        self.read(address)[15 - column % 16]
+    }
+}
+
+impl Clocked for Screen {
+    fn tick(&mut self) {
+        let low = std::array::from_fn(|k| self.address[k + 1]);
+        let loads = bg::demux(self.load, self.address[0]);
+        for (k, ram) in self.rams.iter_mut().enumerate() {
+            ram.i = self.i;
+            ram.load = loads[1 - k];
+            ram.address = low;
+            ram.tick();
+        }
+    }
+
+    fn tock(&mut self) {
+        self.rams.tock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::clock::Clock;
+
+    fn address(n: usize) -> [bool; 13] {
+        std::array::from_fn(|k| (n >> (12 - k)) & 1 == 1)
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut clock = Clock::new();
+        let mut screen = Screen::new();
+
+        // leftmost pixel of the first row, and rightmost of the last
+        let mut leftmost = [false; 16];
+        leftmost[15] = true;
+        let mut rightmost = [false; 16];
+        rightmost[0] = true;
+
+        screen.i = leftmost;
+        screen.load = true;
+        screen.address = address(0);
+        clock.cycle(&mut screen);
+        screen.i = rightmost;
+        screen.address = address(8191);
+        clock.cycle(&mut screen);
+
+        assert_eq!(screen.read(address(0)), leftmost);
+        assert_eq!(screen.read(address(8191)), rightmost);
+        assert_eq!(screen.read(address(4095)), [false; 16]);
+        assert_eq!(screen.pixel(0, 0), true);
+        assert_eq!(screen.pixel(0, 1), false);
+        assert_eq!(screen.pixel(ROWS - 1, COLUMNS - 1), true);
+        assert_eq!(screen.pixel(ROWS - 1, COLUMNS - 2), false);
+    }
+}
@@ -0,0 +1,37 @@
+use assembler::diagnostics;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "usage: hack-asm <file.asm> [-o <file.hack>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, output) = match args.as_slice() {
+        [input] => {
+            let input = PathBuf::from(input);
+            let output = input.with_extension("hack");
+            (input, output)
+        }
+        [input, o, output] if o == "-o" => (PathBuf::from(input), PathBuf::from(output)),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let source = fs::read_to_string(&input).unwrap_or_else(|e| {
+        eprintln!("{}: {}", input.display(), e);
+        process::exit(1);
+    });
+    let words = assembler::assemble(&source).unwrap_or_else(|errors| {
+        let file = input.display().to_string();
+        eprint!("{}", diagnostics::render(&file, &source, &errors));
+        process::exit(1);
+    });
+    if let Err(e) = fs::write(&output, assembler::to_hack(&words)) {
+        eprintln!("{}: {}", output.display(), e);
+        process::exit(1);
+    }
+}
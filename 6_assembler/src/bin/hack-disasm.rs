@@ -0,0 +1,50 @@
+use assembler::disasm;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "usage: hack-disasm <file.hack> [-o <file.asm>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, output) = match args.as_slice() {
+        [input] => (PathBuf::from(input), None),
+        [input, o, output] if o == "-o" => (PathBuf::from(input), Some(PathBuf::from(output))),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let hack = fs::read_to_string(&input).unwrap_or_else(|e| {
+        eprintln!("{}: {}", input.display(), e);
+        process::exit(1);
+    });
+    let program = disasm::disassemble(&hack).unwrap_or_else(|e| {
+        eprintln!("{}: {}", input.display(), e);
+        process::exit(1);
+    });
+    for (address, d) in program.iter().enumerate() {
+        for warning in d.warnings.iter() {
+            eprintln!(
+                "warning: {}: ROM[{}] = {:016b}: {}",
+                input.display(),
+                address,
+                d.word,
+                warning
+            );
+        }
+    }
+
+    let asm = disasm::to_asm(&program);
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(&output, asm) {
+                eprintln!("{}: {}", output.display(), e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", asm),
+    }
+}
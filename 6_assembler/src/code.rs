@@ -0,0 +1,187 @@
+//! Binary codes of the C-instruction fields.
+//!
+//! 1 1 1 | a c1 c2 c3 c4 c5 c6 | d1 d2 d3 | j1 j2 j3
+//!       |        comp         |   dest   |   jump
+//!
+//! The comp bits are the a bit, selecting A (0) or M (1) as the ALU's y
+//! input, followed by the zx nx zy ny f no control bits of alu::alu,
+//! whose x input is always D.
+
+///  mnemonic | a zx nx zy ny f no
+pub const COMP: [(&str, u16); 28] = [
+    ("0", 0b0101010),
+    ("1", 0b0111111),
+    ("-1", 0b0111010),
+    ("D", 0b0001100),
+    ("A", 0b0110000),
+    ("!D", 0b0001101),
+    ("!A", 0b0110001),
+    ("-D", 0b0001111),
+    ("-A", 0b0110011),
+    ("D+1", 0b0011111),
+    ("A+1", 0b0110111),
+    ("D-1", 0b0001110),
+    ("A-1", 0b0110010),
+    ("D+A", 0b0000010),
+    ("D-A", 0b0010011),
+    ("A-D", 0b0000111),
+    ("D&A", 0b0000000),
+    ("D|A", 0b0010101),
+    ("M", 0b1110000),
+    ("!M", 0b1110001),
+    ("-M", 0b1110011),
+    ("M+1", 0b1110111),
+    ("M-1", 0b1110010),
+    ("D+M", 0b1000010),
+    ("D-M", 0b1010011),
+    ("M-D", 0b1000111),
+    ("D&M", 0b1000000),
+    ("D|M", 0b1010101),
+];
+
+/// mnemonic | j1 (out < 0) j2 (out = 0) j3 (out > 0)
+pub const JUMP: [(&str, u16); 7] = [
+    ("JGT", 0b001),
+    ("JEQ", 0b010),
+    ("JGE", 0b011),
+    ("JLT", 0b100),
+    ("JNE", 0b101),
+    ("JLE", 0b110),
+    ("JMP", 0b111),
+];
+
+/// Commutative operations can be written either way, D+A or A+D.
+pub fn comp(mnemonic: &str) -> Option<u16> {
+    let find = |m: &str| COMP.iter().find(|(c, _)| *c == m).map(|(_, b)| *b);
+    find(mnemonic).or_else(|| {
+        let mut chars = mnemonic.chars();
+        match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (
+                Some(x @ ('A' | 'D' | 'M')),
+                Some(op @ ('+' | '&' | '|')),
+                Some(y @ ('A' | 'D' | 'M')),
+                None,
+            ) => find(&format!("{}{}{}", y, op, x)),
+            _ => None,
+        }
+    })
+}
+
+/// Mnemonics the book uses for each dest, indexed by d1 d2 d3.
+pub const DEST: [&str; 8] = ["", "M", "D", "MD", "A", "AM", "AD", "AMD"];
+
+/// Any combination of A, D and M, each at most once, in any order.
+///
+/// d1 d2 d3
+///  A  D  M
+pub fn dest(mnemonic: &str) -> Option<u16> {
+    let mut bits = 0;
+    for c in mnemonic.chars() {
+        let bit = match c {
+            'A' => 0b100,
+            'D' => 0b010,
+            'M' => 0b001,
+            _ => return None,
+        };
+        if bits & bit != 0 {
+            return None;
+        }
+        bits |= bit;
+    }
+    Some(bits)
+}
+
+pub fn jump(mnemonic: &str) -> Option<u16> {
+    JUMP.iter().find(|(j, _)| *j == mnemonic).map(|(_, b)| *b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alu::alu::alu;
+    use logic_gates::convert::{from_i16, to_i16};
+
+    /// What a comp mnemonic means, written independently of the table.
+    fn meaning(mnemonic: &str, d: i16, a: i16, m: i16) -> i16 {
+        let register = |c: char| match c {
+            'D' => d,
+            'A' => a,
+            'M' => m,
+            _ => c.to_digit(10).unwrap() as i16,
+        };
+        let c: Vec<char> = mnemonic.chars().collect();
+        match c.as_slice() {
+            ['-', '1'] => -1,
+            [x] => register(*x),
+            ['!', x] => !register(*x),
+            ['-', x] => register(*x).wrapping_neg(),
+            [x, '+', y] => register(*x).wrapping_add(register(*y)),
+            [x, '-', y] => register(*x).wrapping_sub(register(*y)),
+            [x, '&', y] => register(*x) & register(*y),
+            [x, '|', y] => register(*x) | register(*y),
+            _ => panic!("unknown comp {}", mnemonic),
+        }
+    }
+
+    #[test]
+    fn test_comp_table_matches_alu() {
+        let samples = [
+            (0, 0, 0),
+            (17, 3, -9),
+            (-5, 12, 31000),
+            (i16::MIN, i16::MAX, 1),
+        ];
+        for (mnemonic, code) in COMP {
+            let c: [bool; 7] = std::array::from_fn(|k| (code >> (6 - k)) & 1 == 1);
+            for (d, a, m) in samples {
+                let y = if c[0] { m } else { a };
+                let (out, zr, ng) =
+                    alu(from_i16(d), from_i16(y), c[1], c[2], c[3], c[4], c[5], c[6]);
+                let expected = meaning(mnemonic, d, a, m);
+                assert_eq!(
+                    to_i16(out),
+                    expected,
+                    "{} d={} a={} m={}",
+                    mnemonic,
+                    d,
+                    a,
+                    m
+                );
+                assert_eq!(zr, expected == 0);
+                assert_eq!(ng, expected < 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_comp() {
+        assert_eq!(comp("D+1"), Some(0b0011111));
+        assert_eq!(comp("A+D"), Some(0b0000010));
+        assert_eq!(comp("M|D"), Some(0b1010101));
+        assert_eq!(comp("A-D"), Some(0b0000111));
+        assert_eq!(comp("1+D"), None);
+        assert_eq!(comp("D*A"), None);
+        assert_eq!(comp(""), None);
+    }
+
+    #[test]
+    fn test_dest() {
+        assert_eq!(dest(""), Some(0));
+        assert_eq!(dest("M"), Some(0b001));
+        assert_eq!(dest("MD"), Some(0b011));
+        assert_eq!(dest("DM"), Some(0b011));
+        assert_eq!(dest("AMD"), Some(0b111));
+        assert_eq!(dest("MM"), None);
+        for (bits, mnemonic) in DEST.iter().enumerate() {
+            assert_eq!(dest(mnemonic), Some(bits as u16));
+        }
+        assert_eq!(dest("X"), None);
+    }
+
+    #[test]
+    fn test_jump() {
+        assert_eq!(jump("JGT"), Some(0b001));
+        assert_eq!(jump("JMP"), Some(0b111));
+        assert_eq!(jump("jmp"), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::ROM_SIZE;
+use crate::code::JUMP;
+use crate::parser::MAX_CONSTANT;
+use std::fmt;
+
+/// Where an error is, lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    /// Number of characters underlined.
+    pub len: usize,
+}
+
+impl Span {
+    /// Span of `text`, found at byte `start` of the line.
+    pub fn new(line: &str, number: usize, start: usize, text: &str) -> Span {
+        Span {
+            line: number,
+            column: line[..start].chars().count() + 1,
+            len: text.chars().count().max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    MissingAddress,
+    InvalidSymbol(String),
+    ConstantTooLarge(String),
+    InvalidDest(String),
+    InvalidComp(String),
+    InvalidJump(String),
+    InvalidLabel(String),
+    DuplicateLabel { label: String, first: usize },
+    PredefinedLabel(String),
+    LabelOutOfRange(String),
+    ProgramTooLarge,
+}
+
+impl ErrorKind {
+    pub fn help(&self) -> Option<String> {
+        match self {
+            ErrorKind::InvalidSymbol(_) | ErrorKind::InvalidLabel(_) => Some(
+                "symbols are letters, digits, '_', '.', '$' and ':', not starting with a digit"
+                    .to_string(),
+            ),
+            ErrorKind::ConstantTooLarge(_) => Some(format!(
+                "A-instructions hold 15 bits, from 0 to {}",
+                MAX_CONSTANT
+            )),
+            ErrorKind::InvalidDest(_) => {
+                Some("dest is any combination of A, D and M, e.g. AM".to_string())
+            }
+            ErrorKind::InvalidJump(_) => {
+                let jumps: Vec<&str> = JUMP.iter().map(|(j, _)| *j).collect();
+                Some(format!("expected one of {}", jumps.join(", ")))
+            }
+            ErrorKind::DuplicateLabel { first, .. } => {
+                Some(format!("first defined on line {}", first))
+            }
+            ErrorKind::PredefinedLabel(_) => Some(
+                "SP, LCL, ARG, THIS, THAT, R0 to R15, SCREEN and KBD are predefined".to_string(),
+            ),
+            ErrorKind::LabelOutOfRange(_) | ErrorKind::ProgramTooLarge => Some(format!(
+                "the ROM holds {} instructions, at addresses 0 to {}",
+                ROM_SIZE, MAX_CONSTANT
+            )),
+            ErrorKind::MissingAddress | ErrorKind::InvalidComp(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::MissingAddress => write!(f, "expected a constant or a symbol after '@'"),
+            ErrorKind::InvalidSymbol(s) => write!(f, "invalid symbol '{}'", s),
+            ErrorKind::ConstantTooLarge(s) => write!(f, "constant {} is out of range", s),
+            ErrorKind::InvalidDest(s) => write!(f, "unknown dest '{}'", s),
+            ErrorKind::InvalidComp(s) => write!(f, "unknown comp mnemonic '{}'", s),
+            ErrorKind::InvalidJump(s) => write!(f, "unknown jump mnemonic '{}'", s),
+            ErrorKind::InvalidLabel(s) => write!(f, "malformed label '{}'", s),
+            ErrorKind::DuplicateLabel { label, .. } => {
+                write!(f, "label '{}' is defined more than once", label)
+            }
+            ErrorKind::PredefinedLabel(label) => {
+                write!(f, "label '{}' redefines a predefined symbol", label)
+            }
+            ErrorKind::LabelOutOfRange(label) => {
+                write!(f, "label '{}' is past the end of the ROM", label)
+            }
+            ErrorKind::ProgramTooLarge => write!(f, "the program does not fit in the ROM"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub span: Span,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.span.line, self.span.column, self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Renders the errors the way rustc does, with the offending
+/// line and the error underlined.
+///
+/// error: unknown comp mnemonic 'D*A'
+///  --> Prog.asm:3:3
+///   |
+/// 3 | D=D*A
+///   |   ^^^
+pub fn render(file: &str, source: &str, errors: &[Error]) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut out = String::new();
+    for error in errors {
+        let span = error.span;
+        let line = lines.get(span.line - 1).copied().unwrap_or("");
+        let gutter = " ".repeat(span.line.to_string().len());
+
+        out += &format!("error: {}\n", error.kind);
+        out += &format!("{}--> {}:{}:{}\n", gutter, file, span.line, span.column);
+        out += &format!("{} |\n", gutter);
+        out += &format!("{} | {}\n", span.line, line.trim_end());
+        out += &format!(
+            "{} | {}{}\n",
+            gutter,
+            " ".repeat(span.column - 1),
+            "^".repeat(span.len)
+        );
+        if let Some(help) = error.kind.help() {
+            out += &format!("{} = help: {}\n", gutter, help);
+        }
+        out += "\n";
+    }
+    match errors.len() {
+        0 => {}
+        1 => out += &format!("error: could not assemble {} due to 1 error\n", file),
+        n => out += &format!("error: could not assemble {} due to {} errors\n", file, n),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        let line = "  D=D*A // x";
+        assert_eq!(
+            Span::new(line, 3, 4, "D*A"),
+            Span {
+                line: 3,
+                column: 5,
+                len: 3
+            }
+        );
+        // columns count characters, not bytes
+        let line = "// é\n";
+        assert_eq!(Span::new(line, 1, 5, "").column, 5);
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "@1\nD=D*A\n";
+        let errors = [Error {
+            span: Span {
+                line: 2,
+                column: 3,
+                len: 3,
+            },
+            kind: ErrorKind::InvalidComp("D*A".to_string()),
+        }];
+        assert_eq!(
+            render("Prog.asm", source, &errors),
+            "\
+error: unknown comp mnemonic 'D*A'
+ --> Prog.asm:2:3
+  |
+2 | D=D*A
+  |   ^^^
+
+error: could not assemble Prog.asm due to 1 error
+"
+        );
+    }
+
+    #[test]
+    fn test_render_help() {
+        let source = "(LOOP)\n\n\n\n\n\n\n\n\n(LOOP)";
+        let errors = [Error {
+            span: Span {
+                line: 10,
+                column: 1,
+                len: 6,
+            },
+            kind: ErrorKind::DuplicateLabel {
+                label: "LOOP".to_string(),
+                first: 1,
+            },
+        }];
+        assert_eq!(
+            render("Prog.asm", source, &errors),
+            "\
+error: label 'LOOP' is defined more than once
+  --> Prog.asm:10:1
+   |
+10 | (LOOP)
+   | ^^^^^^
+   = help: first defined on line 1
+
+error: could not assemble Prog.asm due to 1 error
+"
+        );
+    }
+}
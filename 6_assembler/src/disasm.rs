@@ -0,0 +1,272 @@
+use crate::code::{COMP, DEST, JUMP};
+use crate::parser::{Address, Instruction};
+use logic_gates::convert::{from_i16, to_i16};
+use std::fmt;
+
+/// Values of D, A and M the ALU is evaluated on to tell its operations
+/// apart. Every pair of Hack operations differs on at least one of them.
+const SAMPLES: [(i16, i16, i16); 8] = [
+    (0, 0, 0),
+    (1, 0, 0),
+    (0, 1, 0),
+    (0, 0, 1),
+    (5, 3, -2),
+    (-7, 12, 9),
+    (0x5555, 0x3333, 0x0F0F),
+    (i16::MIN, -1, i16::MAX),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// Bits 14 and 13 of a C-instruction should be 1, the CPU ignores them.
+    UnusedBits,
+    /// The comp bits compute a Hack operation, but aren't the bits the
+    /// assembler uses for it, so assembling it back gives another word.
+    NonStandardComp(&'static str),
+    /// The comp bits (a zx nx zy ny f no) compute none of the Hack operations.
+    InvalidComp(u16),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::UnusedBits => write!(f, "bits 14 and 13 of a C-instruction should be 1"),
+            Warning::NonStandardComp(comp) => {
+                write!(f, "non-standard encoding of comp '{}'", comp)
+            }
+            Warning::InvalidComp(bits) => {
+                write!(f, "comp bits {:07b} are not a Hack ALU operation", bits)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembled {
+    pub word: u16,
+    /// None when the word can't be written in assembly.
+    pub instruction: Option<Instruction>,
+    pub warnings: Vec<Warning>,
+}
+
+impl fmt::Display for Disassembled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.instruction {
+            Some(instruction) => write!(f, "{}", instruction),
+            None => write!(f, "// invalid instruction {:016b}", self.word),
+        }
+    }
+}
+
+/// What alu::alu computes with these comp bits, as a CPU would wire it.
+fn evaluate(comp: u16, d: i16, a: i16, m: i16) -> i16 {
+    let c: [bool; 7] = std::array::from_fn(|k| (comp >> (6 - k)) & 1 == 1);
+    let y = if c[0] { m } else { a };
+    to_i16(alu::alu::alu(from_i16(d), from_i16(y), c[1], c[2], c[3], c[4], c[5], c[6]).0)
+}
+
+/// The comp mnemonic computed by the 7 comp bits, and whether the bits
+/// are the standard ones for it.
+///
+/// Of the 128 combinations only 28 are in the assembler's table,
+/// the others are matched against them by running the ALU.
+pub fn operation(comp: u16) -> Option<(&'static str, bool)> {
+    if let Some((mnemonic, _)) = COMP.iter().find(|(_, c)| *c == comp) {
+        return Some((mnemonic, true));
+    }
+    COMP.iter()
+        .find(|(_, standard)| {
+            SAMPLES
+                .iter()
+                .all(|&(d, a, m)| evaluate(comp, d, a, m) == evaluate(*standard, d, a, m))
+        })
+        .map(|(mnemonic, _)| (*mnemonic, false))
+}
+
+pub fn disassemble_word(word: u16) -> Disassembled {
+    if word & 0x8000 == 0 {
+        return Disassembled {
+            word,
+            instruction: Some(Instruction::A(Address::Constant(word))),
+            warnings: Vec::new(),
+        };
+    }
+
+    let mut warnings = Vec::new();
+    if word & 0x6000 != 0x6000 {
+        warnings.push(Warning::UnusedBits);
+    }
+    let comp = (word >> 6) & 0b1111111;
+    let dest = (word >> 3) & 0b111;
+    let jump = word & 0b111;
+
+    let instruction = match operation(comp) {
+        Some((mnemonic, standard)) => {
+            if !standard {
+                warnings.push(Warning::NonStandardComp(mnemonic));
+            }
+            let dest = DEST[dest as usize];
+            Some(Instruction::C {
+                dest: (!dest.is_empty()).then(|| dest.to_string()),
+                comp: mnemonic.to_string(),
+                jump: JUMP
+                    .iter()
+                    .find(|(_, j)| *j == jump)
+                    .map(|(j, _)| j.to_string()),
+            })
+        }
+        None => {
+            warnings.push(Warning::InvalidComp(comp));
+            None
+        }
+    };
+    Disassembled {
+        word,
+        instruction,
+        warnings,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidWord {
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for InvalidWord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: '{}' is not a 16-bit binary word",
+            self.line, self.text
+        )
+    }
+}
+
+impl std::error::Error for InvalidWord {}
+
+/// Disassembles a .hack file, one 16 character binary word per line.
+pub fn disassemble(hack: &str) -> Result<Vec<Disassembled>, InvalidWord> {
+    let mut program = Vec::new();
+    for (n, line) in hack.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let valid = line.len() == 16 && line.chars().all(|c| c == '0' || c == '1');
+        match valid {
+            true => program.push(disassemble_word(u16::from_str_radix(line, 2).unwrap())),
+            false => {
+                return Err(InvalidWord {
+                    line: n + 1,
+                    text: line.to_string(),
+                });
+            }
+        }
+    }
+    Ok(program)
+}
+
+/// The program as assembly, one instruction per line.
+pub fn to_asm(program: &[Disassembled]) -> String {
+    program.iter().map(|d| format!("{}\n", d)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble, to_hack};
+
+    #[test]
+    fn test_a_instruction() {
+        let d = disassemble_word(123);
+        assert_eq!(d.to_string(), "@123");
+        assert!(d.warnings.is_empty());
+        assert_eq!(disassemble_word(0x7FFF).to_string(), "@32767");
+    }
+
+    #[test]
+    fn test_c_instruction() {
+        assert_eq!(
+            disassemble_word(0b1110000010010001).to_string(),
+            "D=D+A;JGT"
+        );
+        assert_eq!(
+            disassemble_word(0b1111010101111110).to_string(),
+            "AMD=D|M;JLE"
+        );
+        assert_eq!(disassemble_word(0b1110101010000111).to_string(), "0;JMP");
+        assert_eq!(disassemble_word(0b1111110010011000).to_string(), "MD=M-1");
+    }
+
+    #[test]
+    fn test_every_standard_comp() {
+        for (mnemonic, comp) in COMP {
+            assert_eq!(operation(comp), Some((mnemonic, true)));
+        }
+    }
+
+    #[test]
+    fn test_samples_tell_operations_apart() {
+        for (i, (_, x)) in COMP.iter().enumerate() {
+            for (_, y) in COMP.iter().skip(i + 1) {
+                assert!(
+                    SAMPLES
+                        .iter()
+                        .any(|&(d, a, m)| evaluate(*x, d, a, m) != evaluate(*y, d, a, m))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_standard_comp() {
+        // zx nx zy ny f no = 101010 with a = 1 still computes 0
+        let d = disassemble_word(0b1111101010000111);
+        assert_eq!(d.to_string(), "0;JMP");
+        assert_eq!(d.warnings, [Warning::NonStandardComp("0")]);
+
+        // zx = 1 and zy = 1 with f = 0, 0 & 0 is 0 too
+        assert_eq!(operation(0b0101000), Some(("0", false)));
+        // D+1 doesn't depend on A or M
+        assert_eq!(operation(0b1011111), Some(("D+1", false)));
+    }
+
+    #[test]
+    fn test_invalid_comp() {
+        // no = 1 on x & y is nand, which Hack assembly can't express
+        let d = disassemble_word(0b1110000001010000);
+        assert_eq!(d.instruction, None);
+        assert_eq!(d.warnings, [Warning::InvalidComp(0b0000001)]);
+        assert_eq!(d.to_string(), "// invalid instruction 1110000001010000");
+
+        let valid = (0..128).filter(|&c| operation(c).is_some()).count();
+        assert!(valid > 28 && valid < 128, "{}", valid);
+    }
+
+    #[test]
+    fn test_unused_bits() {
+        let d = disassemble_word(0b1000000010010001);
+        assert_eq!(d.to_string(), "D=D+A;JGT");
+        assert_eq!(d.warnings, [Warning::UnusedBits]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = "@2\nD=A\n@3\nD=D+A\n@0\nM=D\nAM=M-1;JNE\nD;JLE\n@32767\n";
+        let hack = to_hack(&assemble(source).unwrap());
+        let program = disassemble(&hack).unwrap();
+        assert_eq!(to_asm(&program), source);
+    }
+
+    #[test]
+    fn test_invalid_word() {
+        assert_eq!(
+            disassemble("0000000000000001\n\n00000002").unwrap_err(),
+            InvalidWord {
+                line: 3,
+                text: "00000002".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,262 @@
+pub mod code;
+pub mod diagnostics;
+pub mod disasm;
+pub mod parser;
+pub mod symbols;
+
+use diagnostics::{Error, ErrorKind};
+use parser::{Address, Instruction, MAX_CONSTANT};
+use std::collections::HashMap;
+use symbols::SymbolTable;
+
+/// Number of instructions ROM32K holds, the addresses an A-instruction can load.
+pub const ROM_SIZE: usize = MAX_CONSTANT as usize + 1;
+
+/// Translates Hack assembly into machine code, one word per instruction.
+///
+/// The first pass records the ROM address of every label,
+/// the second one translates the instructions, allocating
+/// variables for the symbols that aren't labels.
+///
+/// Every error in the source is returned, not only the first one.
+pub fn assemble(source: &str) -> Result<Vec<u16>, Vec<Error>> {
+    let (program, mut errors) = parser::parse(source);
+    let mut symbols = SymbolTable::new();
+
+    let mut first_lines = HashMap::new();
+    let mut address = 0;
+    for (span, instruction) in program.iter() {
+        match instruction {
+            Instruction::Label(label) if address >= ROM_SIZE => {
+                let kind = ErrorKind::LabelOutOfRange(label.clone());
+                errors.push(Error { span: *span, kind });
+            }
+            Instruction::Label(label) => {
+                if !symbols.add_label(label, address as u16) {
+                    // taken by a label of the source, or else a predefined symbol
+                    let kind = match first_lines.get(label) {
+                        Some(&first) => ErrorKind::DuplicateLabel {
+                            label: label.clone(),
+                            first,
+                        },
+                        None => ErrorKind::PredefinedLabel(label.clone()),
+                    };
+                    errors.push(Error { span: *span, kind });
+                }
+                first_lines.entry(label.clone()).or_insert(span.line);
+            }
+            _ => {
+                // only the first instruction past the end, not every one after it
+                if address == ROM_SIZE {
+                    let kind = ErrorKind::ProgramTooLarge;
+                    errors.push(Error { span: *span, kind });
+                }
+                address += 1;
+            }
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| (e.span.line, e.span.column));
+        return Err(errors);
+    }
+
+    let mut words = Vec::new();
+    for (_, instruction) in program.iter() {
+        match instruction {
+            Instruction::A(Address::Constant(n)) => words.push(*n),
+            Instruction::A(Address::Symbol(s)) => words.push(symbols.resolve(s)),
+            Instruction::C { dest, comp, jump } => words.push(translate_c(dest, comp, jump)),
+            Instruction::Label(_) => {}
+        }
+    }
+    Ok(words)
+}
+
+/// 1 1 1 | a c1 c2 c3 c4 c5 c6 | d1 d2 d3 | j1 j2 j3
+fn translate_c(dest: &Option<String>, comp: &str, jump: &Option<String>) -> u16 {
+    // the parser only accepts valid mnemonics
+    let dest = dest.as_deref().map_or(0, |d| code::dest(d).unwrap());
+    let comp = code::comp(comp).unwrap();
+    let jump = jump.as_deref().map_or(0, |j| code::jump(j).unwrap());
+    0b111 << 13 | comp << 6 | dest << 3 | jump
+}
+
+/// Words in the .hack format, one 16 character binary number per line.
+pub fn to_hack(words: &[u16]) -> String {
+    words.iter().map(|w| format!("{:016b}\n", w)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diagnostics::Span;
+
+    const MAX: &str = "
+        // R2 = max(R0, R1)
+        @R0
+        D=M
+        @R1
+        D=D-M
+        @OUTPUT_FIRST
+        D;JGT
+        @R1
+        D=M
+        @OUTPUT_D
+        0;JMP
+    (OUTPUT_FIRST)
+        @R0
+        D=M
+    (OUTPUT_D)
+        @R2
+        M=D
+    (END)
+        @END
+        0;JMP
+    ";
+
+    const MAX_HACK: &str = "\
+0000000000000000
+1111110000010000
+0000000000000001
+1111010011010000
+0000000000001010
+1110001100000001
+0000000000000001
+1111110000010000
+0000000000001100
+1110101010000111
+0000000000000000
+1111110000010000
+0000000000000010
+1110001100001000
+0000000000001110
+1110101010000111
+";
+
+    #[test]
+    fn test_max() {
+        assert_eq!(to_hack(&assemble(MAX).unwrap()), MAX_HACK);
+    }
+
+    #[test]
+    fn test_variables() {
+        let source = "@i\nM=1\n@sum\nM=0\n(LOOP)\n@i\nD=M\n@LOOP\n@SCREEN\n@KBD\n@THAT";
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![
+                16,
+                0b1110111111001000,
+                17,
+                0b1110101010001000,
+                16,
+                0b1111110000010000,
+                4,
+                16384,
+                24576,
+                4
+            ]
+        );
+    }
+
+    #[test]
+    fn test_c_instructions() {
+        assert_eq!(assemble("AMD=D|M;JLE").unwrap(), vec![0b1111010101111110]);
+        assert_eq!(assemble("D;JGT").unwrap(), vec![0b1110001100000001]);
+        assert_eq!(assemble("M=D").unwrap(), vec![0b1110001100001000]);
+        assert_eq!(assemble("MD=M-1").unwrap(), vec![0b1111110010011000]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = assemble("(LOOP)\n@LOOP\n  (LOOP)\n").unwrap_err();
+        assert_eq!(
+            errors,
+            [Error {
+                span: Span {
+                    line: 3,
+                    column: 3,
+                    len: 6
+                },
+                kind: ErrorKind::DuplicateLabel {
+                    label: "LOOP".into(),
+                    first: 1
+                }
+            }]
+        );
+
+        // predefined symbols can't be labels
+        let errors = assemble("@0\n(SP)\n(R3)").unwrap_err();
+        let kinds: Vec<&ErrorKind> = errors.iter().map(|e| &e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &ErrorKind::PredefinedLabel("SP".into()),
+                &ErrorKind::PredefinedLabel("R3".into())
+            ]
+        );
+        assert_eq!(
+            errors[0].kind.help().unwrap(),
+            "SP, LCL, ARG, THIS, THAT, R0 to R15, SCREEN and KBD are predefined"
+        );
+    }
+
+    #[test]
+    fn test_rom_overflow() {
+        let full = "D=0\n".repeat(ROM_SIZE - 1) + "(LAST)\n@LAST\n";
+        let words = assemble(&full).unwrap();
+        assert_eq!(words.len(), ROM_SIZE);
+        assert_eq!(words[ROM_SIZE - 1], 32767);
+
+        // one error for the first instruction that doesn't fit, not every one after it
+        let source = full + "(END)\n@END\n0;JMP\n";
+        let errors = assemble(&source).unwrap_err();
+        let found: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            found,
+            [
+                format!("{}:1: label 'END' is past the end of the ROM", ROM_SIZE + 2),
+                format!("{}:1: the program does not fit in the ROM", ROM_SIZE + 3),
+            ]
+        );
+        assert_eq!(
+            errors[0].kind.help().unwrap(),
+            "the ROM holds 32768 instructions, at addresses 0 to 32767"
+        );
+    }
+
+    #[test]
+    fn test_all_errors_are_reported() {
+        let source = "\
+(LOOP)
+    @32768
+    D=D*A
+(LOOP)
+    0;JUMP
+    @LOOP
+";
+        let errors = assemble(source).unwrap_err();
+        let found: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            found,
+            [
+                "2:6: constant 32768 is out of range",
+                "3:7: unknown comp mnemonic 'D*A'",
+                "4:1: label 'LOOP' is defined more than once",
+                "5:7: unknown jump mnemonic 'JUMP'",
+            ]
+        );
+        assert_eq!(
+            diagnostics::render("Loop.asm", source, &errors[..1]),
+            "\
+error: constant 32768 is out of range
+ --> Loop.asm:2:6
+  |
+2 |     @32768
+  |      ^^^^^
+  = help: A-instructions hold 15 bits, from 0 to 32767
+
+error: could not assemble Loop.asm due to 1 error
+"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use crate::code;
+use crate::diagnostics::{Error, ErrorKind, Span};
+use std::fmt;
+
+/// Largest constant of an A-instruction, its first bit must be 0.
+pub const MAX_CONSTANT: u16 = 32767;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    Constant(u16),
+    Symbol(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    /// @value
+    A(Address),
+    /// dest=comp;jump
+    C {
+        dest: Option<String>,
+        comp: String,
+        jump: Option<String>,
+    },
+    /// (LABEL)
+    Label(String),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::A(Address::Constant(n)) => write!(f, "@{}", n),
+            Instruction::A(Address::Symbol(s)) => write!(f, "@{}", s),
+            Instruction::C { dest, comp, jump } => {
+                if let Some(dest) = dest {
+                    write!(f, "{}=", dest)?;
+                }
+                write!(f, "{}", comp)?;
+                if let Some(jump) = jump {
+                    write!(f, ";{}", jump)?;
+                }
+                Ok(())
+            }
+            Instruction::Label(l) => write!(f, "({})", l),
+        }
+    }
+}
+
+/// Letters, digits, '_', '.', '$' and ':', not starting with a digit.
+pub fn is_symbol(s: &str) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || "_.$:".contains(c);
+    match s.chars().next() {
+        Some(first) => !first.is_ascii_digit() && s.chars().all(valid),
+        None => false,
+    }
+}
+
+/// A piece of a line, along with the byte it starts at.
+#[derive(Debug, Clone, Copy)]
+struct Part<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+impl<'a> Part<'a> {
+    fn trim(self) -> Part<'a> {
+        let trimmed = self.text.trim_start();
+        Part {
+            text: trimmed.trim_end(),
+            start: self.start + self.text.len() - trimmed.len(),
+        }
+    }
+
+    fn split_once(self, c: char) -> Option<(Part<'a>, Part<'a>)> {
+        let at = self.text.find(c)?;
+        let before = Part {
+            text: &self.text[..at],
+            start: self.start,
+        };
+        let after = Part {
+            text: &self.text[at + c.len_utf8()..],
+            start: self.start + at + c.len_utf8(),
+        };
+        Some((before.trim(), after.trim()))
+    }
+
+    /// The text without any whitespace, so that M - 1 reads as M-1.
+    fn compact(self) -> String {
+        self.text.chars().filter(|c| !c.is_whitespace()).collect()
+    }
+}
+
+/// Parses one line of assembly, None for lines with only whitespace
+/// and comments. Every error found on the line is returned.
+pub fn parse_line(line: &str, number: usize) -> Result<Option<(Span, Instruction)>, Vec<Error>> {
+    let code = match line.find("//") {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+    let code = Part {
+        text: code,
+        start: 0,
+    }
+    .trim();
+    let error = |part: Part, kind: ErrorKind| Error {
+        span: Span::new(line, number, part.start, part.text),
+        kind,
+    };
+    let span = Span::new(line, number, code.start, code.text);
+
+    if code.text.is_empty() {
+        Ok(None)
+    } else if let Some(value) = code.text.strip_prefix('@') {
+        let value = Part {
+            text: value,
+            start: code.start + 1,
+        }
+        .trim();
+        if value.text.is_empty() {
+            return Err(vec![error(code, ErrorKind::MissingAddress)]);
+        }
+        if value.text.chars().all(|c| c.is_ascii_digit()) {
+            return match value.text.parse::<u16>() {
+                Ok(n) if n <= MAX_CONSTANT => {
+                    Ok(Some((span, Instruction::A(Address::Constant(n)))))
+                }
+                _ => Err(vec![error(
+                    value,
+                    ErrorKind::ConstantTooLarge(value.text.to_string()),
+                )]),
+            };
+        }
+        match is_symbol(value.text) {
+            true => Ok(Some((
+                span,
+                Instruction::A(Address::Symbol(value.text.to_string())),
+            ))),
+            false => Err(vec![error(
+                value,
+                ErrorKind::InvalidSymbol(value.text.to_string()),
+            )]),
+        }
+    } else if code.text.starts_with('(') {
+        let label = code.text[1..].strip_suffix(')').map(str::trim);
+        match label {
+            Some(label) if is_symbol(label) => {
+                Ok(Some((span, Instruction::Label(label.to_string()))))
+            }
+            _ => Err(vec![error(
+                code,
+                ErrorKind::InvalidLabel(code.text.to_string()),
+            )]),
+        }
+    } else {
+        parse_c(code, error).map(|c| Some((span, c)))
+    }
+}
+
+fn parse_c(
+    code: Part,
+    error: impl Fn(Part, ErrorKind) -> Error,
+) -> Result<Instruction, Vec<Error>> {
+    let (dest, rest) = match code.split_once('=') {
+        Some((dest, rest)) => (Some(dest), rest),
+        None => (None, code),
+    };
+    let (comp, jump) = match rest.split_once(';') {
+        Some((comp, jump)) => (comp, Some(jump)),
+        None => (rest, None),
+    };
+
+    let mut errors = Vec::new();
+    if let Some(dest) = dest
+        && (dest.text.is_empty() || code::dest(&dest.compact()).is_none())
+    {
+        errors.push(error(dest, ErrorKind::InvalidDest(dest.text.to_string())));
+    }
+    if code::comp(&comp.compact()).is_none() {
+        errors.push(error(comp, ErrorKind::InvalidComp(comp.text.to_string())));
+    }
+    if let Some(jump) = jump
+        && code::jump(&jump.compact()).is_none()
+    {
+        errors.push(error(jump, ErrorKind::InvalidJump(jump.text.to_string())));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(Instruction::C {
+        dest: dest.map(Part::compact),
+        comp: comp.compact(),
+        jump: jump.map(Part::compact),
+    })
+}
+
+/// Every instruction of the source along with where it is,
+/// and every error found in the lines that don't parse.
+pub fn parse(source: &str) -> (Vec<(Span, Instruction)>, Vec<Error>) {
+    let mut instructions = Vec::new();
+    let mut errors = Vec::new();
+    for (n, line) in source.lines().enumerate() {
+        match parse_line(line, n + 1) {
+            Ok(Some(instruction)) => instructions.push(instruction),
+            Ok(None) => {}
+            Err(e) => errors.extend(e),
+        }
+    }
+    (instructions, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(dest: Option<&str>, comp: &str, jump: Option<&str>) -> Instruction {
+        Instruction::C {
+            dest: dest.map(String::from),
+            comp: comp.to_string(),
+            jump: jump.map(String::from),
+        }
+    }
+
+    fn instruction(line: &str) -> Option<Instruction> {
+        parse_line(line, 1).unwrap().map(|(_, i)| i)
+    }
+
+    /// (column, len, kind) of every error in the line
+    fn errors(line: &str) -> Vec<(usize, usize, ErrorKind)> {
+        parse_line(line, 1)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| (e.span.column, e.span.len, e.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(instruction(""), None);
+        assert_eq!(instruction("   // comment"), None);
+        assert_eq!(
+            instruction("@21 // comment"),
+            Some(Instruction::A(Address::Constant(21)))
+        );
+        assert_eq!(
+            instruction("  @sum.$1:x"),
+            Some(Instruction::A(Address::Symbol("sum.$1:x".to_string())))
+        );
+        assert_eq!(
+            instruction("(LOOP)"),
+            Some(Instruction::Label("LOOP".to_string()))
+        );
+        assert_eq!(instruction("D=D+A"), Some(c(Some("D"), "D+A", None)));
+        assert_eq!(instruction("0;JMP"), Some(c(None, "0", Some("JMP"))));
+        assert_eq!(
+            instruction("AM = M - 1 ; JNE"),
+            Some(c(Some("AM"), "M-1", Some("JNE")))
+        );
+    }
+
+    #[test]
+    fn test_instruction_span() {
+        let (span, _) = parse_line("    D=D+A  // add", 7).unwrap().unwrap();
+        assert_eq!(
+            span,
+            Span {
+                line: 7,
+                column: 5,
+                len: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        use ErrorKind::*;
+        assert_eq!(errors("@32768"), [(2, 5, ConstantTooLarge("32768".into()))]);
+        assert_eq!(
+            errors("  @99999999"),
+            [(4, 8, ConstantTooLarge("99999999".into()))]
+        );
+        assert_eq!(errors("@1abc"), [(2, 4, InvalidSymbol("1abc".into()))]);
+        assert_eq!(errors("@"), [(1, 1, MissingAddress)]);
+        assert_eq!(errors("(LOOP"), [(1, 5, InvalidLabel("(LOOP".into()))]);
+        assert_eq!(errors("(1LOOP)"), [(1, 7, InvalidLabel("(1LOOP)".into()))]);
+        assert_eq!(errors("X=D"), [(1, 1, InvalidDest("X".into()))]);
+        assert_eq!(errors("=D"), [(1, 1, InvalidDest("".into()))]);
+        assert_eq!(errors("D=D*A"), [(3, 3, InvalidComp("D*A".into()))]);
+        assert_eq!(errors("D;JMPS"), [(3, 4, InvalidJump("JMPS".into()))]);
+        assert_eq!(
+            errors("XY = D * A ; JJ // three errors"),
+            [
+                (1, 2, InvalidDest("XY".into())),
+                (6, 5, InvalidComp("D * A".into())),
+                (14, 2, InvalidJump("JJ".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        for line in ["@21", "@LOOP", "(LOOP)", "D=D+A", "0;JMP", "AMD=M-1;JNE"] {
+            assert_eq!(instruction(line).unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let source = "// Adds 2 and 3\n@2\nD=A\n\n@3\nD=D+A\n";
+        let (instructions, errors) = parse(source);
+        assert!(errors.is_empty());
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].0.line, 2);
+        assert_eq!(instructions[3].0.line, 6);
+
+        let (instructions, errors) = parse("@2\n\nD=X\n@40000\nD=A\n0;JUMP");
+        assert_eq!(instructions.len(), 2);
+        let lines: Vec<usize> = errors.iter().map(|e| e.span.line).collect();
+        assert_eq!(lines, [3, 4, 6]);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+pub const PREDEFINED: [(&str, u16); 23] = [
+    ("SP", 0),
+    ("LCL", 1),
+    ("ARG", 2),
+    ("THIS", 3),
+    ("THAT", 4),
+    ("R0", 0),
+    ("R1", 1),
+    ("R2", 2),
+    ("R3", 3),
+    ("R4", 4),
+    ("R5", 5),
+    ("R6", 6),
+    ("R7", 7),
+    ("R8", 8),
+    ("R9", 9),
+    ("R10", 10),
+    ("R11", 11),
+    ("R12", 12),
+    ("R13", 13),
+    ("R14", 14),
+    ("R15", 15),
+    ("SCREEN", 16384),
+    ("KBD", 24576),
+];
+
+/// Variables are allocated in RAM starting at this address,
+/// in the order they are first used.
+pub const FIRST_VARIABLE: u16 = 16;
+
+/// Predefined symbols, labels (ROM addresses) and variables (RAM addresses).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTable {
+    symbols: HashMap<String, u16>,
+    next_variable: u16,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            symbols: PREDEFINED
+                .iter()
+                .map(|(s, a)| (s.to_string(), *a))
+                .collect(),
+            next_variable: FIRST_VARIABLE,
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.contains_key(symbol)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<u16> {
+        self.symbols.get(symbol).copied()
+    }
+
+    /// Returns false if the symbol is already defined.
+    pub fn add_label(&mut self, label: &str, address: u16) -> bool {
+        if self.contains(label) {
+            return false;
+        }
+        self.symbols.insert(label.to_string(), address);
+        true
+    }
+
+    /// Address of the symbol, allocating a new variable if it is undefined.
+    pub fn resolve(&mut self, symbol: &str) -> u16 {
+        if let Some(address) = self.get(symbol) {
+            return address;
+        }
+        let address = self.next_variable;
+        self.symbols.insert(symbol.to_string(), address);
+        self.next_variable += 1;
+        address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predefined() {
+        let table = SymbolTable::new();
+        assert_eq!(table.get("SP"), Some(0));
+        assert_eq!(table.get("THAT"), Some(4));
+        assert_eq!(table.get("R15"), Some(15));
+        assert_eq!(table.get("SCREEN"), Some(0x4000));
+        assert_eq!(table.get("KBD"), Some(0x6000));
+        assert_eq!(table.get("R16"), None);
+    }
+
+    #[test]
+    fn test_labels_and_variables() {
+        let mut table = SymbolTable::new();
+        assert!(table.add_label("LOOP", 10));
+        assert!(!table.add_label("LOOP", 12));
+        assert!(!table.add_label("R1", 12));
+
+        assert_eq!(table.resolve("i"), 16);
+        assert_eq!(table.resolve("LOOP"), 10);
+        assert_eq!(table.resolve("sum"), 17);
+        assert_eq!(table.resolve("i"), 16);
+    }
+}
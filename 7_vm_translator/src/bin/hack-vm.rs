@@ -0,0 +1,88 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "usage: hack-vm <file.vm | directory> [--shared] [-o <file.asm>]
+
+A single file is translated as is. All the .vm files of a directory are
+translated into one program, Dir/Dir.asm by default, starting with the
+bootstrap code that calls Sys.init. With --shared, calls, returns and
+comparisons of the program go through routines written once, which
+programs as large as the ones compiled with the Jack OS need to fit in
+the ROM.";
+
+fn fail(path: &Path, e: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", path.display(), e);
+    process::exit(1);
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let shared = args.iter().any(|a| a == "--shared");
+    args.retain(|a| a != "--shared");
+    let (input, output) = match args.as_slice() {
+        [input] => (PathBuf::from(input), None),
+        [input, o, output] if o == "-o" => (PathBuf::from(input), Some(PathBuf::from(output))),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let (asm, output) = if input.is_dir() {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&input)
+            .unwrap_or_else(|e| fail(&input, e))
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "vm"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::new();
+        for path in paths.iter() {
+            let source = fs::read_to_string(path).unwrap_or_else(|e| fail(path, e));
+            files.push((stem(path), source));
+        }
+        let files: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(n, s)| (n.as_str(), s.as_str()))
+            .collect();
+        let translate = match shared {
+            true => vm_translator::translate_program_shared,
+            false => vm_translator::translate_program,
+        };
+        let asm = translate(&files).unwrap_or_else(|errors| {
+            for (file, e) in errors {
+                eprintln!("{}: {}", input.join(format!("{}.vm", file)).display(), e);
+            }
+            process::exit(1);
+        });
+        let output = output.unwrap_or_else(|| {
+            // the name of the directory itself, also for . and ..
+            let directory = fs::canonicalize(&input).unwrap_or_else(|e| fail(&input, e));
+            let name = directory.file_name().unwrap_or_default().to_string_lossy();
+            input.join(format!("{}.asm", name))
+        });
+        (asm, output)
+    } else {
+        let source = fs::read_to_string(&input).unwrap_or_else(|e| fail(&input, e));
+        let asm = vm_translator::translate(&stem(&input), &source).unwrap_or_else(|errors| {
+            for e in errors {
+                eprintln!("{}: {}", input.display(), e);
+            }
+            process::exit(1);
+        });
+        (asm, output.unwrap_or_else(|| input.with_extension("asm")))
+    };
+
+    if let Err(e) = fs::write(&output, asm) {
+        fail(&output, e);
+    }
+}
@@ -0,0 +1,499 @@
+use crate::parser::{Arithmetic, Command, Segment};
+
+/// RAM address of the temp segment, R5 to R12.
+pub const TEMP: u16 = 5;
+/// RAM address of the pointer segment, THIS and THAT.
+pub const POINTER: u16 = 3;
+
+/// Address the stack starts at.
+pub const STACK: u16 = 256;
+
+/// Translates VM commands into Hack assembly.
+///
+/// The stack starts at RAM[SP] and grows upwards, SP pointing to the
+/// first free word. local, argument, this and that are based at the
+/// addresses in LCL, ARG, THIS and THAT. Static variables of file Foo
+/// become the assembler variables Foo.0, Foo.1, ...
+///
+/// A call pushes the frame of the caller and jumps to the function:
+///
+///  argument 0      <- ARG
+///  ...
+///  argument n - 1
+///  return address
+///  LCL
+///  ARG
+///  THIS
+///  THAT
+///  local 0         <- LCL
+///  ...
+///  local k - 1
+///  working stack   <- SP
+///
+/// Labels of function Foo.bar are written Foo.bar$label.
+///
+/// After bootstrap_shared, calls, returns and comparisons jump to
+/// routines written once for the whole program instead of being written
+/// out in full every time, which keeps programs as large as the Jack OS
+/// within the 32K words of ROM.
+#[derive(Debug, Clone, Default)]
+pub struct CodeWriter {
+    file: String,
+    function: Option<String>,
+    labels: usize,
+    shared: bool,
+    out: String,
+}
+
+impl CodeWriter {
+    pub fn new() -> CodeWriter {
+        CodeWriter::default()
+    }
+
+    /// Name of the file being translated, without its extension.
+    pub fn set_file(&mut self, name: &str) {
+        self.file = name.to_string();
+        self.function = None;
+    }
+
+    /// Sets SP to 256 and calls Sys.init, which should never return.
+    pub fn bootstrap(&mut self) {
+        self.comment("bootstrap");
+        self.emit(&[&format!("@{}", STACK), "D=A", "@SP", "M=D"]);
+        self.comment("call Sys.init 0");
+        self.call("Sys.init", 0);
+    }
+
+    /// The bootstrap followed by the shared routines, which the commands
+    /// written after it use.
+    pub fn bootstrap_shared(&mut self) {
+        self.shared = true;
+        self.bootstrap();
+        self.routines();
+    }
+
+    /// $call expects the return address in D, 5 + the number of
+    /// arguments in R13 and the function in R14.
+    /// $eq, $gt and $lt expect the return address in D, and use R15.
+    fn routines(&mut self) {
+        self.comment("shared call");
+        self.emit(&["($call)"]);
+        self.push_frame();
+        self.emit(&[
+            "@SP", "D=M", "@LCL", "M=D", "@R13", "D=D-M", "@ARG", "M=D", "@R14", "A=M", "0;JMP",
+        ]);
+        self.comment("shared return");
+        self.emit(&["($return)"]);
+        self.ret_inline();
+        for jump in ["JEQ", "JGT", "JLT"] {
+            let name = jump[1..].to_lowercase();
+            self.comment(&format!("shared {}", name));
+            self.emit(&[&format!("(${})", name), "@R15", "M=D"]);
+            self.pop_d();
+            if jump == "JEQ" {
+                self.emit(&["A=A-1", "D=M-D"]);
+            } else {
+                let labels = ["negative", "subtract", "sign"].map(|l| format!("${}.{}", name, l));
+                self.sign(&labels);
+            }
+            self.emit(&[
+                "@SP",
+                "A=M-1",
+                "M=-1",
+                &format!("@${}.true", name),
+                &format!("D;{}", jump),
+                "@SP",
+                "A=M-1",
+                "M=0",
+                &format!("(${}.true)", name),
+                "@R15",
+                "A=M",
+                "0;JMP",
+            ]);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn emit(&mut self, lines: &[&str]) {
+        for line in lines {
+            self.out += line;
+            self.out += "\n";
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.out += &format!("// {}\n", text);
+    }
+
+    /// The function (or file, outside of functions) labels belong to.
+    fn scope(&self) -> &str {
+        self.function.as_deref().unwrap_or(&self.file)
+    }
+
+    /// A label no other command of the program uses.
+    fn unique_label(&mut self, name: &str) -> String {
+        self.labels += 1;
+        format!("{}${}.{}", self.scope(), name, self.labels)
+    }
+
+    /// The assembly label of a VM label.
+    fn label(&self, label: &str) -> String {
+        format!("{}${}", self.scope(), label)
+    }
+
+    pub fn write(&mut self, command: &Command) {
+        match command {
+            Command::Arithmetic(op) => {
+                self.comment(&format!("{:?}", op).to_lowercase());
+                self.arithmetic(*op);
+            }
+            Command::Push(segment, i) => {
+                self.comment(&format!("push {:?} {}", segment, i).to_lowercase());
+                self.push(*segment, *i);
+            }
+            Command::Pop(segment, i) => {
+                self.comment(&format!("pop {:?} {}", segment, i).to_lowercase());
+                self.pop(*segment, *i);
+            }
+            Command::Label(label) => {
+                self.comment(&format!("label {}", label));
+                let label = self.label(label);
+                self.emit(&[&format!("({})", label)]);
+            }
+            Command::Goto(label) => {
+                self.comment(&format!("goto {}", label));
+                let label = self.label(label);
+                self.emit(&[&format!("@{}", label), "0;JMP"]);
+            }
+            Command::IfGoto(label) => {
+                self.comment(&format!("if-goto {}", label));
+                let label = self.label(label);
+                self.pop_d();
+                self.emit(&[&format!("@{}", label), "D;JNE"]);
+            }
+            Command::Function(name, locals) => {
+                self.comment(&format!("function {} {}", name, locals));
+                self.function(name, *locals);
+            }
+            Command::Call(name, arguments) => {
+                self.comment(&format!("call {} {}", name, arguments));
+                self.call(name, *arguments);
+            }
+            Command::Return => {
+                self.comment("return");
+                self.ret();
+            }
+        }
+    }
+
+    /// The function's entry point, followed by its local variables
+    /// initialized to 0.
+    fn function(&mut self, name: &str, locals: u16) {
+        self.function = Some(name.to_string());
+        self.emit(&[&format!("({})", name)]);
+        for _ in 0..locals {
+            self.emit(&["@SP", "A=M", "M=0", "@SP", "M=M+1"]);
+        }
+    }
+
+    fn call(&mut self, name: &str, arguments: u16) {
+        let return_label = self.unique_label("ret");
+        if self.shared {
+            self.emit(&[
+                &format!("@{}", 5 + arguments),
+                "D=A",
+                "@R13",
+                "M=D",
+                &format!("@{}", name),
+                "D=A",
+                "@R14",
+                "M=D",
+                &format!("@{}", return_label),
+                "D=A",
+                "@$call",
+                "0;JMP",
+                &format!("({})", return_label),
+            ]);
+            return;
+        }
+        self.emit(&[&format!("@{}", return_label), "D=A"]);
+        self.push_frame();
+        // ARG = SP - 5 - arguments, LCL = SP
+        self.emit(&[
+            "@SP",
+            "D=M",
+            "@LCL",
+            "M=D",
+            &format!("@{}", 5 + arguments),
+            "D=D-A",
+            "@ARG",
+            "M=D",
+            &format!("@{}", name),
+            "0;JMP",
+            &format!("({})", return_label),
+        ]);
+    }
+
+    /// Pushes the return address in D, then LCL, ARG, THIS and THAT.
+    fn push_frame(&mut self) {
+        self.push_d();
+        for register in ["@LCL", "@ARG", "@THIS", "@THAT"] {
+            self.emit(&[register, "D=M"]);
+            self.push_d();
+        }
+    }
+
+    fn ret(&mut self) {
+        match self.shared {
+            true => self.emit(&["@$return", "0;JMP"]),
+            false => self.ret_inline(),
+        }
+    }
+
+    fn ret_inline(&mut self) {
+        // R13 = frame (LCL), R14 = return address, read before the
+        // return value overwrites it when the function has no arguments.
+        self.emit(&[
+            "@LCL", "D=M", "@R13", "M=D", "@5", "A=D-A", "D=M", "@R14", "M=D",
+        ]);
+        // *ARG = pop(), SP = ARG + 1
+        self.pop_d();
+        self.emit(&["@ARG", "A=M", "M=D", "@ARG", "D=M+1", "@SP", "M=D"]);
+        for register in ["@THAT", "@THIS", "@ARG", "@LCL"] {
+            self.emit(&["@R13", "AM=M-1", "D=M", register, "M=D"]);
+        }
+        self.emit(&["@R14", "A=M", "0;JMP"]);
+    }
+
+    /// Pushes D onto the stack.
+    fn push_d(&mut self) {
+        self.emit(&["@SP", "A=M", "M=D", "@SP", "M=M+1"]);
+    }
+
+    /// Pops the stack into D.
+    fn pop_d(&mut self) {
+        self.emit(&["@SP", "AM=M-1", "D=M"]);
+    }
+
+    /// The register holding the base address of a segment.
+    fn base(segment: Segment) -> &'static str {
+        match segment {
+            Segment::Local => "LCL",
+            Segment::Argument => "ARG",
+            Segment::This => "THIS",
+            Segment::That => "THAT",
+            _ => unreachable!("{:?} has a fixed address", segment),
+        }
+    }
+
+    /// The symbol of a segment entry that has a fixed address.
+    fn fixed(&self, segment: Segment, i: u16) -> String {
+        match segment {
+            Segment::Pointer => format!("@{}", POINTER + i),
+            Segment::Temp => format!("@{}", TEMP + i),
+            Segment::Static => format!("@{}.{}", self.file, i),
+            _ => unreachable!("{:?} is based on a pointer", segment),
+        }
+    }
+
+    fn push(&mut self, segment: Segment, i: u16) {
+        match segment {
+            Segment::Constant => {
+                self.emit(&[&format!("@{}", i), "D=A"]);
+            }
+            Segment::Local | Segment::Argument | Segment::This | Segment::That => {
+                let base = format!("@{}", Self::base(segment));
+                self.emit(&[&format!("@{}", i), "D=A", &base, "A=D+M", "D=M"]);
+            }
+            Segment::Pointer | Segment::Temp | Segment::Static => {
+                let address = self.fixed(segment, i);
+                self.emit(&[&address, "D=M"]);
+            }
+        }
+        self.push_d();
+    }
+
+    fn pop(&mut self, segment: Segment, i: u16) {
+        match segment {
+            Segment::Local | Segment::Argument | Segment::This | Segment::That => {
+                let base = format!("@{}", Self::base(segment));
+                self.emit(&[&format!("@{}", i), "D=A", &base, "D=D+M", "@R13", "M=D"]);
+                self.pop_d();
+                self.emit(&["@R13", "A=M", "M=D"]);
+            }
+            Segment::Pointer | Segment::Temp | Segment::Static => {
+                let address = self.fixed(segment, i);
+                self.pop_d();
+                self.emit(&[&address, "M=D"]);
+            }
+            Segment::Constant => unreachable!("the parser rejects pop constant"),
+        }
+    }
+
+    fn arithmetic(&mut self, op: Arithmetic) {
+        match op {
+            Arithmetic::Neg => self.emit(&["@SP", "A=M-1", "M=-M"]),
+            Arithmetic::Not => self.emit(&["@SP", "A=M-1", "M=!M"]),
+            Arithmetic::Add => self.binary("M=D+M"),
+            Arithmetic::Sub => self.binary("M=M-D"),
+            Arithmetic::And => self.binary("M=D&M"),
+            Arithmetic::Or => self.binary("M=D|M"),
+            Arithmetic::Eq => self.compare("JEQ"),
+            Arithmetic::Gt => self.compare("JGT"),
+            Arithmetic::Lt => self.compare("JLT"),
+        }
+    }
+
+    /// Pops y into D and leaves A pointing at x, which is replaced by
+    /// the result.
+    fn binary(&mut self, operation: &str) {
+        self.pop_d();
+        self.emit(&["A=A-1", operation]);
+    }
+
+    /// With y popped into D and x on top of the stack, leaves a number
+    /// with the sign of x - y in D. x - y overflows when x and y have
+    /// opposite signs, like 32767 - -1, so then x is the larger one if
+    /// it is the positive one. Uses R13 and the three labels given.
+    fn sign(&mut self, [negative, subtract, sign]: &[String; 3]) {
+        self.emit(&[
+            "@R13",
+            "M=D",
+            "@SP",
+            "A=M-1",
+            "D=M",
+            &format!("@{}", negative),
+            "D;JLT",
+            // x >= 0, x > y if y < 0
+            "@R13",
+            "D=M",
+            &format!("@{}", subtract),
+            "D;JGE",
+            "D=1",
+            &format!("@{}", sign),
+            "0;JMP",
+            // x < 0, x < y if y >= 0
+            &format!("({})", negative),
+            "@R13",
+            "D=M",
+            &format!("@{}", subtract),
+            "D;JLT",
+            "D=-1",
+            &format!("@{}", sign),
+            "0;JMP",
+            // same signs, x - y can't overflow
+            &format!("({})", subtract),
+            "@R13",
+            "D=M",
+            "@SP",
+            "A=M-1",
+            "D=M-D",
+            &format!("({})", sign),
+        ]);
+    }
+
+    /// true is -1, false is 0.
+    fn compare(&mut self, jump: &str) {
+        if self.shared {
+            let return_label = self.unique_label("ret");
+            let name = jump[1..].to_lowercase();
+            self.emit(&[
+                &format!("@{}", return_label),
+                "D=A",
+                &format!("@${}", name),
+                "0;JMP",
+                &format!("({})", return_label),
+            ]);
+            return;
+        }
+        let true_label = self.unique_label("true");
+        let end_label = self.unique_label("end");
+        self.pop_d();
+        if jump == "JEQ" {
+            self.emit(&["A=A-1", "D=M-D"]);
+        } else {
+            let labels = ["negative", "subtract", "sign"].map(|name| self.unique_label(name));
+            self.sign(&labels);
+        }
+        self.emit(&[
+            &format!("@{}", true_label),
+            &format!("D;{}", jump),
+            "@SP",
+            "A=M-1",
+            "M=0",
+            &format!("@{}", end_label),
+            "0;JMP",
+            &format!("({})", true_label),
+            "@SP",
+            "A=M-1",
+            "M=-1",
+            &format!("({})", end_label),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_constant() {
+        let mut writer = CodeWriter::new();
+        writer.write(&Command::Push(Segment::Constant, 7));
+        assert_eq!(
+            writer.finish(),
+            "// push constant 7\n@7\nD=A\n@SP\nA=M\nM=D\n@SP\nM=M+1\n"
+        );
+    }
+
+    #[test]
+    fn test_static_names() {
+        let mut writer = CodeWriter::new();
+        writer.set_file("Foo");
+        writer.write(&Command::Pop(Segment::Static, 3));
+        assert!(writer.finish().contains("@Foo.3\nM=D\n"));
+    }
+
+    #[test]
+    fn test_shared_routines() {
+        let mut writer = CodeWriter::new();
+        writer.bootstrap();
+        writer.write(&Command::Arithmetic(Arithmetic::Lt));
+        writer.write(&Command::Return);
+        let asm = writer.finish();
+        assert!(!asm.contains("($call)"));
+        assert!(!asm.contains("@$lt"));
+        assert!(!asm.contains("@$return"));
+
+        let mut writer = CodeWriter::new();
+        writer.bootstrap_shared();
+        writer.set_file("Foo");
+        writer.write(&Command::Function("Foo.f".to_string(), 0));
+        writer.write(&Command::Arithmetic(Arithmetic::Lt));
+        writer.write(&Command::Call("Foo.g".to_string(), 2));
+        writer.write(&Command::Return);
+        let asm = writer.finish();
+        for routine in ["($call)", "($return)", "($eq)", "($gt)", "($lt)"] {
+            assert_eq!(asm.matches(routine).count(), 1, "{}", routine);
+        }
+        let f = &asm[asm.find("(Foo.f)").unwrap()..];
+        assert!(f.contains("@$lt\n0;JMP\n"));
+        assert!(f.contains("@7\nD=A\n@R13\nM=D\n@Foo.g\nD=A\n@R14\nM=D\n"));
+        assert!(f.ends_with("@$return\n0;JMP\n"));
+    }
+
+    #[test]
+    fn test_unique_labels() {
+        let mut writer = CodeWriter::new();
+        writer.set_file("Foo");
+        writer.write(&Command::Arithmetic(Arithmetic::Eq));
+        writer.write(&Command::Arithmetic(Arithmetic::Eq));
+        let asm = writer.finish();
+        for label in ["(Foo$true.1)", "(Foo$end.2)", "(Foo$true.3)", "(Foo$end.4)"] {
+            assert_eq!(asm.matches(label).count(), 1, "{}", label);
+        }
+    }
+}
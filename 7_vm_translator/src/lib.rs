@@ -0,0 +1,514 @@
+pub mod code_writer;
+pub mod parser;
+
+use code_writer::CodeWriter;
+use parser::Error;
+
+/// Translates the VM code of one file into Hack assembly.
+/// `file` is the name of the file without its extension,
+/// used to name its static variables.
+pub fn translate(file: &str, source: &str) -> Result<String, Vec<Error>> {
+    let commands = parser::parse(source)?;
+    let mut writer = CodeWriter::new();
+    writer.set_file(file);
+    for (_, command) in commands.iter() {
+        writer.write(command);
+    }
+    Ok(writer.finish())
+}
+
+/// Translates every file of a program into a single assembly file,
+/// starting with the bootstrap code that calls Sys.init.
+/// Files are (name without extension, VM code) pairs.
+pub fn translate_program(files: &[(&str, &str)]) -> Result<String, Vec<(String, Error)>> {
+    program(files, false)
+}
+
+/// Like translate_program, with calls, returns and comparisons going
+/// through routines shared by the whole program, for programs too large
+/// for the ROM otherwise, like the ones linked with the Jack OS.
+pub fn translate_program_shared(files: &[(&str, &str)]) -> Result<String, Vec<(String, Error)>> {
+    program(files, true)
+}
+
+fn program(files: &[(&str, &str)], shared: bool) -> Result<String, Vec<(String, Error)>> {
+    let mut writer = CodeWriter::new();
+    let mut errors = Vec::new();
+    match shared {
+        true => writer.bootstrap_shared(),
+        false => writer.bootstrap(),
+    }
+    for (file, source) in files {
+        match parser::parse(source) {
+            Ok(commands) => {
+                writer.set_file(file);
+                for (_, command) in commands.iter() {
+                    writer.write(command);
+                }
+            }
+            Err(e) => errors.extend(e.into_iter().map(|e| (file.to_string(), e))),
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(writer.finish()),
+        false => Err(errors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use computer::computer::Computer;
+    use computer::rom::ROM32K;
+
+    fn computer(asm: &str, ram: &[(u16, u16)]) -> (Computer, usize) {
+        let words = assembler::assemble(asm).unwrap();
+        let rom = ROM32K::from_hack(&assembler::to_hack(&words)).unwrap();
+        let mut computer = Computer::new(rom);
+        for &(address, value) in ram {
+            computer.poke(address, value);
+        }
+        (computer, words.len())
+    }
+
+    /// Runs the translated code on the Hack computer until it has gone
+    /// through every instruction once, after setting up the RAM.
+    fn run(source: &str, ram: &[(u16, u16)]) -> Computer {
+        let (mut computer, len) = computer(&translate("Test", source).unwrap(), ram);
+        computer.step(len);
+        computer
+    }
+
+    /// Runs a program that loops, for the given number of cycles.
+    fn run_for(asm: &str, ram: &[(u16, u16)], cycles: usize) -> Computer {
+        let (mut computer, _) = computer(asm, ram);
+        computer.step(cycles);
+        computer
+    }
+
+    #[test]
+    fn test_simple_add() {
+        let computer = run("push constant 7\npush constant 8\nadd\n", &[(0, 256)]);
+        assert_eq!(computer.peek(0), 257);
+        assert_eq!(computer.peek(256), 15);
+    }
+
+    #[test]
+    fn test_stack() {
+        let source = "
+            push constant 17
+            push constant 17
+            eq
+            push constant 17
+            push constant 16
+            eq
+            push constant 892
+            push constant 891
+            lt
+            push constant 891
+            push constant 892
+            lt
+            push constant 32767
+            push constant 32766
+            gt
+            push constant 57
+            push constant 31
+            push constant 53
+            add
+            push constant 112
+            sub
+            neg
+            and
+            push constant 82
+            or
+            not
+        ";
+        let computer = run(source, &[(0, 256)]);
+        assert_eq!(computer.peek(0), 262);
+        let stack: Vec<u16> = (256..262).map(|a| computer.peek(a)).collect();
+        assert_eq!(stack, [0xFFFF, 0, 0, 0xFFFF, 0xFFFF, (-91i16) as u16]);
+    }
+
+    #[test]
+    fn test_compare_overflow() {
+        // x - y overflows for each of these
+        let source = "
+            push constant 32767
+            push constant 1
+            neg
+            gt
+            push constant 32767
+            neg
+            push constant 1
+            sub
+            push constant 1
+            lt
+            push constant 2
+            neg
+            push constant 32767
+            lt
+            push constant 1
+            neg
+            push constant 32767
+            gt
+            push constant 32767
+            push constant 32767
+            neg
+            push constant 1
+            sub
+            lt
+        ";
+        let computer = run(source, &[(0, 256)]);
+        assert_eq!(computer.peek(0), 261);
+        let stack: Vec<u16> = (256..261).map(|a| computer.peek(a)).collect();
+        assert_eq!(stack, [0xFFFF, 0xFFFF, 0xFFFF, 0, 0]);
+    }
+
+    #[test]
+    fn test_segments() {
+        let source = "
+            push constant 10
+            pop local 0
+            push constant 21
+            push constant 22
+            pop argument 2
+            pop argument 1
+            push constant 36
+            pop this 6
+            push constant 42
+            push constant 45
+            pop that 5
+            pop that 2
+            push constant 510
+            pop temp 6
+            push local 0
+            push that 5
+            add
+            push argument 1
+            sub
+            push this 6
+            push this 6
+            add
+            sub
+            push temp 6
+            add
+        ";
+        let ram = [(0, 256), (1, 300), (2, 400), (3, 3000), (4, 3010)];
+        let computer = run(source, &ram);
+        assert_eq!(computer.peek(256), 472);
+        assert_eq!(computer.peek(300), 10);
+        assert_eq!(computer.peek(401), 21);
+        assert_eq!(computer.peek(402), 22);
+        assert_eq!(computer.peek(3006), 36);
+        assert_eq!(computer.peek(3012), 42);
+        assert_eq!(computer.peek(3015), 45);
+        assert_eq!(computer.peek(11), 510);
+    }
+
+    #[test]
+    fn test_pointer_and_static() {
+        let source = "
+            push constant 3030
+            pop pointer 0
+            push constant 3040
+            pop pointer 1
+            push constant 32
+            pop this 2
+            push constant 46
+            pop that 6
+            push constant 111
+            pop static 8
+            push constant 333
+            pop static 3
+            push pointer 0
+            push pointer 1
+            add
+            push this 2
+            sub
+            push that 6
+            add
+            push static 3
+            push static 8
+            sub
+        ";
+        let computer = run(source, &[(0, 256)]);
+        assert_eq!(computer.peek(3), 3030);
+        assert_eq!(computer.peek(4), 3040);
+        assert_eq!(computer.peek(3032), 32);
+        assert_eq!(computer.peek(3046), 46);
+        assert_eq!(computer.peek(256), 6084);
+        assert_eq!(computer.peek(257), 222);
+        // statics are allocated in the order the assembler meets them
+        assert_eq!(computer.peek(16), 111);
+        assert_eq!(computer.peek(17), 333);
+    }
+
+    #[test]
+    fn test_basic_loop() {
+        // sums 1..=argument 0 into local 0
+        let source = "
+            push constant 0
+            pop local 0
+            label LOOP
+            push argument 0
+            push local 0
+            add
+            pop local 0
+            push argument 0
+            push constant 1
+            sub
+            pop argument 0
+            push argument 0
+            if-goto LOOP
+            push local 0
+        ";
+        let asm = translate("BasicLoop", source).unwrap();
+        let ram = [(0, 256), (1, 300), (2, 400), (400, 3)];
+        let computer = run_for(&asm, &ram, 600);
+        assert_eq!(computer.peek(0), 257);
+        assert_eq!(computer.peek(256), 6);
+    }
+
+    #[test]
+    fn test_fibonacci_series() {
+        // writes the first argument 0 Fibonacci numbers at argument 1
+        let source = "
+            push argument 1
+            pop pointer 1
+            push constant 0
+            pop that 0
+            push constant 1
+            pop that 1
+            push argument 0
+            push constant 2
+            sub
+            pop argument 0
+            label LOOP
+            push argument 0
+            if-goto COMPUTE_ELEMENT
+            goto END
+            label COMPUTE_ELEMENT
+            push that 0
+            push that 1
+            add
+            pop that 2
+            push pointer 1
+            push constant 1
+            add
+            pop pointer 1
+            push argument 0
+            push constant 1
+            sub
+            pop argument 0
+            goto LOOP
+            label END
+            goto END
+        ";
+        let asm = translate("FibonacciSeries", source).unwrap();
+        let ram = [(0, 256), (1, 300), (2, 400), (400, 6), (401, 3000)];
+        let computer = run_for(&asm, &ram, 1100);
+        let series: Vec<u16> = (3000..3006).map(|a| computer.peek(a)).collect();
+        assert_eq!(series, [0, 1, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_simple_function() {
+        let source = "
+            function SimpleFunction.test 2
+            push local 0
+            push local 1
+            add
+            not
+            push argument 0
+            add
+            push argument 1
+            sub
+            return
+        ";
+        // a caller frame with 2 arguments, as call would have left it
+        let ram = [
+            (0, 317),
+            (1, 317),
+            (2, 310),
+            (3, 3000),
+            (4, 4000),
+            (310, 1234),
+            (311, 37),
+            (312, 1000),
+            (313, 305),
+            (314, 300),
+            (315, 3010),
+            (316, 4010),
+        ];
+        let asm = translate("SimpleFunction", source).unwrap();
+        let computer = run_for(&asm, &ram, 200);
+        assert_eq!(computer.peek(0), 311);
+        assert_eq!(computer.peek(1), 305);
+        assert_eq!(computer.peek(2), 300);
+        assert_eq!(computer.peek(3), 3010);
+        assert_eq!(computer.peek(4), 4010);
+        assert_eq!(computer.peek(310), 1196);
+    }
+
+    #[test]
+    fn test_recursive_calls() {
+        let main = "
+            function Main.fibonacci 0
+            push argument 0
+            push constant 2
+            lt
+            if-goto N_LT_2
+            goto N_GE_2
+            label N_LT_2
+            push argument 0
+            return
+            label N_GE_2
+            push argument 0
+            push constant 2
+            sub
+            call Main.fibonacci 1
+            push argument 0
+            push constant 1
+            sub
+            call Main.fibonacci 1
+            add
+            return
+        ";
+        let sys = "
+            function Sys.init 0
+            push constant 4
+            call Main.fibonacci 1
+            label END
+            goto END
+        ";
+        for translate in [translate_program, translate_program_shared] {
+            let asm = translate(&[("Main", main), ("Sys", sys)]).unwrap();
+            let computer = run_for(&asm, &[], 2500);
+            // the bootstrap's call to Sys.init leaves a frame of 5 words
+            assert_eq!(computer.peek(0), 262);
+            assert_eq!(computer.peek(261), 3);
+        }
+    }
+
+    #[test]
+    fn test_shared_compare_overflow() {
+        let sys = "
+            function Sys.init 0
+            push constant 32767
+            push constant 1
+            neg
+            gt
+            push constant 32767
+            neg
+            push constant 1
+            sub
+            push constant 1
+            lt
+            push constant 2
+            neg
+            push constant 32767
+            lt
+            push constant 32767
+            push constant 2
+            neg
+            lt
+            label END
+            goto END
+        ";
+        let asm = translate_program_shared(&[("Sys", sys)]).unwrap();
+        let computer = run_for(&asm, &[], 400);
+        assert_eq!(computer.peek(0), 265);
+        let stack: Vec<u16> = (261..265).map(|a| computer.peek(a)).collect();
+        assert_eq!(stack, [0xFFFF, 0xFFFF, 0xFFFF, 0]);
+    }
+
+    #[test]
+    fn test_statics_per_file() {
+        let class1 = "
+            function Class1.set 0
+            push argument 0
+            pop static 0
+            push argument 1
+            pop static 1
+            push constant 0
+            return
+            function Class1.get 0
+            push static 0
+            push static 1
+            sub
+            return
+        ";
+        let class2 = class1.replace("Class1", "Class2");
+        let sys = "
+            function Sys.init 0
+            push constant 6
+            push constant 8
+            call Class1.set 2
+            pop temp 0
+            push constant 23
+            push constant 15
+            call Class2.set 2
+            pop temp 0
+            call Class1.get 0
+            call Class2.get 0
+            label END
+            goto END
+        ";
+        let files = [("Class1", class1), ("Class2", &class2), ("Sys", sys)];
+        for translate in [translate_program, translate_program_shared] {
+            let asm = translate(&files).unwrap();
+            let computer = run_for(&asm, &[], 1200);
+            assert_eq!(computer.peek(0), 263);
+            assert_eq!(computer.peek(261), (-2i16) as u16);
+            assert_eq!(computer.peek(262), 8);
+        }
+    }
+
+    #[test]
+    fn test_shared_routines() {
+        let sys = "
+            function Sys.init 0
+            push constant 1
+            push constant 2
+            lt
+            call Sys.halt 1
+            function Sys.halt 0
+            label END
+            goto END
+        ";
+        let inline = translate_program(&[("Sys", sys)]).unwrap();
+        let shared = translate_program_shared(&[("Sys", sys)]).unwrap();
+        for routine in ["($call)", "($return)", "($lt)"] {
+            assert!(!inline.contains(routine), "{}", routine);
+            assert!(shared.contains(routine), "{}", routine);
+        }
+        for asm in [inline, shared] {
+            let computer = run_for(&asm, &[], 400);
+            // Sys.halt's frame is above its argument, true
+            assert_eq!(computer.peek(261), 0xFFFF);
+            assert_eq!(computer.peek(0), 267);
+        }
+    }
+
+    #[test]
+    fn test_program_errors() {
+        let errors =
+            translate_program(&[("A", "push x 1"), ("B", "goto"), ("C", "add")]).unwrap_err();
+        let files: Vec<&str> = errors.iter().map(|(f, _)| f.as_str()).collect();
+        assert_eq!(files, ["A", "B"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors =
+            translate("Test", "push constant 1\npop constant 1\npush that x\n").unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 2: can't pop to the constant segment",
+                "line 3: 'x' is not a valid index"
+            ]
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    Constant,
+    Local,
+    Argument,
+    This,
+    That,
+    Pointer,
+    Temp,
+    Static,
+}
+
+impl Segment {
+    pub fn parse(name: &str) -> Option<Segment> {
+        Some(match name {
+            "constant" => Segment::Constant,
+            "local" => Segment::Local,
+            "argument" => Segment::Argument,
+            "this" => Segment::This,
+            "that" => Segment::That,
+            "pointer" => Segment::Pointer,
+            "temp" => Segment::Temp,
+            "static" => Segment::Static,
+            _ => return None,
+        })
+    }
+
+    /// Largest valid index, pointer is THIS and THAT, temp is R5 to R12.
+    pub fn max_index(self) -> u16 {
+        match self {
+            Segment::Pointer => 1,
+            Segment::Temp => 7,
+            _ => 32767,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    Add,
+    Sub,
+    Neg,
+    Eq,
+    Gt,
+    Lt,
+    And,
+    Or,
+    Not,
+}
+
+impl Arithmetic {
+    pub fn parse(name: &str) -> Option<Arithmetic> {
+        Some(match name {
+            "add" => Arithmetic::Add,
+            "sub" => Arithmetic::Sub,
+            "neg" => Arithmetic::Neg,
+            "eq" => Arithmetic::Eq,
+            "gt" => Arithmetic::Gt,
+            "lt" => Arithmetic::Lt,
+            "and" => Arithmetic::And,
+            "or" => Arithmetic::Or,
+            "not" => Arithmetic::Not,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Arithmetic(Arithmetic),
+    Push(Segment, u16),
+    Pop(Segment, u16),
+    Label(String),
+    Goto(String),
+    IfGoto(String),
+    /// function name, number of local variables
+    Function(String, u16),
+    /// function name, number of arguments
+    Call(String, u16),
+    Return,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnknownCommand(String),
+    UnknownSegment(String),
+    InvalidIndex(String),
+    InvalidName(String),
+    InvalidCount(String),
+    IndexOutOfRange(Segment, u16),
+    PopConstant,
+    WrongArguments { command: String, expected: usize },
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownCommand(c) => write!(f, "unknown command '{}'", c),
+            ErrorKind::UnknownSegment(s) => write!(f, "unknown segment '{}'", s),
+            ErrorKind::InvalidIndex(i) => write!(f, "'{}' is not a valid index", i),
+            ErrorKind::InvalidName(n) => write!(f, "'{}' is not a valid name", n),
+            ErrorKind::InvalidCount(n) => write!(f, "'{}' is not a valid count", n),
+            ErrorKind::IndexOutOfRange(segment, i) => write!(
+                f,
+                "index {} is out of range, the largest {:?} index is {}",
+                i,
+                segment,
+                segment.max_index()
+            ),
+            ErrorKind::PopConstant => write!(f, "can't pop to the constant segment"),
+            ErrorKind::WrongArguments { command, expected } => {
+                write!(f, "'{}' takes {} arguments", command, expected)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn parse_index(segment: Segment, index: &str) -> Result<u16, ErrorKind> {
+    let i: u16 = index
+        .parse()
+        .map_err(|_| ErrorKind::InvalidIndex(index.to_string()))?;
+    match i <= segment.max_index() {
+        true => Ok(i),
+        false => Err(ErrorKind::IndexOutOfRange(segment, i)),
+    }
+}
+
+/// Letters, digits, '_', '.', '$' and ':', not starting with a digit,
+/// the same as assembler symbols.
+fn parse_name(name: &str) -> Result<String, ErrorKind> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || "_.$:".contains(c);
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() && name.chars().all(valid) => Ok(name.to_string()),
+        _ => Err(ErrorKind::InvalidName(name.to_string())),
+    }
+}
+
+fn parse_count(count: &str) -> Result<u16, ErrorKind> {
+    count
+        .parse()
+        .map_err(|_| ErrorKind::InvalidCount(count.to_string()))
+}
+
+fn arguments(words: &[&str], expected: usize) -> Result<(), ErrorKind> {
+    match words.len() == expected + 1 {
+        true => Ok(()),
+        false => Err(ErrorKind::WrongArguments {
+            command: words[0].to_string(),
+            expected,
+        }),
+    }
+}
+
+/// Parses one line of VM code, None for lines with only whitespace
+/// and comments.
+pub fn parse_line(line: &str) -> Result<Option<Command>, ErrorKind> {
+    let line = match line.find("//") {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some(&command) = words.first() else {
+        return Ok(None);
+    };
+
+    if let Some(op) = Arithmetic::parse(command) {
+        arguments(&words, 0)?;
+        return Ok(Some(Command::Arithmetic(op)));
+    }
+    match command {
+        "push" | "pop" => {
+            arguments(&words, 2)?;
+            let segment = Segment::parse(words[1])
+                .ok_or_else(|| ErrorKind::UnknownSegment(words[1].to_string()))?;
+            let index = parse_index(segment, words[2])?;
+            if command == "push" {
+                Ok(Some(Command::Push(segment, index)))
+            } else if segment == Segment::Constant {
+                Err(ErrorKind::PopConstant)
+            } else {
+                Ok(Some(Command::Pop(segment, index)))
+            }
+        }
+        "label" | "goto" | "if-goto" => {
+            arguments(&words, 1)?;
+            let label = parse_name(words[1])?;
+            Ok(Some(match command {
+                "label" => Command::Label(label),
+                "goto" => Command::Goto(label),
+                _ => Command::IfGoto(label),
+            }))
+        }
+        "function" | "call" => {
+            arguments(&words, 2)?;
+            let name = parse_name(words[1])?;
+            let count = parse_count(words[2])?;
+            Ok(Some(match command {
+                "function" => Command::Function(name, count),
+                _ => Command::Call(name, count),
+            }))
+        }
+        "return" => {
+            arguments(&words, 0)?;
+            Ok(Some(Command::Return))
+        }
+        _ => Err(ErrorKind::UnknownCommand(command.to_string())),
+    }
+}
+
+/// Every command of the source along with its line number,
+/// or every error found in it.
+pub fn parse(source: &str) -> Result<Vec<(usize, Command)>, Vec<Error>> {
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+    for (n, line) in source.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(command)) => commands.push((n + 1, command)),
+            Ok(None) => {}
+            Err(kind) => errors.push(Error { line: n + 1, kind }),
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(commands),
+        false => Err(errors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("  // comment"), Ok(None));
+        assert_eq!(
+            parse_line("push constant 7 // seven"),
+            Ok(Some(Command::Push(Segment::Constant, 7)))
+        );
+        assert_eq!(
+            parse_line("pop\tlocal  2"),
+            Ok(Some(Command::Pop(Segment::Local, 2)))
+        );
+        assert_eq!(
+            parse_line("pop pointer 1"),
+            Ok(Some(Command::Pop(Segment::Pointer, 1)))
+        );
+        assert_eq!(
+            parse_line("lt"),
+            Ok(Some(Command::Arithmetic(Arithmetic::Lt)))
+        );
+    }
+
+    #[test]
+    fn test_parse_flow_and_functions() {
+        assert_eq!(
+            parse_line("label LOOP_START"),
+            Ok(Some(Command::Label("LOOP_START".into())))
+        );
+        assert_eq!(
+            parse_line("goto END"),
+            Ok(Some(Command::Goto("END".into())))
+        );
+        assert_eq!(
+            parse_line("if-goto Main.loop$1"),
+            Ok(Some(Command::IfGoto("Main.loop$1".into())))
+        );
+        assert_eq!(
+            parse_line("function Main.fibonacci 2"),
+            Ok(Some(Command::Function("Main.fibonacci".into(), 2)))
+        );
+        assert_eq!(
+            parse_line("call Math.multiply 2 // x * y"),
+            Ok(Some(Command::Call("Math.multiply".into(), 2)))
+        );
+        assert_eq!(parse_line("return"), Ok(Some(Command::Return)));
+
+        use ErrorKind::*;
+        assert_eq!(parse_line("label 1ABC"), Err(InvalidName("1ABC".into())));
+        assert_eq!(parse_line("goto a-b"), Err(InvalidName("a-b".into())));
+        assert_eq!(parse_line("call Foo.bar n"), Err(InvalidCount("n".into())));
+        assert_eq!(
+            parse_line("return 1"),
+            Err(WrongArguments {
+                command: "return".into(),
+                expected: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        use ErrorKind::*;
+        assert_eq!(parse_line("jump 3"), Err(UnknownCommand("jump".into())));
+        assert_eq!(
+            parse_line("push stack 3"),
+            Err(UnknownSegment("stack".into()))
+        );
+        assert_eq!(parse_line("push local x"), Err(InvalidIndex("x".into())));
+        assert_eq!(parse_line("push local -1"), Err(InvalidIndex("-1".into())));
+        assert_eq!(
+            parse_line("pop pointer 2"),
+            Err(IndexOutOfRange(Segment::Pointer, 2))
+        );
+        assert_eq!(
+            parse_line("push temp 8"),
+            Err(IndexOutOfRange(Segment::Temp, 8))
+        );
+        assert_eq!(
+            parse_line("push constant 32768"),
+            Err(IndexOutOfRange(Segment::Constant, 32768))
+        );
+        assert_eq!(parse_line("pop constant 1"), Err(PopConstant));
+        assert_eq!(
+            parse_line("add 1"),
+            Err(WrongArguments {
+                command: "add".into(),
+                expected: 0
+            })
+        );
+        assert_eq!(
+            parse_line("push local"),
+            Err(WrongArguments {
+                command: "push".into(),
+                expected: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let commands = parse("push constant 7\n\npush constant 8\nadd\n").unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                (1, Command::Push(Segment::Constant, 7)),
+                (3, Command::Push(Segment::Constant, 8)),
+                (4, Command::Arithmetic(Arithmetic::Add)),
+            ]
+        );
+        let errors = parse("push constant 7\npop constant 1\nfoo\n").unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].to_string(), "line 3: unknown command 'foo'");
+    }
+}
@@ -0,0 +1,23 @@
+// The built-in ALU, there is no ALU.hdl here.
+
+load ALU.hdl,
+output-file ALU.out,
+compare-to ALU.cmp,
+output-list x%B1.16.1 y%B1.16.1 zx%D1.1.1 nx%D1.1.1 zy%D1.1.1
+            ny%D1.1.1 f%D1.1.1 no%D1.1.1 out%D1.16.1 zr%D1.1.1 ng%D1.1.1;
+
+set x %D17,
+set y %D3;
+
+// x + y
+set zx 0, set nx 0, set zy 0, set ny 0, set f 1, set no 0, eval, output;
+// x - y
+set zx 0, set nx 1, set zy 0, set ny 0, set f 1, set no 1, eval, output;
+// y - x
+set zx 0, set nx 0, set zy 0, set ny 1, set f 1, set no 1, eval, output;
+// x & y
+set zx 0, set nx 0, set zy 0, set ny 0, set f 0, set no 0, eval, output;
+// -1
+set zx 1, set nx 1, set zy 1, set ny 0, set f 1, set no 0, eval, output;
+// 0
+set zx 1, set nx 0, set zy 1, set ny 0, set f 1, set no 0, eval, output;
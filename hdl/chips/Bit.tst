@@ -0,0 +1,29 @@
+load Bit.hdl,
+output-file Bit.out,
+compare-to Bit.cmp,
+output-list time%S1.4.1 in%B3.1.3 load%B3.1.3 out%B3.1.3;
+
+set in 1,
+set load 1,
+tick,
+output;
+
+tock,
+output;
+
+set in 0,
+set load 0,
+tick,
+output;
+
+tock,
+output;
+
+set load 1,
+repeat 2 {
+    tick,
+    output;
+
+    tock,
+    output;
+}
@@ -0,0 +1,59 @@
+use crate::error::Position;
+
+/// `CHIP name { IN inputs; OUT outputs; body }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipDef {
+    pub position: Position,
+    pub name: String,
+    pub inputs: Vec<PinDec>,
+    pub outputs: Vec<PinDec>,
+    pub body: Body,
+}
+
+/// `name` or `name[width]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinDec {
+    pub position: Position,
+    pub name: String,
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Body {
+    /// `PARTS: part*`
+    Parts(Vec<Part>),
+    /// `BUILTIN name; CLOCKED pins;`, a chip implemented in Rust.
+    /// The CLOCKED pins are taken from the built-in itself.
+    Builtin { position: Position, name: String },
+}
+
+/// `Chip(connection, ...);`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub position: Position,
+    pub chip: String,
+    pub connections: Vec<Connection>,
+}
+
+/// `inner=outer`, inner being a pin of the part and outer a pin of the
+/// chip, an internal pin or a constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    pub inner: PinRef,
+    pub outer: Outer,
+}
+
+/// `name`, `name[i]` or `name[i..j]`, bits i to j included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinRef {
+    pub position: Position,
+    pub name: String,
+    pub range: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outer {
+    Pin(PinRef),
+    /// `true` or `false`, on every bit of the inner pin.
+    Constant(bool),
+}
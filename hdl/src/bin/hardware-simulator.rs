@@ -0,0 +1,52 @@
+use hdl::runner::Runner;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "usage: hardware-simulator <file.tst>...
+
+Runs each test script with the .hdl chips of its directory, or the
+built-in ones, writes its output file next to it and compares it with
+its compare file.";
+
+fn fail(path: &Path, e: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", path.display(), e);
+    process::exit(1);
+}
+
+fn main() {
+    let scripts: Vec<PathBuf> = env::args().skip(1).map(PathBuf::from).collect();
+    if scripts.is_empty() {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let mut failed = false;
+    for path in scripts.iter() {
+        let source = fs::read_to_string(path).unwrap_or_else(|e| fail(path, e));
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut runner = Runner::from_dir(dir).unwrap_or_else(|e| fail(dir, e));
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        let result = runner.run(&file, &source);
+
+        if let Some(output) = &runner.output_file {
+            let output = dir.join(output);
+            if let Err(e) = fs::write(&output, &runner.output) {
+                fail(&output, e);
+            }
+        }
+        match result {
+            Ok(()) => println!("{}: end of script", path.display()),
+            Err(errors) => {
+                for (file, e) in errors {
+                    eprintln!("{}:{}", dir.join(file).display(), e);
+                }
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,439 @@
+use crate::chip::{Chip, Interface};
+use ::alu::{adders, alu};
+use logic_gates::Arr16;
+use logic_gates::basic_gates as bg;
+use logic_gates::convert::{bits, value};
+use logic_gates::multibit_basic_gates as mbg;
+use logic_gates::multiway_basic_gates as mwg;
+use logic_gates::nand::nand;
+use memory::bit::Bit;
+use memory::clock::Clocked;
+use memory::dff::DFF;
+use memory::pc::PC;
+use memory::ram::{RAM4K, RAM8, RAM16K, RAM64, RAM512};
+use memory::register::Register;
+
+/// The chips of the course that are implemented in Rust, by their HDL name.
+pub const BUILTINS: [&str; 34] = [
+    "Nand",
+    "Not",
+    "And",
+    "Or",
+    "Xor",
+    "Mux",
+    "DMux",
+    "Not16",
+    "And16",
+    "Or16",
+    "Xor16",
+    "Mux16",
+    "Or8Way",
+    "And8Way",
+    "Mux4Way16",
+    "Mux8Way16",
+    "DMux4Way",
+    "DMux8Way",
+    "HalfAdder",
+    "FullAdder",
+    "Add16",
+    "Inc16",
+    "ALU",
+    "DFF",
+    "Bit",
+    "Register",
+    "ARegister",
+    "DRegister",
+    "PC",
+    "RAM8",
+    "RAM64",
+    "RAM512",
+    "RAM4K",
+    "RAM16K",
+];
+
+fn bit(n: u64) -> bool {
+    n & 1 == 1
+}
+
+fn word(n: u64) -> Arr16 {
+    bits(n as u16)
+}
+
+fn number(a: &[bool]) -> u64 {
+    value(a) as u64
+}
+
+/// A chip without state, computing its outputs with a function.
+struct Gate {
+    interface: Interface,
+    f: fn(&[u64]) -> Vec<u64>,
+}
+
+impl Chip for Gate {
+    fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    fn eval(&mut self, inputs: &[u64]) -> Vec<u64> {
+        (self.f)(inputs)
+    }
+}
+
+/// A clocked chip of the memory crate, with functions setting its pins
+/// from the inputs and reading its outputs.
+struct Sequential<C> {
+    interface: Interface,
+    chip: C,
+    set: fn(&mut C, &[u64]),
+    out: fn(&C) -> Vec<u64>,
+}
+
+impl<C: Clocked> Chip for Sequential<C> {
+    fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    fn eval(&mut self, inputs: &[u64]) -> Vec<u64> {
+        (self.set)(&mut self.chip, inputs);
+        (self.out)(&self.chip)
+    }
+
+    fn tick(&mut self, inputs: &[u64]) {
+        (self.set)(&mut self.chip, inputs);
+        self.chip.tick();
+    }
+
+    fn tock(&mut self) {
+        self.chip.tock();
+    }
+}
+
+fn gate(
+    name: &str,
+    inputs: &[(&str, usize)],
+    outputs: &[(&str, usize)],
+    f: fn(&[u64]) -> Vec<u64>,
+) -> Box<dyn Chip> {
+    Box::new(Gate {
+        interface: Interface::new(name, inputs, outputs, &[]),
+        f,
+    })
+}
+
+fn sequential<C: Clocked + 'static>(
+    interface: Interface,
+    chip: C,
+    set: fn(&mut C, &[u64]),
+    out: fn(&C) -> Vec<u64>,
+) -> Box<dyn Chip> {
+    Box::new(Sequential {
+        interface,
+        chip,
+        set,
+        out,
+    })
+}
+
+fn register(name: &str) -> Box<dyn Chip> {
+    sequential(
+        Interface::new(
+            name,
+            &[("in", 16), ("load", 1)],
+            &[("out", 16)],
+            &["in", "load"],
+        ),
+        Register::new(),
+        |r, i| {
+            r.i = word(i[0]);
+            r.load = bit(i[1]);
+        },
+        |r| vec![number(&r.out())],
+    )
+}
+
+/// RAM8 to RAM16K, with the address as the only combinational input.
+macro_rules! ram {
+    ($name:expr, $ram:ident, $width:expr) => {
+        sequential(
+            Interface::new(
+                $name,
+                &[("in", 16), ("load", 1), ("address", $width)],
+                &[("out", 16)],
+                &["in", "load"],
+            ),
+            $ram::new(),
+            |r, i| {
+                r.i = word(i[0]);
+                r.load = bit(i[1]);
+                r.address = bits(i[2] as u16);
+            },
+            |r| vec![number(&r.out())],
+        )
+    };
+}
+
+const AB: [(&str, usize); 2] = [("a", 1), ("b", 1)];
+const AB16: [(&str, usize); 2] = [("a", 16), ("b", 16)];
+const OUT: [(&str, usize); 1] = [("out", 1)];
+const OUT16: [(&str, usize); 1] = [("out", 16)];
+const SUM_CARRY: [(&str, usize); 2] = [("sum", 1), ("carry", 1)];
+
+/// A new instance of the built-in chip with that name.
+pub fn builtin(name: &str) -> Option<Box<dyn Chip>> {
+    let chip = match name {
+        "Nand" => gate(name, &AB, &OUT, |i| vec![nand(bit(i[0]), bit(i[1])) as u64]),
+        "Not" => gate(
+            name,
+            &[("in", 1)],
+            &OUT,
+            |i| vec![bg::not(bit(i[0])) as u64],
+        ),
+        "And" => gate(name, &AB, &OUT, |i| {
+            vec![bg::and(bit(i[0]), bit(i[1])) as u64]
+        }),
+        "Or" => gate(name, &AB, &OUT, |i| {
+            vec![bg::or(bit(i[0]), bit(i[1])) as u64]
+        }),
+        "Xor" => gate(name, &AB, &OUT, |i| {
+            vec![bg::xor(bit(i[0]), bit(i[1])) as u64]
+        }),
+        "Mux" => gate(name, &[("a", 1), ("b", 1), ("sel", 1)], &OUT, |i| {
+            vec![bg::mux(bit(i[0]), bit(i[1]), bit(i[2])) as u64]
+        }),
+        "DMux" => gate(name, &[("in", 1), ("sel", 1)], &AB, |i| {
+            // demux puts in on [1] when sel is 0
+            let [b, a] = bg::demux(bit(i[0]), bit(i[1]));
+            vec![a as u64, b as u64]
+        }),
+        "Not16" => gate(name, &[("in", 16)], &OUT16, |i| {
+            vec![number(&mbg::not16(word(i[0])))]
+        }),
+        "And16" => gate(name, &AB16, &OUT16, |i| {
+            vec![number(&mbg::and16(word(i[0]), word(i[1])))]
+        }),
+        "Or16" => gate(name, &AB16, &OUT16, |i| {
+            vec![number(&mbg::or16(word(i[0]), word(i[1])))]
+        }),
+        "Xor16" => gate(name, &AB16, &OUT16, |i| {
+            vec![number(&mbg::xor16(word(i[0]), word(i[1])))]
+        }),
+        "Mux16" => gate(name, &[("a", 16), ("b", 16), ("sel", 1)], &OUT16, |i| {
+            vec![number(&mbg::mux16(word(i[0]), word(i[1]), bit(i[2])))]
+        }),
+        "Or8Way" => gate(name, &[("in", 8)], &OUT, |i| {
+            vec![mwg::or8way(bits(i[0] as u16)) as u64]
+        }),
+        "And8Way" => gate(name, &[("in", 8)], &OUT, |i| {
+            vec![mwg::and8way(bits(i[0] as u16)) as u64]
+        }),
+        "Mux4Way16" => gate(
+            name,
+            &[("a", 16), ("b", 16), ("c", 16), ("d", 16), ("sel", 2)],
+            &OUT16,
+            |i| {
+                let out = mwg::mux4way16(
+                    word(i[0]),
+                    word(i[1]),
+                    word(i[2]),
+                    word(i[3]),
+                    bits(i[4] as u16),
+                );
+                vec![number(&out)]
+            },
+        ),
+        "Mux8Way16" => gate(
+            name,
+            &[
+                ("a", 16),
+                ("b", 16),
+                ("c", 16),
+                ("d", 16),
+                ("e", 16),
+                ("f", 16),
+                ("g", 16),
+                ("h", 16),
+                ("sel", 3),
+            ],
+            &OUT16,
+            |i| {
+                let out = mwg::mux8way16(
+                    word(i[0]),
+                    word(i[1]),
+                    word(i[2]),
+                    word(i[3]),
+                    word(i[4]),
+                    word(i[5]),
+                    word(i[6]),
+                    word(i[7]),
+                    bits(i[8] as u16),
+                );
+                vec![number(&out)]
+            },
+        ),
+        "DMux4Way" => gate(
+            name,
+            &[("in", 1), ("sel", 2)],
+            &[("a", 1), ("b", 1), ("c", 1), ("d", 1)],
+            |i| {
+                // demux4way puts in on [3] when sel is 0
+                let out = mwg::demux4way(bit(i[0]), bits(i[1] as u16));
+                out.iter().rev().map(|&b| b as u64).collect()
+            },
+        ),
+        "DMux8Way" => gate(
+            name,
+            &[("in", 1), ("sel", 3)],
+            &[
+                ("a", 1),
+                ("b", 1),
+                ("c", 1),
+                ("d", 1),
+                ("e", 1),
+                ("f", 1),
+                ("g", 1),
+                ("h", 1),
+            ],
+            |i| {
+                let out = mwg::demux8way(bit(i[0]), bits(i[1] as u16));
+                out.iter().rev().map(|&b| b as u64).collect()
+            },
+        ),
+        "HalfAdder" => gate(name, &AB, &SUM_CARRY, |i| {
+            let [carry, sum] = adders::half_adder(bit(i[0]), bit(i[1]));
+            vec![sum as u64, carry as u64]
+        }),
+        "FullAdder" => gate(name, &[("a", 1), ("b", 1), ("c", 1)], &SUM_CARRY, |i| {
+            let [carry, sum] = adders::full_adder(bit(i[0]), bit(i[1]), bit(i[2]));
+            vec![sum as u64, carry as u64]
+        }),
+        "Add16" => gate(name, &AB16, &OUT16, |i| {
+            vec![number(&adders::adder16(word(i[0]), word(i[1])))]
+        }),
+        "Inc16" => gate(name, &[("in", 16)], &OUT16, |i| {
+            vec![number(&adders::incrementer16(word(i[0])))]
+        }),
+        "ALU" => gate(
+            name,
+            &[
+                ("x", 16),
+                ("y", 16),
+                ("zx", 1),
+                ("nx", 1),
+                ("zy", 1),
+                ("ny", 1),
+                ("f", 1),
+                ("no", 1),
+            ],
+            &[("out", 16), ("zr", 1), ("ng", 1)],
+            |i| {
+                let (out, zr, ng) = alu::alu(
+                    word(i[0]),
+                    word(i[1]),
+                    bit(i[2]),
+                    bit(i[3]),
+                    bit(i[4]),
+                    bit(i[5]),
+                    bit(i[6]),
+                    bit(i[7]),
+                );
+                vec![number(&out), zr as u64, ng as u64]
+            },
+        ),
+        "DFF" => sequential(
+            Interface::new(name, &[("in", 1)], &OUT, &["in"]),
+            DFF::new(),
+            |d, i| d.i = bit(i[0]),
+            |d| vec![d.out() as u64],
+        ),
+        "Bit" => sequential(
+            Interface::new(name, &[("in", 1), ("load", 1)], &OUT, &["in", "load"]),
+            Bit::new(),
+            |b, i| {
+                b.i = bit(i[0]);
+                b.load = bit(i[1]);
+            },
+            |b| vec![b.out() as u64],
+        ),
+        "Register" | "ARegister" | "DRegister" => register(name),
+        "PC" => sequential(
+            Interface::new(
+                name,
+                &[("in", 16), ("load", 1), ("inc", 1), ("reset", 1)],
+                &OUT16,
+                &["in", "load", "inc", "reset"],
+            ),
+            PC::new(),
+            |pc, i| {
+                pc.i = word(i[0]);
+                pc.load = bit(i[1]);
+                pc.inc = bit(i[2]);
+                pc.reset = bit(i[3]);
+            },
+            |pc| vec![number(&pc.out())],
+        ),
+        "RAM8" => ram!(name, RAM8, 3),
+        "RAM64" => ram!(name, RAM64, 6),
+        "RAM512" => ram!(name, RAM512, 9),
+        "RAM4K" => ram!(name, RAM4K, 12),
+        "RAM16K" => ram!(name, RAM16K, 14),
+        _ => return None,
+    };
+    Some(chip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(name: &str, inputs: &[u64]) -> Vec<u64> {
+        builtin(name).unwrap().eval(inputs)
+    }
+
+    #[test]
+    fn test_builtins() {
+        for name in BUILTINS {
+            let chip = builtin(name).unwrap();
+            let interface = chip.interface();
+            assert_eq!(interface.name, name);
+            assert_eq!(interface.clocked.len(), interface.inputs.len());
+        }
+        assert!(builtin("Nor").is_none());
+    }
+
+    #[test]
+    fn test_gates() {
+        assert_eq!(eval("Nand", &[1, 1]), [0]);
+        assert_eq!(eval("Mux", &[1, 0, 0]), [1]);
+        assert_eq!(eval("DMux", &[1, 0]), [1, 0]);
+        assert_eq!(eval("DMux", &[1, 1]), [0, 1]);
+        assert_eq!(eval("Or8Way", &[0b0000_0100]), [1]);
+        assert_eq!(eval("Not16", &[0x00FF]), [0xFF00]);
+        assert_eq!(eval("Mux4Way16", &[1, 2, 3, 4, 2]), [3]);
+        assert_eq!(eval("Mux8Way16", &[1, 2, 3, 4, 5, 6, 7, 8, 6]), [7]);
+        assert_eq!(eval("DMux4Way", &[1, 1]), [0, 1, 0, 0]);
+        assert_eq!(eval("DMux8Way", &[1, 6]), [0, 0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(eval("FullAdder", &[1, 1, 1]), [1, 1]);
+        assert_eq!(eval("Add16", &[0xFFFF, 2]), [1]);
+        // x - y
+        assert_eq!(eval("ALU", &[3, 5, 0, 1, 0, 0, 1, 1]), [0xFFFE, 0, 1]);
+    }
+
+    #[test]
+    fn test_sequential() {
+        let mut ram = builtin("RAM64").unwrap();
+        assert_eq!(ram.interface().clocked, [true, true, false]);
+        ram.tick(&[1234, 1, 42]);
+        assert_eq!(ram.eval(&[1234, 1, 42]), [0]);
+        ram.tock();
+        assert_eq!(ram.eval(&[0, 0, 42]), [1234]);
+        // the address is read without the clock
+        assert_eq!(ram.eval(&[0, 0, 41]), [0]);
+
+        let mut pc = builtin("PC").unwrap();
+        for _ in 0..3 {
+            pc.tick(&[0, 0, 1, 0]);
+            pc.tock();
+        }
+        assert_eq!(pc.eval(&[0, 0, 0, 0]), [3]);
+    }
+}
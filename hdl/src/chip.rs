@@ -0,0 +1,89 @@
+/// A pin of a chip, `width` bits wide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub width: usize,
+}
+
+/// The pins of a chip, in the order of their values in Chip's methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub inputs: Vec<Pin>,
+    pub outputs: Vec<Pin>,
+    /// For each input, whether it is only read on tick, so that the
+    /// outputs don't depend on it until the next tock.
+    pub clocked: Vec<bool>,
+}
+
+impl Interface {
+    /// An interface from (name, width) pins and the names of the
+    /// clocked inputs.
+    pub fn new(
+        name: &str,
+        inputs: &[(&str, usize)],
+        outputs: &[(&str, usize)],
+        clocked: &[&str],
+    ) -> Interface {
+        let pins = |pins: &[(&str, usize)]| {
+            pins.iter()
+                .map(|&(name, width)| Pin {
+                    name: name.to_string(),
+                    width,
+                })
+                .collect()
+        };
+        Interface {
+            name: name.to_string(),
+            inputs: pins(inputs),
+            outputs: pins(outputs),
+            clocked: inputs.iter().map(|(n, _)| clocked.contains(n)).collect(),
+        }
+    }
+
+    pub fn input(&self, name: &str) -> Option<usize> {
+        self.inputs.iter().position(|p| p.name == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<usize> {
+        self.outputs.iter().position(|p| p.name == name)
+    }
+}
+
+/// A chip the HDL can use as a part: a built-in one implemented in Rust,
+/// or one described in HDL.
+///
+/// Pin values are numbers with bit i of the pin as bit i, the HDL
+/// numbering, in which in[0] is the least significant bit.
+pub trait Chip {
+    fn interface(&self) -> &Interface;
+
+    /// The outputs for the given inputs and the current state.
+    fn eval(&mut self, inputs: &[u64]) -> Vec<u64>;
+
+    /// The first half of a clock cycle: clocked chips read their inputs.
+    fn tick(&mut self, _inputs: &[u64]) {}
+
+    /// The second half: clocked chips update their state and outputs.
+    fn tock(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface() {
+        let bit = Interface::new("Bit", &[("in", 1), ("load", 1)], &[("out", 1)], &["in"]);
+        assert_eq!(bit.input("load"), Some(1));
+        assert_eq!(bit.output("in"), None);
+        assert_eq!(bit.clocked, [true, false]);
+        assert_eq!(
+            bit.outputs,
+            [Pin {
+                name: "out".to_string(),
+                width: 1
+            }]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::parser::MAX_WIDTH;
+use std::fmt;
+
+/// Where a token starts, lines and columns start at 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    UnterminatedComment,
+    Expected { expected: String, found: String },
+    UnknownChip(String),
+    UnknownBuiltin(String),
+    RecursiveChip(String),
+    UnknownPin { chip: String, pin: String },
+    DuplicatePin(String),
+    BadWidth(usize),
+    OutOfRange { pin: String, width: usize },
+    WidthMismatch { expected: usize, found: usize },
+    DrivenTwice(String),
+    ConstantOutput,
+    DrivesInput(String),
+    ReadsOutput(String),
+    Undriven(String),
+    SubscriptedInternal(String),
+    CombinationalLoop(String),
+    Unreadable { file: String, reason: String },
+    NoChip,
+    NotAnInput(String),
+    ComparisonFailure(usize),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+            ErrorKind::UnterminatedComment => write!(f, "comment is missing its closing '*/'"),
+            ErrorKind::Expected { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::UnknownChip(name) => {
+                write!(
+                    f,
+                    "no chip named '{}' in the library or the built-ins",
+                    name
+                )
+            }
+            ErrorKind::UnknownBuiltin(name) => write!(f, "no built-in chip named '{}'", name),
+            ErrorKind::RecursiveChip(name) => write!(f, "chip '{}' is a part of itself", name),
+            ErrorKind::UnknownPin { chip, pin } => {
+                write!(f, "chip '{}' has no pin '{}'", chip, pin)
+            }
+            ErrorKind::DuplicatePin(pin) => write!(f, "pin '{}' is declared twice", pin),
+            ErrorKind::BadWidth(width) => write!(
+                f,
+                "pin width must be 1 to {} bits, found {}",
+                MAX_WIDTH, width
+            ),
+            ErrorKind::OutOfRange { pin, width } => {
+                write!(f, "sub-bus out of the {} bits of pin '{}'", width, pin)
+            }
+            ErrorKind::WidthMismatch { expected, found } => {
+                write!(f, "expected a bus of {} bits, found {}", expected, found)
+            }
+            ErrorKind::DrivenTwice(pin) => write!(f, "pin '{}' is driven more than once", pin),
+            ErrorKind::ConstantOutput => write!(f, "an output can't be connected to a constant"),
+            ErrorKind::DrivesInput(pin) => {
+                write!(f, "input pin '{}' can't be driven by a part", pin)
+            }
+            ErrorKind::ReadsOutput(pin) => write!(f, "output pin '{}' can't feed a part", pin),
+            ErrorKind::Undriven(pin) => write!(f, "no part drives pin '{}'", pin),
+            ErrorKind::SubscriptedInternal(pin) => {
+                write!(f, "internal pin '{}' can't be subscripted", pin)
+            }
+            ErrorKind::CombinationalLoop(chip) => {
+                write!(f, "part '{}' is in a loop with no clocked chip", chip)
+            }
+            ErrorKind::Unreadable { file, reason } => {
+                write!(f, "can't read '{}': {}", file, reason)
+            }
+            ErrorKind::NoChip => write!(f, "no chip is loaded"),
+            ErrorKind::NotAnInput(pin) => write!(f, "'{}' is not an input of the chip", pin),
+            ErrorKind::ComparisonFailure(line) => write!(f, "comparison failure at line {}", line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub position: Position,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
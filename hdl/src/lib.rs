@@ -0,0 +1,327 @@
+pub mod ast;
+pub mod builtins;
+pub mod chip;
+pub mod error;
+pub mod netlist;
+pub mod parser;
+pub mod runner;
+pub mod script;
+pub mod tokenizer;
+
+use ast::{Body, ChipDef};
+use chip::Chip;
+use error::{Error, ErrorKind};
+use netlist::Netlist;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// An error with the name of the file it's in.
+pub type FileError = (String, Error);
+
+/// The chips described in .hdl files, by chip name. Chips that are in
+/// neither are taken from the built-ins.
+#[derive(Debug, Default)]
+pub struct Library {
+    chips: HashMap<String, Result<ChipDef, Vec<Error>>>,
+}
+
+impl Library {
+    pub fn new() -> Library {
+        Library::default()
+    }
+
+    /// The library of the .hdl files of a directory, named after the files.
+    pub fn from_dir(dir: &Path) -> std::io::Result<Library> {
+        let mut library = Library::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "hdl") {
+                let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                library.add(&name, &std::fs::read_to_string(&path)?);
+            }
+        }
+        Ok(library)
+    }
+
+    /// Adds the source of a chip, replacing any chip of the same name.
+    pub fn add(&mut self, name: &str, source: &str) {
+        self.chips.insert(name.to_string(), parser::parse(source));
+    }
+
+    /// A new instance of the chip with that name.
+    pub fn chip(&self, name: &str) -> Result<Box<dyn Chip>, Vec<FileError>> {
+        match self.load(name, &mut Vec::new())? {
+            Some(chip) => Ok(chip),
+            None => Err(vec![(
+                String::new(),
+                Error {
+                    position: Default::default(),
+                    kind: ErrorKind::UnknownChip(name.to_string()),
+                },
+            )]),
+        }
+    }
+
+    /// The chip, or None if there is no chip with that name. `stack` holds
+    /// the chips being loaded, which can't be parts of it.
+    fn load(
+        &self,
+        name: &str,
+        stack: &mut Vec<String>,
+    ) -> Result<Option<Box<dyn Chip>>, Vec<FileError>> {
+        let Some(def) = self.chips.get(name) else {
+            return Ok(builtins::builtin(name));
+        };
+        let file = format!("{}.hdl", name);
+        let in_file = |errors: Vec<Error>| -> Vec<FileError> {
+            errors.into_iter().map(|e| (file.clone(), e)).collect()
+        };
+        let def = def.as_ref().map_err(|e| in_file(e.clone()))?;
+
+        let parts = match &def.body {
+            Body::Builtin { position, name } => {
+                let chip = builtins::builtin(name).ok_or_else(|| {
+                    in_file(vec![Error {
+                        position: *position,
+                        kind: ErrorKind::UnknownBuiltin(name.clone()),
+                    }])
+                })?;
+                return Ok(Some(chip));
+            }
+            Body::Parts(parts) => parts,
+        };
+
+        stack.push(name.to_string());
+        let mut chips = Vec::new();
+        let mut errors = Vec::new();
+        for part in parts {
+            let kind = if stack.contains(&part.chip) {
+                ErrorKind::RecursiveChip(part.chip.clone())
+            } else {
+                match self.load(&part.chip, stack) {
+                    Ok(Some(chip)) => {
+                        chips.push(chip);
+                        continue;
+                    }
+                    Ok(None) => ErrorKind::UnknownChip(part.chip.clone()),
+                    Err(e) => {
+                        errors.extend(e);
+                        continue;
+                    }
+                }
+            };
+            errors.push((
+                file.clone(),
+                Error {
+                    position: part.position,
+                    kind,
+                },
+            ));
+        }
+        stack.pop();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let netlist = Netlist::new(def, chips).map_err(in_file)?;
+        Ok(Some(Box::new(netlist)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOT: &str = "CHIP Not { IN in; OUT out; PARTS: Nand(a=in, b=in, out=out); }";
+
+    const XOR: &str = "
+CHIP Xor {
+    IN a, b;
+    OUT out;
+
+    PARTS:
+    Nand(a=a, b=b, out=nand);
+    Nand(a=a, b=nand, out=x);
+    Nand(a=nand, b=b, out=y);
+    Nand(a=x, b=y, out=out);
+}";
+
+    fn library(chips: &[(&str, &str)]) -> Library {
+        let mut library = Library::new();
+        for (name, source) in chips {
+            library.add(name, source);
+        }
+        library
+    }
+
+    fn errors(library: &Library, name: &str) -> Vec<String> {
+        let Err(errors) = library.chip(name) else {
+            panic!("{} has no errors", name);
+        };
+        errors
+            .iter()
+            .map(|(file, e)| format!("{}:{}", file, e))
+            .collect()
+    }
+
+    #[test]
+    fn test_xor() {
+        let library = library(&[("Xor", XOR)]);
+        let mut xor = library.chip("Xor").unwrap();
+        assert_eq!(xor.interface().clocked, [false, false]);
+        for (a, b, out) in [(0, 0, 0), (0, 1, 1), (1, 0, 1), (1, 1, 0)] {
+            assert_eq!(xor.eval(&[a, b]), [out]);
+        }
+    }
+
+    #[test]
+    fn test_buses() {
+        // swaps the bytes, the high one going through Not16 twice
+        let swap = "CHIP Swap { IN in[16]; OUT out[16];
+            PARTS:
+            Not16(in[0..7]=in[8..15], in[8..15]=true, out[0..7]=lo);
+            Not16(in[0..7]=lo2, out[0..7]=out[0..7]);
+            Or16(a[0..7]=lo, out[0..7]=lo2);
+            Or16(a[0..7]=in[0..7], b[0..7]=false, out[0..7]=out[8..15]);
+        }";
+        let library = library(&[("Swap", swap)]);
+        let mut chip = library.chip("Swap").unwrap();
+        assert_eq!(chip.eval(&[0x12F0]), [0xF012]);
+    }
+
+    #[test]
+    fn test_nested() {
+        let mux = "CHIP Mux { IN a, b, sel; OUT out;
+            PARTS:
+            Not(in=sel, out=notsel);
+            Nand(a=a, b=notsel, out=x);
+            Nand(a=b, b=sel, out=y);
+            Nand(a=x, b=y, out=out);
+        }";
+        let library = library(&[("Mux", mux), ("Not", NOT)]);
+        let mut chip = library.chip("Mux").unwrap();
+        for k in 0..8 {
+            let (a, b, sel) = (k >> 2 & 1, k >> 1 & 1, k & 1);
+            let out = if sel == 1 { b } else { a };
+            assert_eq!(chip.eval(&[a, b, sel]), [out], "{}", k);
+        }
+    }
+
+    #[test]
+    fn test_sequential() {
+        let bit = "CHIP Bit { IN in, load; OUT out;
+            PARTS:
+            Mux(a=dff, b=in, sel=load, out=next);
+            DFF(in=next, out=dff, out=out);
+        }";
+        let register = "CHIP Register { IN in[16], load; OUT out[16];
+            PARTS:
+            Bit(in=in[0], load=load, out=out[0]);
+            Bit(in=in[15], load=load, out=out[15]);
+        }";
+        let library = library(&[("Bit", bit), ("Register", register)]);
+        let mut chip = library.chip("Register").unwrap();
+        assert_eq!(chip.interface().clocked, [true, true]);
+        chip.tick(&[0x8001, 1]);
+        assert_eq!(chip.eval(&[0x8001, 1]), [0]);
+        chip.tock();
+        assert_eq!(chip.eval(&[0, 0]), [0x8001]);
+        chip.tick(&[0, 0]);
+        chip.tock();
+        assert_eq!(chip.eval(&[0, 0]), [0x8001]);
+    }
+
+    #[test]
+    fn test_builtin() {
+        let dff = "CHIP DFF { IN in; OUT out; BUILTIN DFF; CLOCKED in; }";
+        let library = library(&[
+            ("DFF", dff),
+            ("Nor", "CHIP Nor { IN a; OUT out; BUILTIN Nor; }"),
+        ]);
+        assert_eq!(library.chip("DFF").unwrap().interface().clocked, [true]);
+        assert_eq!(
+            errors(&library, "Nor"),
+            ["Nor.hdl:1:35: no built-in chip named 'Nor'"]
+        );
+        assert!(library.chip("ALU").is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        let library = library(&[
+            (
+                "Loop",
+                "CHIP Loop { IN a; OUT out; PARTS: Loop(a=a, out=out); }",
+            ),
+            ("Parse", "CHIP Parse { IN a OUT out; }"),
+            (
+                "Uses",
+                "CHIP Uses { IN a; OUT out; PARTS: Parse(a=a); Nor(a=a); }",
+            ),
+        ]);
+        assert_eq!(
+            errors(&library, "Loop"),
+            ["Loop.hdl:1:35: chip 'Loop' is a part of itself"]
+        );
+        assert_eq!(
+            errors(&library, "Uses"),
+            [
+                "Parse.hdl:1:19: expected ';', found 'OUT'",
+                "Uses.hdl:1:47: no chip named 'Nor' in the library or the built-ins",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wiring_errors() {
+        let cases = [
+            (
+                "Nand(a=a, b=b, out=a);",
+                "1:57: input pin 'a' can't be driven by a part",
+            ),
+            (
+                "Nand(a=a, c=b, out=out);",
+                "1:48: chip 'Nand' has no pin 'c'",
+            ),
+            ("Nand(a=a, b=x, out=out);", "1:50: no part drives pin 'x'"),
+            (
+                "Nand(a=a, b=out, out=out);",
+                "1:50: output pin 'out' can't feed a part",
+            ),
+            (
+                "Nand(a=a, b=b, out=true);",
+                "1:53: an output can't be connected to a constant",
+            ),
+            (
+                "Nand(a=a, b=b, out=out); Not(in=a, out=out);",
+                "1:77: pin 'out' is driven more than once",
+            ),
+            (
+                "Not16(in[0]=a, out=x); Not(in=x, out=out);",
+                "1:68: expected a bus of 1 bits, found 16",
+            ),
+            (
+                "Not16(in[16]=a, out=w);",
+                "1:44: sub-bus out of the 16 bits of pin 'in'",
+            ),
+            (
+                "Not(in=a, out=w); Not(in=w[0], out=out);",
+                "1:63: internal pin 'w' can't be subscripted",
+            ),
+            (
+                "Not(in=x, out=y); Not(in=y, out=x); Not(in=a, out=out);",
+                "1:38: part 'Not' is in a loop with no clocked chip",
+            ),
+        ];
+        for (parts, error) in cases {
+            let source = format!("CHIP Chip {{ IN a, b; OUT out; PARTS: {} }}", parts);
+            let library = library(&[("Chip", &source)]);
+            assert_eq!(
+                errors(&library, "Chip"),
+                [format!("Chip.hdl:{}", error)],
+                "{}",
+                parts
+            );
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use crate::ast::{Body, ChipDef, Outer, PinRef};
+use crate::chip::{Chip, Interface, Pin};
+use crate::error::{Error, ErrorKind, Position};
+use std::collections::HashMap;
+
+// Every bit of every pin is a wire, an index into the wire values.
+// Wires 0 and 1 hold false and true, for constants and unconnected inputs.
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+
+/// A part of a netlist and the wires it reads and drives.
+struct Instance {
+    position: Position,
+    chip: Box<dyn Chip>,
+    /// The wire of each bit of each input, LSB first.
+    inputs: Vec<Vec<usize>>,
+    /// (output, bit, wire) for each output bit connected to a wire.
+    outputs: Vec<(usize, usize, usize)>,
+}
+
+impl Instance {
+    fn input_values(&self, wires: &[bool]) -> Vec<u64> {
+        self.inputs.iter().map(|pin| read(pin, wires)).collect()
+    }
+}
+
+fn read(pin: &[usize], wires: &[bool]) -> u64 {
+    pin.iter()
+        .enumerate()
+        .map(|(k, &w)| (wires[w] as u64) << k)
+        .sum()
+}
+
+/// A chip described in HDL, its parts wired together.
+pub struct Netlist {
+    interface: Interface,
+    wires: Vec<bool>,
+    inputs: Vec<Vec<usize>>,
+    outputs: Vec<Vec<usize>>,
+    /// In an order where a part comes after the parts its combinational
+    /// inputs depend on.
+    parts: Vec<Instance>,
+}
+
+/// Where a pin name refers to in the chip being elaborated.
+enum Scope {
+    Input(usize),
+    Output(usize),
+    Internal(Vec<usize>),
+}
+
+struct Builder<'a> {
+    def: &'a ChipDef,
+    wires: usize,
+    inputs: Vec<Vec<usize>>,
+    outputs: Vec<Vec<usize>>,
+    internals: HashMap<&'a str, Vec<usize>>,
+    driven: Vec<bool>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Builder<'a> {
+    fn wires(&mut self, width: usize) -> Vec<usize> {
+        let wires = (self.wires..self.wires + width).collect();
+        self.wires += width;
+        self.driven.resize(self.wires, false);
+        wires
+    }
+
+    fn error(&mut self, position: Position, kind: ErrorKind) {
+        self.errors.push(Error { position, kind });
+    }
+
+    fn scope(&self, name: &str) -> Option<Scope> {
+        let def = self.def;
+        if let Some(k) = def.inputs.iter().position(|p| p.name == name) {
+            Some(Scope::Input(k))
+        } else if let Some(k) = def.outputs.iter().position(|p| p.name == name) {
+            Some(Scope::Output(k))
+        } else {
+            self.internals.get(name).cloned().map(Scope::Internal)
+        }
+    }
+
+    /// The bits of a pin `width` bits wide that a reference selects.
+    fn range(&mut self, pin: &PinRef, width: usize) -> Option<std::ops::RangeInclusive<usize>> {
+        match pin.range {
+            None => Some(0..=width - 1),
+            Some((start, end)) if start <= end && end < width => Some(start..=end),
+            Some(_) => {
+                self.error(
+                    pin.position,
+                    ErrorKind::OutOfRange {
+                        pin: pin.name.clone(),
+                        width,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    fn check_width(&mut self, position: Position, expected: usize, found: usize) -> bool {
+        if expected != found {
+            self.error(position, ErrorKind::WidthMismatch { expected, found });
+        }
+        expected == found
+    }
+
+    /// The wires of a pin of the chip, sliced by the reference.
+    fn chip_pin(&mut self, pin: &PinRef, wires: &[usize]) -> Option<Vec<usize>> {
+        let range = self.range(pin, wires.len())?;
+        Some(wires[range].to_vec())
+    }
+
+    /// Connects the part outputs, defining the internal pins.
+    fn drive(&mut self, part: &'a crate::ast::Part, chip: &dyn Chip) -> Vec<(usize, usize, usize)> {
+        let interface = chip.interface();
+        let mut outputs = Vec::new();
+        for connection in &part.connections {
+            let inner = &connection.inner;
+            let Some(k) = interface.output(&inner.name) else {
+                continue;
+            };
+            let Some(bits) = self.range(inner, interface.outputs[k].width) else {
+                continue;
+            };
+            let outer = match &connection.outer {
+                Outer::Pin(outer) => outer,
+                Outer::Constant(_) => {
+                    self.error(inner.position, ErrorKind::ConstantOutput);
+                    continue;
+                }
+            };
+            let wires = match self.scope(&outer.name) {
+                Some(Scope::Input(_)) => {
+                    self.error(outer.position, ErrorKind::DrivesInput(outer.name.clone()));
+                    continue;
+                }
+                Some(Scope::Output(o)) => {
+                    let pin = self.outputs[o].clone();
+                    let Some(wires) = self.chip_pin(outer, &pin) else {
+                        continue;
+                    };
+                    wires
+                }
+                Some(Scope::Internal(_)) => {
+                    self.error(outer.position, ErrorKind::DrivenTwice(outer.name.clone()));
+                    continue;
+                }
+                None if outer.range.is_some() => {
+                    let error = ErrorKind::SubscriptedInternal(outer.name.clone());
+                    self.error(outer.position, error);
+                    continue;
+                }
+                None => {
+                    let wires = self.wires(bits.clone().count());
+                    self.internals.insert(&outer.name, wires.clone());
+                    wires
+                }
+            };
+            if !self.check_width(outer.position, bits.clone().count(), wires.len()) {
+                continue;
+            }
+            for (bit, wire) in bits.zip(wires) {
+                if self.driven[wire] {
+                    self.error(outer.position, ErrorKind::DrivenTwice(outer.name.clone()));
+                    break;
+                }
+                self.driven[wire] = true;
+                outputs.push((k, bit, wire));
+            }
+        }
+        outputs
+    }
+
+    /// Connects the part inputs, unconnected bits reading false.
+    fn read(&mut self, part: &crate::ast::Part, chip: &dyn Chip) -> Vec<Vec<usize>> {
+        let interface = chip.interface();
+        let mut inputs: Vec<Vec<usize>> = interface
+            .inputs
+            .iter()
+            .map(|p| vec![FALSE; p.width])
+            .collect();
+        for connection in &part.connections {
+            let inner = &connection.inner;
+            let Some(k) = interface.input(&inner.name) else {
+                if interface.output(&inner.name).is_none() {
+                    let error = ErrorKind::UnknownPin {
+                        chip: part.chip.clone(),
+                        pin: inner.name.clone(),
+                    };
+                    self.error(inner.position, error);
+                }
+                continue;
+            };
+            let Some(bits) = self.range(inner, interface.inputs[k].width) else {
+                continue;
+            };
+            let wires = match &connection.outer {
+                Outer::Constant(value) => {
+                    vec![if *value { TRUE } else { FALSE }; bits.clone().count()]
+                }
+                Outer::Pin(outer) => {
+                    let wires = match self.scope(&outer.name) {
+                        Some(Scope::Input(i)) => {
+                            let pin = self.inputs[i].clone();
+                            self.chip_pin(outer, &pin)
+                        }
+                        Some(Scope::Output(_)) => {
+                            let error = ErrorKind::ReadsOutput(outer.name.clone());
+                            self.error(outer.position, error);
+                            None
+                        }
+                        Some(Scope::Internal(_)) if outer.range.is_some() => {
+                            let error = ErrorKind::SubscriptedInternal(outer.name.clone());
+                            self.error(outer.position, error);
+                            None
+                        }
+                        Some(Scope::Internal(wires)) => Some(wires),
+                        None => {
+                            self.error(outer.position, ErrorKind::Undriven(outer.name.clone()));
+                            None
+                        }
+                    };
+                    let Some(wires) = wires else {
+                        continue;
+                    };
+                    if !self.check_width(outer.position, bits.clone().count(), wires.len()) {
+                        continue;
+                    }
+                    wires
+                }
+            };
+            for (bit, wire) in bits.zip(wires) {
+                inputs[k][bit] = wire;
+            }
+        }
+        inputs
+    }
+}
+
+impl Netlist {
+    /// Wires the parts of a chip, given an instance of the chip of each
+    /// of its parts, in order.
+    pub fn new(def: &ChipDef, chips: Vec<Box<dyn Chip>>) -> Result<Netlist, Vec<Error>> {
+        let Body::Parts(parts) = &def.body else {
+            panic!("chip {} is built-in", def.name);
+        };
+        let mut builder = Builder {
+            def,
+            wires: 2,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            internals: HashMap::new(),
+            driven: vec![true; 2],
+            errors: Vec::new(),
+        };
+
+        let mut names = Vec::new();
+        for pin in def.inputs.iter().chain(&def.outputs) {
+            if names.contains(&&pin.name) {
+                builder.error(pin.position, ErrorKind::DuplicatePin(pin.name.clone()));
+            }
+            names.push(&pin.name);
+        }
+        for pin in &def.inputs {
+            let wires = builder.wires(pin.width);
+            for &w in &wires {
+                builder.driven[w] = true;
+            }
+            builder.inputs.push(wires);
+        }
+        for pin in &def.outputs {
+            let wires = builder.wires(pin.width);
+            builder.outputs.push(wires);
+        }
+
+        let outputs: Vec<_> = parts
+            .iter()
+            .zip(&chips)
+            .map(|(part, chip)| builder.drive(part, chip.as_ref()))
+            .collect();
+        let inputs: Vec<_> = parts
+            .iter()
+            .zip(&chips)
+            .map(|(part, chip)| builder.read(part, chip.as_ref()))
+            .collect();
+        if !builder.errors.is_empty() {
+            return Err(builder.errors);
+        }
+
+        let instances: Vec<Instance> = parts
+            .iter()
+            .zip(chips)
+            .zip(inputs.into_iter().zip(outputs))
+            .map(|((part, chip), (inputs, outputs))| Instance {
+                position: part.position,
+                chip,
+                inputs,
+                outputs,
+            })
+            .collect();
+        let parts = sort(instances, builder.wires).map_err(|part| {
+            vec![Error {
+                position: part.position,
+                kind: ErrorKind::CombinationalLoop(part.chip.interface().name.clone()),
+            }]
+        })?;
+
+        let clocked = clocked(&parts, &builder.inputs, &builder.outputs, builder.wires);
+        let pins = |pins: &[crate::ast::PinDec]| {
+            pins.iter()
+                .map(|p| Pin {
+                    name: p.name.clone(),
+                    width: p.width,
+                })
+                .collect()
+        };
+        let mut wires = vec![false; builder.wires];
+        wires[TRUE] = true;
+        Ok(Netlist {
+            interface: Interface {
+                name: def.name.clone(),
+                inputs: pins(&def.inputs),
+                outputs: pins(&def.outputs),
+                clocked,
+            },
+            wires,
+            inputs: builder.inputs,
+            outputs: builder.outputs,
+            parts,
+        })
+    }
+
+    fn set(&mut self, inputs: &[u64]) {
+        for (pin, value) in self.inputs.iter().zip(inputs) {
+            for (k, &w) in pin.iter().enumerate() {
+                self.wires[w] = value >> k & 1 == 1;
+            }
+        }
+        for part in &mut self.parts {
+            let values = part.chip.eval(&part.input_values(&self.wires));
+            for &(output, bit, wire) in &part.outputs {
+                self.wires[wire] = values[output] >> bit & 1 == 1;
+            }
+        }
+    }
+}
+
+/// The combinational inputs of a part, which its outputs follow right away.
+fn combinational(part: &Instance) -> impl Iterator<Item = usize> + '_ {
+    let clocked = &part.chip.interface().clocked;
+    part.inputs
+        .iter()
+        .zip(clocked)
+        .filter(|(_, clocked)| !**clocked)
+        .flat_map(|(pin, _)| pin.iter().copied())
+}
+
+/// Sorts the parts so that each comes after the parts driving its
+/// combinational inputs, or returns a part in a loop.
+fn sort(mut parts: Vec<Instance>, wires: usize) -> Result<Vec<Instance>, Instance> {
+    let mut driver = vec![None; wires];
+    for (k, part) in parts.iter().enumerate() {
+        for &(_, _, wire) in &part.outputs {
+            driver[wire] = Some(k);
+        }
+    }
+    let dependencies: Vec<Vec<usize>> = parts
+        .iter()
+        .map(|part| {
+            let mut parts: Vec<usize> = combinational(part).filter_map(|w| driver[w]).collect();
+            parts.sort();
+            parts.dedup();
+            parts
+        })
+        .collect();
+
+    let mut order = Vec::new();
+    let mut done = vec![false; parts.len()];
+    while order.len() < parts.len() {
+        let ready: Vec<usize> = (0..parts.len())
+            .filter(|&k| !done[k] && dependencies[k].iter().all(|&d| done[d]))
+            .collect();
+        if ready.is_empty() {
+            let k = done.iter().position(|d| !d).unwrap();
+            return Err(parts.swap_remove(k));
+        }
+        for k in ready {
+            done[k] = true;
+            order.push(k);
+        }
+    }
+
+    let mut parts: Vec<Option<Instance>> = parts.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .map(|k| parts[k].take().unwrap())
+        .collect())
+}
+
+/// Whether each input is clocked: no combinational path leads from it to
+/// an output, given the parts in order.
+fn clocked(
+    parts: &[Instance],
+    inputs: &[Vec<usize>],
+    outputs: &[Vec<usize>],
+    wires: usize,
+) -> Vec<bool> {
+    inputs
+        .iter()
+        .map(|pin| {
+            let mut reached = vec![false; wires];
+            for &w in pin {
+                reached[w] = true;
+            }
+            for part in parts {
+                if combinational(part).any(|w| reached[w]) {
+                    for &(_, _, wire) in &part.outputs {
+                        reached[wire] = true;
+                    }
+                }
+            }
+            !outputs.iter().flatten().any(|&w| reached[w])
+        })
+        .collect()
+}
+
+impl Chip for Netlist {
+    fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    fn eval(&mut self, inputs: &[u64]) -> Vec<u64> {
+        self.set(inputs);
+        self.outputs
+            .iter()
+            .map(|pin| read(pin, &self.wires))
+            .collect()
+    }
+
+    fn tick(&mut self, inputs: &[u64]) {
+        self.set(inputs);
+        for part in &mut self.parts {
+            let values = part.input_values(&self.wires);
+            part.chip.tick(&values);
+        }
+    }
+
+    fn tock(&mut self) {
+        for part in &mut self.parts {
+            part.chip.tock();
+        }
+    }
+}
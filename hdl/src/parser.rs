@@ -0,0 +1,359 @@
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Position};
+use crate::tokenizer::{Token, tokenize};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// The widest bus a pin can be, as in the course.
+pub const MAX_WIDTH: usize = 16;
+
+/// A recursive-descent parser with one token of lookahead.
+pub struct Parser {
+    tokens: Vec<(Position, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<(Position, Token)>) -> Parser {
+        Parser { tokens, index: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, t)| t)
+    }
+
+    /// Where the next token starts, or the last one at the end of the file.
+    fn position(&self) -> Position {
+        self.tokens
+            .get(self.index)
+            .or(self.tokens.last())
+            .map(|&(p, _)| p)
+            .unwrap_or_default()
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T> {
+        let found = match self.peek() {
+            Some(token) => format!("'{}'", token),
+            None => "end of file".to_string(),
+        };
+        Err(Error {
+            position: self.position(),
+            kind: ErrorKind::Expected {
+                expected: expected.to_string(),
+                found,
+            },
+        })
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Symbol(c))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Identifier(word)) if word == keyword)
+    }
+
+    fn symbol(&mut self, c: char) -> Result<()> {
+        if self.is_symbol(c) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", c))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.is_keyword(keyword) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", keyword))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.index += 1;
+                Ok(name)
+            }
+            _ => self.error("a name"),
+        }
+    }
+
+    fn number(&mut self) -> Result<usize> {
+        match self.peek() {
+            Some(&Token::Number(n)) => {
+                self.index += 1;
+                Ok(n)
+            }
+            _ => self.error("a number"),
+        }
+    }
+
+    /// 'CHIP' name '{' (('IN' | 'OUT') pins ';')* body '}'
+    pub fn chip(&mut self) -> Result<ChipDef> {
+        self.keyword("CHIP")?;
+        let position = self.position();
+        let name = self.identifier()?;
+        self.symbol('{')?;
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        loop {
+            if self.is_keyword("IN") {
+                self.index += 1;
+                inputs.extend(self.pins()?);
+            } else if self.is_keyword("OUT") {
+                self.index += 1;
+                outputs.extend(self.pins()?);
+            } else {
+                break;
+            }
+        }
+        let body = self.body()?;
+        self.symbol('}')?;
+        if self.peek().is_some() {
+            return self.error("end of file");
+        }
+        Ok(ChipDef {
+            position,
+            name,
+            inputs,
+            outputs,
+            body,
+        })
+    }
+
+    /// pin (',' pin)* ';' where pin is name ('[' width ']')?
+    fn pins(&mut self) -> Result<Vec<PinDec>> {
+        let mut pins = Vec::new();
+        loop {
+            let position = self.position();
+            let name = self.identifier()?;
+            let mut width = 1;
+            if self.is_symbol('[') {
+                self.index += 1;
+                let position = self.position();
+                width = self.number()?;
+                if !(1..=MAX_WIDTH).contains(&width) {
+                    return Err(Error {
+                        position,
+                        kind: ErrorKind::BadWidth(width),
+                    });
+                }
+                self.symbol(']')?;
+            }
+            pins.push(PinDec {
+                position,
+                name,
+                width,
+            });
+            if !self.is_symbol(',') {
+                break;
+            }
+            self.index += 1;
+        }
+        self.symbol(';')?;
+        Ok(pins)
+    }
+
+    /// 'PARTS' ':' part* | 'BUILTIN' name ';' ('CLOCKED' names ';')?
+    fn body(&mut self) -> Result<Body> {
+        if self.is_keyword("BUILTIN") {
+            self.index += 1;
+            let position = self.position();
+            let name = self.identifier()?;
+            self.symbol(';')?;
+            if self.is_keyword("CLOCKED") {
+                self.index += 1;
+                self.identifier()?;
+                while self.is_symbol(',') {
+                    self.index += 1;
+                    self.identifier()?;
+                }
+                self.symbol(';')?;
+            }
+            return Ok(Body::Builtin { position, name });
+        }
+
+        self.keyword("PARTS")?;
+        self.symbol(':')?;
+        let mut parts = Vec::new();
+        while !self.is_symbol('}') && self.peek().is_some() {
+            parts.push(self.part()?);
+        }
+        Ok(Body::Parts(parts))
+    }
+
+    /// chip '(' connection (',' connection)* ')' ';'
+    fn part(&mut self) -> Result<Part> {
+        let position = self.position();
+        let chip = self.identifier()?;
+        self.symbol('(')?;
+        let mut connections = Vec::new();
+        if !self.is_symbol(')') {
+            loop {
+                connections.push(self.connection()?);
+                if !self.is_symbol(',') {
+                    break;
+                }
+                self.index += 1;
+            }
+        }
+        self.symbol(')')?;
+        self.symbol(';')?;
+        Ok(Part {
+            position,
+            chip,
+            connections,
+        })
+    }
+
+    /// pin '=' (pin | 'true' | 'false')
+    fn connection(&mut self) -> Result<Connection> {
+        let inner = self.pin_ref()?;
+        self.symbol('=')?;
+        let outer = if self.is_keyword("true") || self.is_keyword("false") {
+            let value = self.is_keyword("true");
+            self.index += 1;
+            Outer::Constant(value)
+        } else {
+            Outer::Pin(self.pin_ref()?)
+        };
+        Ok(Connection { inner, outer })
+    }
+
+    /// name ('[' i ('..' j)? ']')?
+    fn pin_ref(&mut self) -> Result<PinRef> {
+        let position = self.position();
+        let name = self.identifier()?;
+        let mut range = None;
+        if self.is_symbol('[') {
+            self.index += 1;
+            let start = self.number()?;
+            let mut end = start;
+            if self.peek() == Some(&Token::Range) {
+                self.index += 1;
+                end = self.number()?;
+            }
+            self.symbol(']')?;
+            range = Some((start, end));
+        }
+        Ok(PinRef {
+            position,
+            name,
+            range,
+        })
+    }
+}
+
+/// Parses the source of a .hdl file.
+pub fn parse(source: &str) -> std::result::Result<ChipDef, Vec<Error>> {
+    let tokens = tokenize(source)?;
+    Parser::new(tokens).chip().map_err(|e| vec![e])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MUX: &str = "
+// Multiplexor: out = a if sel == 0, b otherwise
+CHIP Mux {
+    IN a, b, sel;
+    OUT out;
+
+    PARTS:
+    Not(in=sel, out=notsel);
+    And(a=a, b=notsel, out=x);
+    And(a=b, b=sel, out=y);
+    Or(a=x, b=y, out=out);
+}";
+
+    fn pin(line: usize, column: usize, name: &str, range: Option<(usize, usize)>) -> PinRef {
+        PinRef {
+            position: Position { line, column },
+            name: name.to_string(),
+            range,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let chip = parse(MUX).unwrap();
+        assert_eq!(chip.name, "Mux");
+        let names: Vec<&str> = chip.inputs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "sel"]);
+        assert_eq!(chip.outputs[0].width, 1);
+        let Body::Parts(parts) = &chip.body else {
+            panic!("{:?}", chip.body);
+        };
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[3].chip, "Or");
+        assert_eq!(
+            parts[0].connections[1],
+            Connection {
+                inner: pin(8, 17, "out", None),
+                outer: Outer::Pin(pin(8, 21, "notsel", None)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_buses() {
+        let source = "CHIP Split { IN in[16]; OUT lo[8], hi[8];
+            PARTS: Or8Way(in=in[8..15], out[0]=x, out=y); And16(a[0..7]=in[3], b=true); }";
+        let chip = parse(source).unwrap();
+        assert_eq!(chip.inputs[0].width, 16);
+        let Body::Parts(parts) = &chip.body else {
+            panic!("{:?}", chip.body);
+        };
+        let connections = &parts[0].connections;
+        assert_eq!(
+            connections[0].outer,
+            Outer::Pin(pin(2, 30, "in", Some((8, 15))))
+        );
+        assert_eq!(connections[1].inner.range, Some((0, 0)));
+        assert_eq!(parts[1].connections[1].outer, Outer::Constant(true));
+    }
+
+    #[test]
+    fn test_builtin() {
+        let source = "CHIP DFF { IN in; OUT out; BUILTIN DFF; CLOCKED in; }";
+        let chip = parse(source).unwrap();
+        assert_eq!(
+            chip.body,
+            Body::Builtin {
+                position: Position {
+                    line: 1,
+                    column: 36
+                },
+                name: "DFF".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = parse("CHIP And { IN a b; }").unwrap_err();
+        assert_eq!(errors[0].to_string(), "1:17: expected ';', found 'b'");
+        let errors = parse("CHIP Not { IN in; OUT out; PARTS: Nand(a=in b=in); }").unwrap_err();
+        assert_eq!(errors[0].to_string(), "1:45: expected ')', found 'b'");
+        let errors = parse("CHIP Not { IN in; OUT out; PARTS:").unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "1:33: expected '}', found end of file"
+        );
+        let errors = parse("CHIP A { IN a[0]; OUT out; BUILTIN Not; }").unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "1:15: pin width must be 1 to 16 bits, found 0"
+        );
+        let errors = parse("CHIP A { IN a[70]; OUT out; BUILTIN Not; }").unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "1:15: pin width must be 1 to 16 bits, found 70"
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use crate::chip::Chip;
+use crate::error::{Error, ErrorKind, Position};
+use crate::script::{Column, Command, Comparison, Condition, Format, Operand, Statement};
+use crate::{FileError, Library};
+use std::io;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Reads the files a script names, its compare file.
+pub type Reader = Box<dyn Fn(&str) -> io::Result<String>>;
+
+/// What an output list column shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Time,
+    Input(usize),
+    Output(usize),
+}
+
+/// Runs test scripts the way the course's hardware simulator does, writing
+/// the same output and comparing it line by line with the compare file.
+pub struct Runner {
+    library: Library,
+    read: Reader,
+    chip: Option<Box<dyn Chip>>,
+    inputs: Vec<u64>,
+    outputs: Vec<u64>,
+    /// Cycles since the chip was loaded, and whether it is between a tick
+    /// and a tock.
+    time: u64,
+    ticked: bool,
+    columns: Vec<(Column, Value)>,
+    compare: Option<Vec<String>>,
+    /// The lines written so far, headers included.
+    lines: usize,
+    /// The file output-file named, if any.
+    pub output_file: Option<String>,
+    pub output: String,
+    /// The text of the last echo, until clear-echo.
+    pub echo: String,
+}
+
+/// `width` bits set.
+fn mask(width: usize) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// A value as the simulator sees it: 16-bit pins hold signed numbers.
+fn signed(value: u64, width: usize) -> i64 {
+    if width == 16 {
+        value as u16 as i16 as i64
+    } else {
+        value as i64
+    }
+}
+
+/// The header of a column, its name centered and cut to fit.
+fn header(column: &Column) -> String {
+    let space = column.left + column.width + column.right;
+    let name: String = column.name.chars().take(space).collect();
+    let left = (space - name.len()) / 2;
+    let right = space - left - name.len();
+    format!("{}{}{}|", " ".repeat(left), name, " ".repeat(right))
+}
+
+/// A value in its column, keeping its last digits if it doesn't fit.
+fn cell(column: &Column, text: &str) -> String {
+    let text = &text[text.len().saturating_sub(column.width)..];
+    let (left, right) = match column.format {
+        Format::String => (column.left, column.right + column.width - text.len()),
+        _ => (column.left + column.width - text.len(), column.right),
+    };
+    format!("{}{}{}|", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Whether a line matches a line of a compare file, where '*' matches
+/// any character.
+fn matches(line: &str, expected: &str) -> bool {
+    line.len() == expected.len()
+        && line
+            .chars()
+            .zip(expected.chars())
+            .all(|(c, e)| e == '*' || c == e)
+}
+
+impl Runner {
+    pub fn new(library: Library, read: Reader) -> Runner {
+        Runner {
+            library,
+            read,
+            chip: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            time: 0,
+            ticked: false,
+            columns: Vec::new(),
+            compare: None,
+            lines: 0,
+            output_file: None,
+            output: String::new(),
+            echo: String::new(),
+        }
+    }
+
+    /// A runner with the chips and files of a directory.
+    pub fn from_dir(dir: &Path) -> io::Result<Runner> {
+        let library = Library::from_dir(dir)?;
+        let dir = PathBuf::from(dir);
+        Ok(Runner::new(
+            library,
+            Box::new(move |file| std::fs::read_to_string(dir.join(file))),
+        ))
+    }
+
+    /// Runs the source of a test script, named `file` in errors, up to its
+    /// end or its first error. A comparison failure is an error too.
+    pub fn run(&mut self, file: &str, source: &str) -> std::result::Result<(), Vec<FileError>> {
+        let in_file = |e: Error| (file.to_string(), e);
+        let statements = crate::script::parse(source)
+            .map_err(|errors| errors.into_iter().map(in_file).collect::<Vec<_>>())?;
+        match self.statements(&statements) {
+            Ok(()) => Ok(()),
+            Err(Failure::Chip(errors)) => Err(errors),
+            Err(Failure::Script(e)) => Err(vec![in_file(e)]),
+        }
+    }
+
+    fn chip(&self, position: Position) -> Result<&dyn Chip> {
+        let error = Error {
+            position,
+            kind: ErrorKind::NoChip,
+        };
+        self.chip.as_deref().ok_or(error)
+    }
+
+    fn value(&self, position: Position, name: &str) -> Result<Value> {
+        if name == "time" {
+            return Ok(Value::Time);
+        }
+        let interface = self.chip(position)?.interface();
+        if let Some(k) = interface.input(name) {
+            Ok(Value::Input(k))
+        } else if let Some(k) = interface.output(name) {
+            Ok(Value::Output(k))
+        } else {
+            let kind = ErrorKind::UnknownPin {
+                chip: interface.name.clone(),
+                pin: name.to_string(),
+            };
+            Err(Error { position, kind })
+        }
+    }
+
+    /// The value and width of a pin.
+    fn get(&self, value: Value) -> (u64, usize) {
+        let interface = self.chip.as_ref().unwrap().interface();
+        match value {
+            Value::Time => (self.time, 64),
+            Value::Input(k) => (self.inputs[k], interface.inputs[k].width),
+            Value::Output(k) => (self.outputs[k], interface.outputs[k].width),
+        }
+    }
+
+    fn operand(&mut self, position: Position, operand: &Operand) -> Result<i64> {
+        match operand {
+            Operand::Number(n) => Ok(*n),
+            Operand::Pin(name) => {
+                let (value, width) = self.get(self.value(position, name)?);
+                Ok(signed(value, width))
+            }
+        }
+    }
+
+    fn condition(&mut self, position: Position, condition: &Condition) -> Result<bool> {
+        let left = self.operand(position, &condition.left)?;
+        let right = self.operand(position, &condition.right)?;
+        Ok(match condition.comparison {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::Greater => left > right,
+            Comparison::LessEqual => left <= right,
+            Comparison::GreaterEqual => left >= right,
+        })
+    }
+
+    fn eval(&mut self) {
+        let chip = self.chip.as_mut().unwrap();
+        self.outputs = chip.eval(&self.inputs);
+    }
+
+    /// Writes a line of output, comparing it if there is a compare file.
+    fn write(&mut self, position: Position, line: String) -> Result<()> {
+        self.output.push_str(&line);
+        self.output.push('\n');
+        self.lines += 1;
+        if let Some(compare) = &self.compare {
+            let expected = compare.get(self.lines - 1);
+            if !expected.is_some_and(|e| matches(&line, e)) {
+                let kind = ErrorKind::ComparisonFailure(self.lines);
+                return Err(Error { position, kind });
+            }
+        }
+        Ok(())
+    }
+
+    fn statements(&mut self, statements: &[Statement]) -> std::result::Result<(), Failure> {
+        for statement in statements {
+            self.statement(statement)?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> std::result::Result<(), Failure> {
+        let position = statement.position;
+        match &statement.command {
+            Command::Load(file) => {
+                let name = file.strip_suffix(".hdl").unwrap_or(file);
+                let chip = self.library.chip(name).map_err(Failure::Chip)?;
+                let interface = chip.interface();
+                self.inputs = vec![0; interface.inputs.len()];
+                self.outputs = vec![0; interface.outputs.len()];
+                self.chip = Some(chip);
+                self.time = 0;
+                self.ticked = false;
+            }
+            Command::OutputFile(file) => self.output_file = Some(file.clone()),
+            Command::CompareTo(file) => {
+                let text = (self.read)(file).map_err(|e| Error {
+                    position,
+                    kind: ErrorKind::Unreadable {
+                        file: file.clone(),
+                        reason: e.to_string(),
+                    },
+                })?;
+                self.compare = Some(text.lines().map(|l| l.to_string()).collect());
+            }
+            Command::OutputList(columns) => {
+                self.columns = columns
+                    .iter()
+                    .map(|c| Ok((c.clone(), self.value(c.position, &c.name)?)))
+                    .collect::<Result<_>>()?;
+                let line: String = columns.iter().map(header).collect();
+                self.write(position, format!("|{}", line))?;
+            }
+            Command::Set { pin, value } => {
+                let Value::Input(k) = self.value(position, pin)? else {
+                    let kind = ErrorKind::NotAnInput(pin.clone());
+                    return Err(Error { position, kind }.into());
+                };
+                let width = self.chip(position)?.interface().inputs[k].width;
+                self.inputs[k] = *value as u64 & mask(width);
+            }
+            Command::Eval => {
+                self.chip(position)?;
+                self.eval();
+            }
+            Command::Tick => {
+                self.chip(position)?;
+                self.chip.as_mut().unwrap().tick(&self.inputs);
+                self.eval();
+                self.ticked = true;
+            }
+            Command::Tock => {
+                self.chip(position)?;
+                self.chip.as_mut().unwrap().tock();
+                self.eval();
+                self.time += 1;
+                self.ticked = false;
+            }
+            Command::Output => {
+                self.chip(position)?;
+                let mut line = String::from("|");
+                for (column, value) in &self.columns {
+                    let (n, width) = self.get(*value);
+                    let text = match (column.format, value) {
+                        (_, Value::Time) if self.ticked => format!("{}+", n),
+                        (Format::Binary, _) => format!("{:0w$b}", n, w = column.width),
+                        (Format::Hex, _) => format!("{:0w$X}", n, w = column.width),
+                        _ => signed(n, width).to_string(),
+                    };
+                    line.push_str(&cell(column, &text));
+                }
+                self.write(position, line)?;
+            }
+            Command::Echo(text) => self.echo = text.clone(),
+            Command::ClearEcho => self.echo.clear(),
+            Command::Repeat { count, body } => {
+                for _ in 0..*count {
+                    self.statements(body)?;
+                }
+            }
+            Command::While { condition, body } => {
+                while self.condition(position, condition)? {
+                    self.statements(body)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a script stopped: an error in it, or in the chip it loads.
+enum Failure {
+    Script(Error),
+    Chip(Vec<FileError>),
+}
+
+impl From<Error> for Failure {
+    fn from(e: Error) -> Failure {
+        Failure::Script(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn runner(chips: &[(&str, &str)], files: &[(&str, &str)]) -> Runner {
+        let mut library = Library::new();
+        for (name, source) in chips {
+            library.add(name, source);
+        }
+        let files: HashMap<String, String> = files
+            .iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect();
+        Runner::new(
+            library,
+            Box::new(move |file| {
+                files
+                    .get(file)
+                    .cloned()
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+            }),
+        )
+    }
+
+    fn errors(result: std::result::Result<(), Vec<FileError>>) -> Vec<String> {
+        result
+            .unwrap_err()
+            .iter()
+            .map(|(file, e)| format!("{}:{}", file, e))
+            .collect()
+    }
+
+    #[test]
+    fn test_alu() {
+        // the first lines of the course's ALU test, on the built-in ALU
+        let script = "load ALU.hdl,
+output-file ALU.out,
+output-list x%B1.16.1 y%B1.16.1 zx%D1.1.1 nx%D1.1.1 zy%D1.1.1
+            ny%D1.1.1 f%D1.1.1 no%D1.1.1 out%B1.16.1 zr%D1.1.1 ng%D1.1.1;
+
+set x %B0000000000000000,  // x = 0
+set y %B1111111111111111;  // y = -1
+
+set zx 1, set nx 0, set zy 1, set ny 0, set f 1, set no 0, eval, output;
+set zx 1, set nx 1, set zy 1, set ny 1, set f 1, set no 1, eval, output;
+set zx 1, set nx 1, set zy 1, set ny 0, set f 1, set no 0, eval, output;";
+        let mut runner = runner(&[], &[]);
+        runner.run("ALU.tst", script).unwrap();
+        assert_eq!(runner.output_file.as_deref(), Some("ALU.out"));
+        assert_eq!(
+            runner.output,
+            "\
+|        x         |        y         |zx |nx |zy |ny | f |no |       out        |zr |ng |
+| 0000000000000000 | 1111111111111111 | 1 | 0 | 1 | 0 | 1 | 0 | 0000000000000000 | 1 | 0 |
+| 0000000000000000 | 1111111111111111 | 1 | 1 | 1 | 1 | 1 | 1 | 0000000000000001 | 0 | 0 |
+| 0000000000000000 | 1111111111111111 | 1 | 1 | 1 | 0 | 1 | 0 | 1111111111111111 | 0 | 1 |
+"
+        );
+    }
+
+    #[test]
+    fn test_clock() {
+        let script = "load Bit.hdl, output-list time%S1.4.1 in%B3.1.3 load%B3.1.3 out%B3.1.3;
+set in 1, set load 1, tick, output; tock, output;
+set load 0, repeat 2 { tick, tock; } output;";
+        let mut runner = runner(&[], &[]);
+        runner.run("Bit.tst", script).unwrap();
+        assert_eq!(
+            runner.output,
+            "\
+| time |  in   | load  |  out  |
+| 0+   |   1   |   1   |   0   |
+| 1    |   1   |   1   |   1   |
+| 3    |   1   |   0   |   1   |
+"
+        );
+    }
+
+    #[test]
+    fn test_formats() {
+        let script = "load Inc16.hdl,
+output-list in%D2.6.2 out%X1.4.1 out%B0.4.0 out%S0.3.0;
+set in -1, eval, output;
+set in %X7FFF, eval, output;
+while out > 0 { set in 5, eval, output; }";
+        let mut runner = runner(&[], &[]);
+        runner.run("Inc16.tst", script).unwrap();
+        assert_eq!(
+            runner.output,
+            "\
+|    in    | out  |out |out|
+|      -1  | 0000 |0000|0  |
+|   32767  | 8000 |0000|768|
+"
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let not = "CHIP Not { IN in; OUT out; PARTS: Nand(a=in, b=in, out=out); }";
+        let script = "load Not.hdl, compare-to Not.cmp, output-list in%B1.1.1 out%B1.1.1;
+set in 0, eval, output;
+set in 1, eval, output;";
+        let cmp = "|in |out|\r\n| 0 | 1 |\r\n| 1 | * |\r\n";
+        let mut passing = runner(&[("Not", not)], &[("Not.cmp", cmp)]);
+        passing.run("Not.tst", script).unwrap();
+
+        let cmp = "|in |out|\n| 0 | 1 |\n| 1 | 1 |\n";
+        let mut runner = runner(&[("Not", not)], &[("Not.cmp", cmp)]);
+        assert_eq!(
+            errors(runner.run("Not.tst", script)),
+            ["Not.tst:3:17: comparison failure at line 3"]
+        );
+        assert_eq!(runner.output, "|in |out|\n| 0 | 1 |\n| 1 | 0 |\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut runner = runner(&[("Bad", "CHIP Bad { IN a; OUT b; PARTS: Nor(); }")], &[]);
+        assert_eq!(
+            errors(runner.run("Bad.tst", "load Bad.hdl;")),
+            ["Bad.hdl:1:32: no chip named 'Nor' in the library or the built-ins"]
+        );
+        assert_eq!(
+            errors(runner.run("Not.tst", "eval;")),
+            ["Not.tst:1:1: no chip is loaded"]
+        );
+        assert_eq!(
+            errors(runner.run("Not.tst", "load Not.hdl, set out 1;")),
+            ["Not.tst:1:15: 'out' is not an input of the chip"]
+        );
+        assert_eq!(
+            errors(runner.run("Not.tst", "output-list x%B1.1.1;")),
+            ["Not.tst:1:13: chip 'Not' has no pin 'x'"]
+        );
+        assert_eq!(
+            errors(runner.run("Not.tst", "compare-to Not.cmp;")),
+            ["Not.tst:1:1: can't read 'Not.cmp': entity not found"]
+        );
+    }
+
+    #[test]
+    fn test_chips() {
+        // every test script of the chips directory passes
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("chips");
+        let mut scripts: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "tst"))
+            .collect();
+        scripts.sort();
+        assert!(!scripts.is_empty());
+        for path in scripts {
+            let mut runner = Runner::from_dir(&dir).unwrap();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let file = path.file_name().unwrap().to_string_lossy();
+            if let Err(errors) = runner.run(&file, &source) {
+                panic!("{:?}\n{}", errors, runner.output);
+            }
+        }
+    }
+}
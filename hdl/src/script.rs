@@ -0,0 +1,477 @@
+use crate::error::{Error, ErrorKind, Position};
+use crate::tokenizer::Cursor;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// How a column of the output list prints its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `%B`, in binary, zero-padded.
+    Binary,
+    /// `%D`, in decimal, signed for 16-bit pins, right-aligned.
+    Decimal,
+    /// `%X`, in hexadecimal, zero-padded.
+    Hex,
+    /// `%S`, as text, left-aligned.
+    String,
+}
+
+/// `name%Fleft.width.right`, a value `width` characters wide between
+/// `left` and `right` spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub position: Position,
+    pub name: String,
+    pub format: Format,
+    pub left: usize,
+    pub width: usize,
+    pub right: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Pin(String),
+    Number(i64),
+}
+
+/// `left comparison right`, as in `while out <> 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Load(String),
+    OutputFile(String),
+    CompareTo(String),
+    OutputList(Vec<Column>),
+    Set {
+        pin: String,
+        value: i64,
+    },
+    Eval,
+    Tick,
+    Tock,
+    Output,
+    Echo(String),
+    ClearEcho,
+    Repeat {
+        count: usize,
+        body: Vec<Statement>,
+    },
+    While {
+        condition: Condition,
+        body: Vec<Statement>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub position: Position,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Anything up to a space or a symbol: commands, file names, pins,
+    /// values and formats.
+    Word(String),
+    Symbol(char),
+    /// A "quoted" string, without its quotes.
+    Text(String),
+}
+
+const SYMBOLS: &str = ",;{}";
+
+fn tokenize(source: &str) -> std::result::Result<Vec<(Position, Token)>, Vec<Error>> {
+    let mut cursor = Cursor::new(source);
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    while let Some(c) = cursor.peek(0) {
+        let position = cursor.position;
+        if c.is_whitespace() {
+            cursor.bump();
+        } else if c == '/' && matches!(cursor.peek(1), Some('/' | '*')) {
+            if !cursor.comment() {
+                errors.push(Error {
+                    position,
+                    kind: ErrorKind::UnterminatedComment,
+                });
+            }
+        } else if SYMBOLS.contains(c) {
+            cursor.bump();
+            tokens.push((position, Token::Symbol(c)));
+        } else if c == '"' {
+            cursor.bump();
+            let text = cursor.take_while(|c| c != '"' && c != '\n');
+            if cursor.bump() != Some('"') {
+                errors.push(Error {
+                    position,
+                    kind: ErrorKind::Expected {
+                        expected: "a closing '\"'".to_string(),
+                        found: "end of line".to_string(),
+                    },
+                });
+            }
+            tokens.push((position, Token::Text(text)));
+        } else {
+            let word = cursor.take_while(|c| !c.is_whitespace() && !SYMBOLS.contains(c));
+            tokens.push((position, Token::Word(word)));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+/// A value as written in `set` and conditions: `%B101`, `%XFF`, `%D-1`
+/// or `-1`.
+pub fn parse_value(word: &str) -> Option<i64> {
+    let (digits, radix) = match word.get(..2) {
+        Some("%B") => (&word[2..], 2),
+        Some("%X") => (&word[2..], 16),
+        Some("%D") => (&word[2..], 10),
+        _ => (word, 10),
+    };
+    i64::from_str_radix(digits, radix).ok()
+}
+
+/// `name%F1.16.1`, or None if it isn't one.
+fn parse_column(position: Position, word: &str) -> Option<Column> {
+    let (name, format) = word.split_once('%')?;
+    let mut chars = format.chars();
+    let format = match chars.next()? {
+        'B' => Format::Binary,
+        'D' => Format::Decimal,
+        'X' => Format::Hex,
+        'S' => Format::String,
+        _ => return None,
+    };
+    let numbers: Vec<usize> = chars
+        .as_str()
+        .split('.')
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    let [left, width, right] = numbers[..] else {
+        return None;
+    };
+    Some(Column {
+        position,
+        name: name.to_string(),
+        format,
+        left,
+        width,
+        right,
+    })
+}
+
+struct Parser {
+    tokens: Vec<(Position, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> Position {
+        self.tokens
+            .get(self.index)
+            .or(self.tokens.last())
+            .map(|&(p, _)| p)
+            .unwrap_or_default()
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T> {
+        let found = match self.peek() {
+            Some(Token::Word(word)) => format!("'{}'", word),
+            Some(Token::Symbol(c)) => format!("'{}'", c),
+            Some(Token::Text(text)) => format!("\"{}\"", text),
+            None => "end of file".to_string(),
+        };
+        Err(Error {
+            position: self.position(),
+            kind: ErrorKind::Expected {
+                expected: expected.to_string(),
+                found,
+            },
+        })
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Symbol(c))
+    }
+
+    fn symbol(&mut self, c: char) -> Result<()> {
+        if self.is_symbol(c) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", c))
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> Result<String> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.index += 1;
+                Ok(word)
+            }
+            _ => self.error(expected),
+        }
+    }
+
+    fn value(&mut self) -> Result<i64> {
+        let word = self.word("a value")?;
+        parse_value(&word).map_or_else(
+            || {
+                self.index -= 1;
+                self.error("a value")
+            },
+            Ok,
+        )
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        let word = self.word("a pin or a value")?;
+        Ok(match parse_value(&word) {
+            Some(n) => Operand::Number(n),
+            None => Operand::Pin(word),
+        })
+    }
+
+    /// statement* up to the end of the file or a '}'.
+    fn statements(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = Vec::new();
+        while self.peek().is_some() && !self.is_symbol('}') {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    /// '{' statement* '}'
+    fn block(&mut self) -> Result<Vec<Statement>> {
+        self.symbol('{')?;
+        let body = self.statements()?;
+        self.symbol('}')?;
+        Ok(body)
+    }
+
+    /// command (',' | ';'), blocks ending in '}' instead.
+    fn statement(&mut self) -> Result<Statement> {
+        let position = self.position();
+        let command = match self.word("a command")?.as_str() {
+            "load" => Command::Load(self.word("a file name")?),
+            "output-file" => Command::OutputFile(self.word("a file name")?),
+            "compare-to" => Command::CompareTo(self.word("a file name")?),
+            "output-list" => {
+                let mut columns = Vec::new();
+                while let Some(Token::Word(word)) = self.peek() {
+                    let Some(column) = parse_column(self.position(), word) else {
+                        return self.error("a column like 'out%B1.16.1'");
+                    };
+                    columns.push(column);
+                    self.index += 1;
+                }
+                Command::OutputList(columns)
+            }
+            "set" => Command::Set {
+                pin: self.word("a pin")?,
+                value: self.value()?,
+            },
+            "eval" => Command::Eval,
+            "tick" => Command::Tick,
+            "tock" => Command::Tock,
+            "output" => Command::Output,
+            "echo" => match self.peek() {
+                Some(Token::Text(text)) => {
+                    let text = text.clone();
+                    self.index += 1;
+                    Command::Echo(text)
+                }
+                _ => return self.error("a quoted text"),
+            },
+            "clear-echo" => Command::ClearEcho,
+            "repeat" => {
+                let count = self.word("a number of times")?;
+                let Ok(count) = count.parse() else {
+                    self.index -= 1;
+                    return self.error("a number of times");
+                };
+                let body = self.block()?;
+                return Ok(Statement {
+                    position,
+                    command: Command::Repeat { count, body },
+                });
+            }
+            "while" => {
+                let left = self.operand()?;
+                let comparison = match self.word("a comparison")?.as_str() {
+                    "=" => Comparison::Equal,
+                    "<>" => Comparison::NotEqual,
+                    "<" => Comparison::Less,
+                    ">" => Comparison::Greater,
+                    "<=" => Comparison::LessEqual,
+                    ">=" => Comparison::GreaterEqual,
+                    _ => {
+                        self.index -= 1;
+                        return self.error("a comparison");
+                    }
+                };
+                let right = self.operand()?;
+                let condition = Condition {
+                    left,
+                    comparison,
+                    right,
+                };
+                let body = self.block()?;
+                return Ok(Statement {
+                    position,
+                    command: Command::While { condition, body },
+                });
+            }
+            _ => {
+                self.index -= 1;
+                return self.error("a command");
+            }
+        };
+        if !self.is_symbol(',') && !self.is_symbol(';') {
+            return self.error("',' or ';'");
+        }
+        self.index += 1;
+        Ok(Statement { position, command })
+    }
+}
+
+/// Parses the source of a .tst test script.
+pub fn parse(source: &str) -> std::result::Result<Vec<Statement>, Vec<Error>> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, index: 0 };
+    let statements = parser.statements().map_err(|e| vec![e])?;
+    if parser.peek().is_some() {
+        return Err(vec![parser.error::<()>("a command").unwrap_err()]);
+    }
+    Ok(statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let source = "
+// Tests Bit.hdl
+load Bit.hdl,
+output-list time%S1.4.1 in%B2.1.2 out%D1.6.1;
+set in %B1, set load 1,
+repeat 2 {
+    tick, output;
+    tock, output;
+}
+while out <> -1 { set in %XFFFF, eval; }
+echo \"done\";";
+        let statements = parse(source).unwrap();
+        let commands: Vec<&Command> = statements.iter().map(|s| &s.command).collect();
+        assert_eq!(commands[0], &Command::Load("Bit.hdl".to_string()));
+        let Command::OutputList(columns) = commands[1] else {
+            panic!("{:?}", commands[1]);
+        };
+        assert_eq!(
+            columns[2],
+            Column {
+                position: Position {
+                    line: 4,
+                    column: 35
+                },
+                name: "out".to_string(),
+                format: Format::Decimal,
+                left: 1,
+                width: 6,
+                right: 1,
+            }
+        );
+        assert_eq!(
+            commands[3],
+            &Command::Set {
+                pin: "load".to_string(),
+                value: 1
+            }
+        );
+        let Command::Repeat { count: 2, body } = commands[4] else {
+            panic!("{:?}", commands[4]);
+        };
+        assert_eq!(body.len(), 4);
+        let Command::While { condition, body } = commands[5] else {
+            panic!("{:?}", commands[5]);
+        };
+        assert_eq!(
+            condition,
+            &Condition {
+                left: Operand::Pin("out".to_string()),
+                comparison: Comparison::NotEqual,
+                right: Operand::Number(-1),
+            }
+        );
+        assert_eq!(
+            body[0].command,
+            Command::Set {
+                pin: "in".to_string(),
+                value: 0xFFFF
+            }
+        );
+        assert_eq!(commands[6], &Command::Echo("done".to_string()));
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(parse_value("%B0101"), Some(5));
+        assert_eq!(parse_value("%X7fff"), Some(0x7FFF));
+        assert_eq!(parse_value("%D-32768"), Some(-32768));
+        assert_eq!(parse_value("12"), Some(12));
+        assert_eq!(parse_value("%B102"), None);
+        assert_eq!(parse_value("out"), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| parse(source).unwrap_err()[0].to_string();
+        assert_eq!(
+            error("load Not.hdl\neval;"),
+            "2:1: expected ',' or ';', found 'eval'"
+        );
+        assert_eq!(
+            error("output-list a%B1.1;"),
+            "1:13: expected a column like 'out%B1.16.1', found 'a%B1.1'"
+        );
+        assert_eq!(error("set a b;"), "1:7: expected a value, found 'b'");
+        assert_eq!(
+            error("repeat { tick; }"),
+            "1:8: expected a number of times, found '{'"
+        );
+        assert_eq!(error("tick; }"), "1:7: expected a command, found '}'");
+        assert_eq!(
+            error("echo \"open"),
+            "1:6: expected a closing '\"', found end of line"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::error::{Error, ErrorKind, Position};
+use std::fmt;
+
+pub const SYMBOLS: &str = "{}()[];,:=";
+
+/// Keywords (CHIP, IN, true...) are identifiers, the parser tells them apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Identifier(String),
+    Number(usize),
+    Symbol(char),
+    /// `..` in sub-buses.
+    Range,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Identifier(s) => write!(f, "{}", s),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Symbol(c) => write!(f, "{}", c),
+            Token::Range => write!(f, ".."),
+        }
+    }
+}
+
+/// Characters of a source with the position of the next one.
+pub(crate) struct Cursor {
+    chars: Vec<char>,
+    index: usize,
+    pub position: Position,
+}
+
+impl Cursor {
+    pub fn new(source: &str) -> Cursor {
+        Cursor {
+            chars: source.chars().collect(),
+            index: 0,
+            position: Position { line: 1, column: 1 },
+        }
+    }
+
+    pub fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    pub fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.index += 1;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    pub fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek(0).filter(|&c| f(c)) {
+            text.push(c);
+            self.bump();
+        }
+        text
+    }
+
+    /// Skips a // or /* */ comment, false if it isn't closed.
+    pub fn comment(&mut self) -> bool {
+        if self.peek(1) == Some('/') {
+            self.take_while(|c| c != '\n');
+            return true;
+        }
+        self.bump();
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('*') if self.peek(0) == Some('/') => {
+                    self.bump();
+                    return true;
+                }
+                Some(_) => {}
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Splits HDL source into tokens, dropping whitespace and comments.
+///
+/// Every error in the source is returned, not only the first one.
+pub fn tokenize(source: &str) -> Result<Vec<(Position, Token)>, Vec<Error>> {
+    let mut cursor = Cursor::new(source);
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    while let Some(c) = cursor.peek(0) {
+        let position = cursor.position;
+        let error = |kind| Error { position, kind };
+
+        if c.is_whitespace() {
+            cursor.bump();
+        } else if c == '/' && matches!(cursor.peek(1), Some('/' | '*')) {
+            if !cursor.comment() {
+                errors.push(error(ErrorKind::UnterminatedComment));
+            }
+        } else if c == '.' && cursor.peek(1) == Some('.') {
+            cursor.bump();
+            cursor.bump();
+            tokens.push((position, Token::Range));
+        } else if c.is_ascii_digit() {
+            let digits = cursor.take_while(|c| c.is_ascii_digit());
+            match digits.parse() {
+                Ok(n) => tokens.push((position, Token::Number(n))),
+                Err(_) => errors.push(error(ErrorKind::Expected {
+                    expected: "a bit index".to_string(),
+                    found: digits,
+                })),
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let word = cursor.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+            tokens.push((position, Token::Identifier(word)));
+        } else if SYMBOLS.contains(c) {
+            cursor.bump();
+            tokens.push((position, Token::Symbol(c)));
+        } else {
+            cursor.bump();
+            errors.push(error(ErrorKind::UnexpectedCharacter(c)));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect()
+    }
+
+    fn ident(s: &str) -> Token {
+        Token::Identifier(s.to_string())
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("/** Sums */ Add16(a[0..7]=x, b=true); // carry"),
+            vec![
+                ident("Add16"),
+                Token::Symbol('('),
+                ident("a"),
+                Token::Symbol('['),
+                Token::Number(0),
+                Token::Range,
+                Token::Number(7),
+                Token::Symbol(']'),
+                Token::Symbol('='),
+                ident("x"),
+                Token::Symbol(','),
+                ident("b"),
+                Token::Symbol('='),
+                ident("true"),
+                Token::Symbol(')'),
+                Token::Symbol(';'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = tokenize("IN a;\n  OUT b#;\n/* open").unwrap_err();
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            [
+                "2:8: unexpected character '#'",
+                "3:1: comment is missing its closing '*/'",
+            ]
+        );
+    }
+}